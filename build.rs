@@ -7,11 +7,11 @@ fn main() {
 
     tonic_build::compile_protos("lib/protos/rippled/xrp_ledger.proto").unwrap();
     tonic_build::configure()
-        .build_server(false)
         .compile(
             &[
                 "lib/protos/ilp/ilp_over_http_service.proto",
                 "lib/protos/ilp/balance_service.proto",
+                "lib/protos/ilp/account_service.proto",
             ],
             &["lib/protos/ilp/"],
         )
@@ -4296,63 +4296,166 @@ module.exports = {
 /***/ 138:
 /***/ (function(module, __unusedexports, __webpack_require__) {
 
-const {Wallet, Signer, Serializer, Utils} = __webpack_require__(139);
-const {AccountAddress,CurrencyAmount,XRPDropsAmount} = __webpack_require__(75);
-const {Payment, Transaction} = __webpack_require__(70);
+const {Wallet, Utils} = __webpack_require__(139);
 const rippleCodec = __webpack_require__(78);
+const {ValidationError} = __webpack_require__(995);
 
-function signTransaction(transaction, wallet) {
-
-  wallet = new Wallet(wallet.public_key, wallet.private_key);
-
-  const t = transaction.transaction;
-  const value = t.payment.xrp_amount.drops;
-  const destination = t.payment.destination;
-  const fee = t.fee.drops;
-  const sequence = t.sequence;
-  const account = t.account;
+function isSet(value) {
+  return value !== undefined && value !== null;
+}
 
-  const paymentAmount = new XRPDropsAmount();
-  paymentAmount.setDrops(value);
+function toClassicAddress(address) {
+  if (!Utils.isValidXAddress(address)) {
+    return { address: address, tag: undefined };
+  }
+  return Utils.decodeXAddress(address);
+}
 
-  const currencyAmount = new CurrencyAmount();
-  currencyAmount.setXrpAmount(paymentAmount);
+function amountToJSON(amount) {
+  if (isSet(amount.xrp_amount)) {
+    return `${amount.xrp_amount.drops}`;
+  }
+  const issued = amount.issued_currency_amount;
+  return {
+    currency: issued.currency,
+    issuer: toClassicAddress(issued.issuer).address,
+    value: issued.value
+  };
+}
 
-  const destinationAddress = new AccountAddress();
-  destinationAddress.setAddress(destination);
+function pathElementToJSON(element) {
+  const e = {};
+  if (isSet(element.account)) {
+    e.account = element.account;
+  }
+  if (isSet(element.currency)) {
+    e.currency = element.currency;
+  }
+  if (isSet(element.issuer)) {
+    e.issuer = element.issuer;
+  }
+  return e;
+}
 
-  const payment = new Payment();
-  payment.setDestination(destinationAddress);
-  payment.setAmount(currencyAmount);
+function memoToJSON(memo) {
+  const m = {};
+  if (isSet(memo.memo_type)) {
+    m.MemoType = memo.memo_type;
+  }
+  if (isSet(memo.memo_format)) {
+    m.MemoFormat = memo.memo_format;
+  }
+  if (isSet(memo.memo_data)) {
+    m.MemoData = memo.memo_data;
+  }
+  return { Memo: m };
+}
 
-  const transactionFee = new XRPDropsAmount();
-  transactionFee.setDrops(fee);
+function transactionToJSON(t) {
+  const account = toClassicAddress(t.account);
+  // Accounts cannot have a tag.
+  if (isSet(account.tag)) {
+    throw new ValidationError('Invalid Account');
+  }
+  const destination = toClassicAddress(t.payment.destination);
+  const json = {
+    TransactionType: 'Payment',
+    Account: account.address,
+    Destination: destination.address,
+    Amount: amountToJSON(t.payment.amount),
+    Fee: `${t.fee.drops}`,
+    Sequence: t.sequence,
+    SigningPubKey: t.signing_public_key_hex,
+    LastLedgerSequence: t.last_ledger_sequence,
+  };
+  if (isSet(destination.tag)) {
+    json.DestinationTag = destination.tag;
+  }
+  if (isSet(t.payment.destination_tag)) {
+    if (isSet(json.DestinationTag) && json.DestinationTag !== t.payment.destination_tag) {
+      throw new ValidationError('Destination Tag Conflict');
+    }
+    json.DestinationTag = t.payment.destination_tag;
+  }
+  if (isSet(t.payment.invoice_id)) {
+    json.InvoiceID = t.payment.invoice_id;
+  }
+  if (isSet(t.payment.send_max)) {
+    json.SendMax = amountToJSON(t.payment.send_max);
+  }
+  if (isSet(t.payment.deliver_min)) {
+    json.DeliverMin = amountToJSON(t.payment.deliver_min);
+  }
+  if (isSet(t.payment.paths) && t.payment.paths.length > 0) {
+    json.Paths = t.payment.paths.map(path => path.map(pathElementToJSON));
+  }
+  if (isSet(t.flags) && t.flags !== 0) {
+    json.Flags = t.flags;
+  }
+  if (isSet(t.source_tag)) {
+    json.SourceTag = t.source_tag;
+  }
+  if (isSet(t.memos) && t.memos.length > 0) {
+    json.Memos = t.memos.map(memoToJSON);
+  }
+  return json;
+}
 
-  const sender = new AccountAddress();
-  sender.setAddress(account);
+function signTransaction(transaction, wallet) {
 
-  const trx = new Transaction();
-  trx.setAccount(sender);
-  trx.setFee(transactionFee);
-  trx.setSequence(sequence);
-  trx.setPayment(payment);
-  trx.setSigningPublicKey(Utils.toBytes(t.signing_public_key_hex));
-  trx.setLastLedgerSequence(t.last_ledger_sequence);
+  wallet = new Wallet(wallet.public_key, wallet.private_key);
 
-  const a = Signer.signTransaction(trx, wallet);
-  if (a) {
+  const trx = transactionToJSON(transaction.transaction);
+  const signature = wallet.sign(rippleCodec.encodeForSigning(trx));
+  if (signature) {
+    trx.TxnSignature = signature;
+    const result = rippleCodec.encode(trx);
     const r = {
-      transaction: Serializer.transactionToJSON(trx),
-      result: Utils.toHex(a)
+      transaction: trx,
+      result,
+      hash: Utils.transactionBlobToTransactionHash(result)
     };
     return r;
   } else {
-    throw Error('Invalid Parameters');
+    throw new ValidationError('Invalid Parameters');
   }
 }
 
+function decodeSignedTransaction(transactionBlobHex) {
+  let transaction;
+  try {
+    transaction = rippleCodec.decode(transactionBlobHex);
+  } catch (e) {
+    throw new ValidationError('Invalid Transaction');
+  }
+  const unsigned = Object.assign({}, transaction);
+  delete unsigned.TxnSignature;
+  let signatureValid = false;
+  let signingAddress = null;
+  if (isSet(transaction.SigningPubKey) && isSet(transaction.TxnSignature)) {
+    try {
+      const wallet = new Wallet(transaction.SigningPubKey, null, false);
+      signingAddress = Utils.decodeXAddress(wallet.getAddress()).address;
+      signatureValid = wallet.verify(
+        rippleCodec.encodeForSigning(unsigned),
+        transaction.TxnSignature,
+        transaction.SigningPubKey
+      );
+    } catch (e) {
+      signatureValid = false;
+    }
+  }
+  return {
+    transaction,
+    hash: Utils.transactionBlobToTransactionHash(transactionBlobHex),
+    signing_address: signingAddress,
+    signature_valid: signatureValid === true
+  };
+}
+
 const signerExports = {
   signTransaction,
+  decodeSignedTransaction,
 };
 
 module.exports = signerExports;
@@ -38832,6 +38935,16 @@ module.exports = {
 /***/ (function(module, __unusedexports, __webpack_require__) {
 
 const {Wallet} = __webpack_require__(139);
+const addressCodec = __webpack_require__(280);
+const elliptic = __webpack_require__(873);
+const crypto = __webpack_require__(373);
+const bip32 = __webpack_require__(941);
+const bip39 = __webpack_require__(830);
+const rippleKeyPair = __webpack_require__(638);
+const {ValidationError} = __webpack_require__(995);
+
+const Ed25519 = elliptic.eddsa('ed25519');
+const Secp256k1 = elliptic.ec('secp256k1');
 
 function generateRandomWallet(genOptions) {
     const w = Wallet.generateRandomWallet(genOptions.entropy, genOptions.test);
@@ -38839,7 +38952,7 @@ function generateRandomWallet(genOptions) {
         w.wallet = enhance(w.wallet);
         return w;
     } else {
-        throw Error('Invalid Entropy');
+        throw new ValidationError('Invalid Entropy');
     }
 }
 
@@ -38848,7 +38961,7 @@ function generateWalletFromMnemonic(genOptions) {
     if (w) {
         return enhance(w);
     } else {
-        throw Error('Invalid mnemonic');
+        throw new ValidationError('Invalid mnemonic');
     }
 }
 
@@ -38857,8 +38970,239 @@ function generateWalletFromSeed(genOptions) {
     if (w) {
         return enhance(w);
     } else {
-        throw Error('Invalid Seed');
+        throw new ValidationError('Invalid Seed');
+    }
+}
+
+// Derives the wallets at m/44'/144'/account'/0/index for `count` indexes from `start`. The seed of
+// the mnemonic and the account node are only computed once.
+function deriveWallets(deriveOptions) {
+    if (!bip39.validateMnemonic(deriveOptions.mnemonic)) {
+        throw new ValidationError('Invalid mnemonic');
+    }
+    const account = deriveOptions.account;
+    const chain = bip32
+        .fromSeed(bip39.mnemonicToSeedSync(deriveOptions.mnemonic))
+        .derivePath(`m/44'/144'/${account}'/0`);
+    const wallets = [];
+    for (let index = deriveOptions.start; index < deriveOptions.start + deriveOptions.count; index++) {
+        const node = chain.derive(index);
+        const publicKey = Wallet.hexFromBuffer(node.publicKey);
+        const privateKey = '00' + Wallet.hexFromBuffer(node.privateKey);
+        wallets.push({
+            wallet: enhance(new Wallet(publicKey, privateKey, deriveOptions.test)),
+            classic_address: rippleKeyPair.deriveAddress(publicKey),
+            account,
+            index,
+            derivation_path: `m/44'/144'/${account}'/0/${index}`
+        });
+    }
+    return wallets;
+}
+
+function isOnCurve(publicKey) {
+    try {
+        return Secp256k1.keyFromPublic(publicKey, 'hex').validate().result;
+    } catch (e) {
+        return false;
+    }
+}
+
+// The public part of a wallet, for watch-only wallets.
+function watchOnlyFromPublicKey(keyOptions) {
+    const key = keyOptions.public_key.toUpperCase();
+    if (!/^(02|03|ED)[0-9A-F]{64}$/.test(key)) {
+        throw new ValidationError('Invalid Public Key');
+    }
+    if (!key.startsWith('ED') && !isOnCurve(key)) {
+        throw new ValidationError('Invalid Public Key');
+    }
+    return {
+        address: new Wallet(key, null, keyOptions.test).getAddress(),
+        classic_address: rippleKeyPair.deriveAddress(key),
+        public_key: key,
+        test: keyOptions.test
+    };
+}
+
+// Encodes 16 bytes of entropy, random ones when they are omitted, as a family seed.
+function seedFromEntropy(seedOptions) {
+    if (seedOptions.entropy === undefined) {
+        return addressCodec.encodeSeed(crypto.randomBytes(16), seedOptions.algorithm);
+    }
+    const entropy = Buffer.from(seedOptions.entropy, 'hex');
+    if (entropy.length !== 16 || entropy.toString('hex') !== seedOptions.entropy.toLowerCase()) {
+        throw new ValidationError('Invalid Entropy');
+    }
+    return addressCodec.encodeSeed(entropy, seedOptions.algorithm);
+}
+
+const SECP256K1_ORDER = BigInt('0x' + Secp256k1.n.toString(16));
+
+function uint32(value) {
+    const bytes = Buffer.alloc(4);
+    bytes.writeUInt32BE(value);
+    return bytes;
+}
+
+function scalarToHex(scalar) {
+    return scalar.toString(16).padStart(64, '0');
+}
+
+// The first half of SHA-512 of `bytes`, the optional `discriminator` and a counter, for the
+// first counter that gives a valid secp256k1 private key.
+function deriveScalar(bytes, discriminator) {
+    for (let i = 0; i <= 0xFFFFFFFF; i++) {
+        const hash = crypto.createHash('sha512').update(bytes);
+        if (discriminator !== undefined) {
+            hash.update(uint32(discriminator));
+        }
+        const scalar = BigInt('0x' + hash.update(uint32(i)).digest('hex').slice(0, 64));
+        if (scalar > BigInt(0) && scalar < SECP256K1_ORDER) {
+            return scalar;
+        }
+    }
+    throw new Error('No secp256k1 scalar for the seed');
+}
+
+// The public key of the seed `entropy`, derived like ripple-keypairs does but without signing a
+// test message, which is most of its cost. A secp256k1 key is the key of the first account of
+// the root generator of the seed.
+function publicKeyFromEntropy(entropy, algorithm) {
+    if (algorithm === 'ed25519') {
+        const secret = crypto.createHash('sha512').update(entropy).digest().slice(0, 32);
+        return 'ED' + Buffer.from(Ed25519.keyFromSecret(Array.from(secret)).pubBytes()).toString('hex');
+    }
+    const root = deriveScalar(entropy);
+    const generator = Secp256k1.keyFromPrivate(scalarToHex(root), 'hex').getPublic(true, 'hex');
+    const account = (deriveScalar(Buffer.from(generator, 'hex'), 0) + root) % SECP256K1_ORDER;
+    return Secp256k1.keyFromPrivate(scalarToHex(account), 'hex').getPublic(true, 'hex');
+}
+
+// Tries up to `attempts` random seeds and returns the first one whose address, classic or X,
+// starts with `prefix` or matches `regex`, with the number of seeds tried.
+function vanitySearch(vanityOptions) {
+    let regex = null;
+    if (vanityOptions.regex !== undefined) {
+        try {
+            regex = new RegExp(vanityOptions.regex);
+        } catch (e) {
+            throw new ValidationError('Invalid Regular Expression');
+        }
+    }
+    for (let attempt = 1; attempt <= vanityOptions.attempts; attempt++) {
+        const entropy = crypto.randomBytes(16);
+        let address = rippleKeyPair.deriveAddress(publicKeyFromEntropy(entropy, vanityOptions.algorithm));
+        if (vanityOptions.x_address) {
+            address = addressCodec.classicAddressToXAddress(address, false, vanityOptions.test);
+        }
+        if (regex !== null ? regex.test(address) : address.startsWith(vanityOptions.prefix)) {
+            return {seed: addressCodec.encodeSeed(entropy, vanityOptions.algorithm), attempts: attempt};
+        }
+    }
+    return {seed: null, attempts: vanityOptions.attempts};
+}
+
+function entropyFromSeed(seedOptions) {
+    let decoded;
+    try {
+        decoded = addressCodec.decodeSeed(seedOptions.seed);
+    } catch (e) {
+        throw new ValidationError('Invalid Seed');
+    }
+    return {
+        entropy: Buffer.from(decoded.bytes).toString('hex').toUpperCase(),
+        algorithm: decoded.type
+    };
+}
+
+function entropyFromMnemonic(mnemonicOptions) {
+    if (!bip39.validateMnemonic(mnemonicOptions.mnemonic)) {
+        throw new ValidationError('Invalid mnemonic');
+    }
+    return bip39.mnemonicToEntropy(mnemonicOptions.mnemonic).toUpperCase();
+}
+
+function mnemonicFromEntropy(entropyOptions) {
+    try {
+        return bip39.entropyToMnemonic(entropyOptions.entropy.toLowerCase());
+    } catch (e) {
+        throw new ValidationError('Invalid Entropy');
+    }
+}
+
+// Accepts the private keys of derived wallets, 33 bytes with a 0x00 (secp256k1) or 0xED
+// (ed25519) prefix, and bare 32 byte secp256k1 keys. Keys come back in the derived form.
+function generateWalletFromPrivateKey(keyOptions) {
+    const key = keyOptions.private_key.toUpperCase();
+    if (!/^([0-9A-F]{2})?[0-9A-F]{64}$/.test(key)) {
+        throw new ValidationError('Invalid Private Key');
+    }
+    let privateKey;
+    let publicKey;
+    if (key.length === 66 && key.startsWith('ED')) {
+        privateKey = key;
+        const secret = Array.from(Buffer.from(key.slice(2), 'hex'));
+        publicKey = 'ED' + Buffer.from(Ed25519.keyFromSecret(secret).pubBytes()).toString('hex').toUpperCase();
+    } else {
+        if (key.length === 66 && !key.startsWith('00')) {
+            throw new ValidationError('Invalid Private Key');
+        }
+        const raw = key.slice(-64);
+        // elliptic reduces keys modulo the order of the curve, so check the range first.
+        const order = Secp256k1.n.toString(16, 64).toUpperCase();
+        if (/^0+$/.test(raw) || raw >= order) {
+            throw new ValidationError('Invalid Private Key');
+        }
+        const pair = Secp256k1.keyFromPrivate(raw, 'hex');
+        privateKey = '00' + raw;
+        publicKey = Buffer.from(pair.getPublic().encodeCompressed()).toString('hex').toUpperCase();
+    }
+    return enhance(new Wallet(publicKey, privateKey, keyOptions.test));
+}
+
+// Signs a 32 byte digest as it is: secp256k1 does not hash it again and only makes low-S
+// signatures, ed25519 signs the digest as its message.
+function signDigest(signOptions) {
+    const key = signOptions.private_key.toUpperCase();
+    const digest = Array.from(Buffer.from(signOptions.digest, 'hex'));
+    if (signOptions.algorithm === 'ed25519') {
+        if (!/^(ED)?[0-9A-F]{64}$/.test(key)) {
+            throw new ValidationError('Invalid ed25519 Private Key');
+        }
+        const pair = Ed25519.keyFromSecret(Array.from(Buffer.from(key.slice(-64), 'hex')));
+        return {
+            signature: pair.sign(digest).toHex().toUpperCase(),
+            public_key: 'ED' + Buffer.from(pair.pubBytes()).toString('hex').toUpperCase()
+        };
+    }
+    const order = Secp256k1.n.toString(16, 64).toUpperCase();
+    const raw = key.slice(-64);
+    if (!/^(00)?[0-9A-F]{64}$/.test(key) || /^0+$/.test(raw) || raw >= order) {
+        throw new ValidationError('Invalid secp256k1 Private Key');
     }
+    const pair = Secp256k1.keyFromPrivate(raw, 'hex');
+    return {
+        signature: Buffer.from(pair.sign(digest, {canonical: true}).toDER()).toString('hex').toUpperCase(),
+        public_key: Buffer.from(pair.getPublic().encodeCompressed()).toString('hex').toUpperCase()
+    };
+}
+
+// The signature is already parsed: `r` and `s` for secp256k1, 64 bytes of hex for ed25519.
+function verifyDigest(verifyOptions) {
+    const key = verifyOptions.public_key.toUpperCase();
+    const digest = Array.from(Buffer.from(verifyOptions.digest, 'hex'));
+    if (key.startsWith('ED')) {
+        try {
+            return Ed25519.verify(digest, verifyOptions.signature, key.slice(2));
+        } catch (e) {
+            throw new ValidationError('Invalid Public Key');
+        }
+    }
+    if (!isOnCurve(key)) {
+        throw new ValidationError('Invalid Public Key');
+    }
+    return Secp256k1.verify(digest, {r: verifyOptions.r, s: verifyOptions.s}, key, 'hex');
 }
 
 function sign(signOptions) {
@@ -38867,7 +39211,7 @@ function sign(signOptions) {
     if (s) {
         return s;
     } else {
-        throw Error('Invalid Message or Key');
+        throw new ValidationError('Invalid Message or Key');
     }
 }
 
@@ -38885,8 +39229,18 @@ const walletExports = {
     generateRandomWallet,
     generateWalletFromMnemonic,
     generateWalletFromSeed,
+    generateWalletFromPrivateKey,
+    deriveWallets,
+    watchOnlyFromPublicKey,
+    seedFromEntropy,
+    entropyFromSeed,
+    entropyFromMnemonic,
+    mnemonicFromEntropy,
+    vanitySearch,
     sign,
-    verify
+    verify,
+    signDigest,
+    verifyDigest
 };
 
 module.exports = walletExports;
@@ -44449,6 +44803,8 @@ module.exports = {
 /***/ (function(module, __unusedexports, __webpack_require__) {
 
 const {Utils} = __webpack_require__(139);
+const addressCodec = __webpack_require__(280);
+const {ValidationError} = __webpack_require__(995);
 
 function isValidAddress(address) {
   return  Utils.isValidAddress(address);
@@ -44459,7 +44815,7 @@ function encodeXAddress(addresOptions) {
     if (a) {
         return a;
     } else {
-        throw Error('Invalid Parameters');
+        throw new ValidationError('Invalid Parameters');
     }
 }
 
@@ -44468,7 +44824,7 @@ function decodeXAddress(addresOptions) {
     if (a) {
         return a;
     } else {
-        throw Error('Invalid Address');
+        throw new ValidationError('Invalid Address');
     }
 }
 
@@ -44477,10 +44833,17 @@ function transactionBlobToTransactionHash(transactionBlobHex) {
     if (a) {
         return a;
     } else {
-        throw Error('Invalid Input');
+        throw new ValidationError('Invalid Input');
     }
 }
 
+// Index of the AccountRoot ledger object of a classic address.
+function accountRootIndex(address) {
+  const accountId = Buffer.from(addressCodec.decodeAccountID(address)).toString('hex');
+  const index = Utils.sha512Half(Buffer.from('0061' + accountId, 'hex'));
+  return Buffer.from(index).toString('hex').toUpperCase();
+}
+
 function isValidXAddress(address) {
   return  Utils.isValidXAddress(address);
 }
@@ -44496,6 +44859,7 @@ const utilExports = {
   isValidXAddress,
   isValidClassicAddress,
   transactionBlobToTransactionHash,
+  accountRootIndex,
 };
 
 module.exports = utilExports;
@@ -50168,41 +50532,41 @@ curve.edwards = __webpack_require__(369);
 /***/ }),
 
 /***/ 994:
-/***/ (function(__unusedmodule, __unusedexports, __webpack_require__) {
+/***/ (function(module, __unusedexports, __webpack_require__) {
 
-const net = __webpack_require__(631);
 const wallet = __webpack_require__(402);
 const utils = __webpack_require__(806);
 const signer = __webpack_require__(138);
 
+function handle(message) {
+  const funcargs = message.func.split('.').map(item => item.trim());
+  try {
+    return [ipcExports[funcargs[0]][funcargs[1]].apply(null, message.arguments), null, message.id];
+  } catch (e) {
+    return [null, {message: e.message, validation: e.name === 'ValidationError'}, message.id];
+  }
+}
+
 function main() {
-  // The first argument will be the port used for ipc.
-  const port = parseInt(process.argv[2], 10);
-  const client = new net.Socket();
-  let buffers = [];
-  let bufferLength = 0;
-  client.connect(port, '127.0.0.1');
-  client.on('data', data => {
-    buffers.push(data);
-    bufferLength += data.length;
-    // Data arrives in chunks. Only process if this was the last chunk of the message.
-    if (data[data.length - 1] === 0x04) {
-      const buffer = Buffer.concat(buffers, bufferLength - 1);
-      const message = JSON.parse(buffer);
-      buffers = [];
-      bufferLength = 0;
-      let response = null;
-      const funcargs = message.func.split('.').map(item => item.trim());
-      try {
-        response = [ipcExports[funcargs[0]][funcargs[1]].apply(null, message.arguments), null];
-      } catch (e) {
-        response = [null, e.message];
-      }
-      // \0x04 is used as a separator between messages it marks the end of a message.
-      client.write(JSON.stringify(response) + '\x04', 'utf-8');
+  // Requests arrive on stdin and responses leave on stdout, both ending with \x04. The nonce
+  // proves to rust that it is talking to the process it started.
+  const nonce = process.env.XPRING_BRIDGE_NONCE;
+  delete process.env.XPRING_BRIDGE_NONCE;
+  // stdout carries the bridge protocol, so anything logged goes to stderr.
+  console.log = console.info = console.debug = console.error;
+  process.stdout.write(JSON.stringify({nonce}) + '\x04', 'utf-8');
+  let pending = Buffer.alloc(0);
+  process.stdin.on('data', data => {
+    pending = Buffer.concat([pending, data]);
+    let end;
+    while ((end = pending.indexOf(0x04)) !== -1) {
+      const message = JSON.parse(pending.slice(0, end));
+      pending = pending.slice(end + 1);
+      // The request id is echoed so rust can match the response to its request.
+      process.stdout.write(JSON.stringify(handle(message)) + '\x04', 'utf-8');
     }
   });
-  client.on('close', () => { process.exit(0); });
+  process.stdin.on('end', () => { process.exit(0); });
 }
 
 // Export the functions that should be available to rust.
@@ -50212,8 +50576,29 @@ function main() {
   signer
  };
 
-main();
+// Without a nonce the bundle was loaded by the embedded engine, which calls the exports directly.
+if (process.env.XPRING_BRIDGE_NONCE !== undefined) {
+  main();
+}
+
+module.exports = ipcExports;
+
+/***/ }),
+
+/***/ 995:
+/***/ (function(module) {
+
+// Thrown when xpring.js rejects its input. Rust reports these as validation errors and any
+// other exception as a JavaScript error. The name is checked rather than the class, because the
+// embedded engine dispatches calls from outside the bundle.
+class ValidationError extends Error {
+  constructor(message) {
+    super(message);
+    this.name = 'ValidationError';
+  }
+}
 
+module.exports = {ValidationError};
 
 /***/ }),
 
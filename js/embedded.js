@@ -0,0 +1,371 @@
+// Loaded before xpring.js by the embedded JavaScript engine, which has none of the node.js
+// globals and modules the bundle expects. The engine provides `__native` with the hash and
+// random functions implemented in rust, all taking and returning hex strings.
+(function (global) {
+  'use strict';
+
+  const HEX = '0123456789abcdef';
+
+  function toHex(bytes) {
+    let out = '';
+    for (let i = 0; i < bytes.length; i++) {
+      out += HEX[bytes[i] >> 4] + HEX[bytes[i] & 15];
+    }
+    return out;
+  }
+
+  function fromHex(string) {
+    const length = string.length >> 1;
+    const bytes = new Uint8Array(length);
+    for (let i = 0; i < length; i++) {
+      const byte = parseInt(string.substr(i * 2, 2), 16);
+      if (Number.isNaN(byte)) {
+        return bytes.subarray(0, i);
+      }
+      bytes[i] = byte;
+    }
+    return bytes;
+  }
+
+  function utf8Encode(string) {
+    const bytes = [];
+    for (let i = 0; i < string.length; i++) {
+      let code = string.codePointAt(i);
+      if (code > 0xffff) {
+        i++;
+      }
+      if (code < 0x80) {
+        bytes.push(code);
+      } else if (code < 0x800) {
+        bytes.push(0xc0 | (code >> 6), 0x80 | (code & 63));
+      } else if (code < 0x10000) {
+        bytes.push(0xe0 | (code >> 12), 0x80 | ((code >> 6) & 63), 0x80 | (code & 63));
+      } else {
+        bytes.push(0xf0 | (code >> 18), 0x80 | ((code >> 12) & 63), 0x80 | ((code >> 6) & 63), 0x80 | (code & 63));
+      }
+    }
+    return bytes;
+  }
+
+  function utf8Decode(bytes) {
+    let out = '';
+    for (let i = 0; i < bytes.length;) {
+      const byte = bytes[i++];
+      let code;
+      if (byte < 0x80) {
+        code = byte;
+      } else if (byte < 0xe0) {
+        code = ((byte & 31) << 6) | (bytes[i++] & 63);
+      } else if (byte < 0xf0) {
+        code = ((byte & 15) << 12) | ((bytes[i++] & 63) << 6) | (bytes[i++] & 63);
+      } else {
+        code = ((byte & 7) << 18) | ((bytes[i++] & 63) << 12) | ((bytes[i++] & 63) << 6) | (bytes[i++] & 63);
+      }
+      out += String.fromCodePoint(code);
+    }
+    return out;
+  }
+
+  const BASE64 = 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/';
+
+  function base64Encode(bytes) {
+    let out = '';
+    for (let i = 0; i < bytes.length; i += 3) {
+      const n = (bytes[i] << 16) | ((bytes[i + 1] || 0) << 8) | (bytes[i + 2] || 0);
+      out += BASE64[n >> 18] + BASE64[(n >> 12) & 63];
+      out += i + 1 < bytes.length ? BASE64[(n >> 6) & 63] : '=';
+      out += i + 2 < bytes.length ? BASE64[n & 63] : '=';
+    }
+    return out;
+  }
+
+  function base64Decode(string) {
+    const clean = string.replace(/[^A-Za-z0-9+/]/g, '');
+    const bytes = [];
+    for (let i = 0; i < clean.length; i += 4) {
+      const n = (BASE64.indexOf(clean[i]) << 18) | (BASE64.indexOf(clean[i + 1]) << 12) |
+        ((BASE64.indexOf(clean[i + 2]) & 63) << 6) | (BASE64.indexOf(clean[i + 3]) & 63);
+      bytes.push(n >> 16);
+      if (i + 2 < clean.length) bytes.push((n >> 8) & 255);
+      if (i + 3 < clean.length) bytes.push(n & 255);
+    }
+    return bytes;
+  }
+
+  function encode(string, encoding) {
+    switch ((encoding || 'utf8').toLowerCase()) {
+      case 'hex':
+        return fromHex(string);
+      case 'base64':
+        return base64Decode(string);
+      case 'ascii':
+      case 'latin1':
+      case 'binary':
+        return Array.from(string, c => c.charCodeAt(0) & 255);
+      default:
+        return utf8Encode(string);
+    }
+  }
+
+  function decode(bytes, encoding) {
+    switch ((encoding || 'utf8').toLowerCase()) {
+      case 'hex':
+        return toHex(bytes);
+      case 'base64':
+        return base64Encode(bytes);
+      case 'ascii':
+      case 'latin1':
+      case 'binary':
+        return Array.from(bytes, b => String.fromCharCode(b)).join('');
+      default:
+        return utf8Decode(bytes);
+    }
+  }
+
+  // The subset of node's Buffer used by the bundled libraries. Like node's, it can be called
+  // without `new`, and its static functions are enumerable so `safe-buffer` can copy them.
+  function Buffer(value, encodingOrOffset, length) {
+    let bytes;
+    if (typeof value === 'number') {
+      bytes = new Uint8Array(value);
+    } else if (value instanceof ArrayBuffer) {
+      const offset = encodingOrOffset || 0;
+      bytes = new Uint8Array(value, offset, length === undefined ? value.byteLength - offset : length);
+    } else {
+      const source = typeof value === 'string' ? encode(value, encodingOrOffset)
+        : value && value.type === 'Buffer' && Array.isArray(value.data) ? value.data : value;
+      bytes = new Uint8Array(source.length);
+      bytes.set(source);
+    }
+    Object.setPrototypeOf(bytes, Buffer.prototype);
+    return bytes;
+  }
+  Object.setPrototypeOf(Buffer.prototype, Uint8Array.prototype);
+  Object.setPrototypeOf(Buffer, Uint8Array);
+
+  Buffer.from = (value, encodingOrOffset, length) => Buffer(value, encodingOrOffset, length);
+
+  Buffer.alloc = (size, fill, encoding) => {
+    const buffer = Buffer(size);
+    if (fill !== undefined) {
+      buffer.fill(fill, 0, size, encoding);
+    }
+    return buffer;
+  };
+
+  Buffer.allocUnsafe = size => Buffer(size);
+  Buffer.allocUnsafeSlow = size => Buffer(size);
+
+  Buffer.isBuffer = value => value instanceof Buffer;
+
+  Buffer.isEncoding = encoding =>
+    ['hex', 'utf8', 'utf-8', 'ascii', 'latin1', 'binary', 'base64'].includes(String(encoding).toLowerCase());
+
+  Buffer.byteLength = (value, encoding) =>
+    typeof value === 'string' ? encode(value, encoding).length : value.byteLength;
+
+  Buffer.concat = (list, totalLength) => {
+    const length = totalLength === undefined ? list.reduce((sum, b) => sum + b.length, 0) : totalLength;
+    const result = Buffer(length);
+    let offset = 0;
+    for (const buffer of list) {
+      if (offset >= length) break;
+      result.set(buffer.subarray(0, length - offset), offset);
+      offset += buffer.length;
+    }
+    return result;
+  };
+
+  Buffer.compare = (a, b) => {
+    for (let i = 0; i < Math.min(a.length, b.length); i++) {
+      if (a[i] !== b[i]) return a[i] < b[i] ? -1 : 1;
+    }
+    return a.length === b.length ? 0 : a.length < b.length ? -1 : 1;
+  };
+
+  function view(buffer) {
+    return new DataView(buffer.buffer, buffer.byteOffset, buffer.byteLength);
+  }
+
+  const methods = {
+    toString(encoding, start, end) {
+      return decode(this.subarray(start || 0, end === undefined ? this.length : end), encoding);
+    },
+    toJSON() {
+      return {type: 'Buffer', data: Array.from(this)};
+    },
+    // Like node, slices share memory with the original buffer.
+    slice(start, end) {
+      return this.subarray(start, end);
+    },
+    equals(other) {
+      return Buffer.compare(this, other) === 0;
+    },
+    compare(other) {
+      return Buffer.compare(this, other);
+    },
+    copy(target, targetStart, sourceStart, sourceEnd) {
+      const source = this.subarray(sourceStart || 0, sourceEnd === undefined ? this.length : sourceEnd);
+      const start = targetStart || 0;
+      const count = Math.min(source.length, target.length - start);
+      target.set(source.subarray(0, count), start);
+      return count;
+    },
+    fill(value, start, end, encoding) {
+      const from = start || 0;
+      const to = end === undefined ? this.length : end;
+      if (typeof value === 'string') {
+        const bytes = encode(value, encoding);
+        for (let i = from; i < to; i++) {
+          this[i] = bytes[(i - from) % bytes.length];
+        }
+        return this;
+      }
+      return Uint8Array.prototype.fill.call(this, value, from, to);
+    },
+    write(string, offset, length, encoding) {
+      if (typeof offset === 'string') {
+        encoding = offset;
+        offset = 0;
+      }
+      const bytes = encode(string, encoding).slice(0, length === undefined ? undefined : length);
+      return Buffer(bytes).copy(this, offset || 0);
+    },
+    readUInt8(offset) {
+      return this[offset || 0];
+    },
+    readUInt16BE(offset) {
+      return view(this).getUint16(offset || 0);
+    },
+    readUInt32BE(offset) {
+      return view(this).getUint32(offset || 0);
+    },
+    readUInt32LE(offset) {
+      return view(this).getUint32(offset || 0, true);
+    },
+    readInt32BE(offset) {
+      return view(this).getInt32(offset || 0);
+    },
+    writeUInt8(value, offset) {
+      this[offset || 0] = value;
+      return (offset || 0) + 1;
+    },
+    writeUInt16BE(value, offset) {
+      view(this).setUint16(offset || 0, value);
+      return (offset || 0) + 2;
+    },
+    writeUInt32BE(value, offset) {
+      view(this).setUint32(offset || 0, value);
+      return (offset || 0) + 4;
+    },
+    writeUInt32LE(value, offset) {
+      view(this).setUint32(offset || 0, value, true);
+      return (offset || 0) + 4;
+    },
+    writeInt32BE(value, offset) {
+      view(this).setInt32(offset || 0, value);
+      return (offset || 0) + 4;
+    },
+  };
+  for (const name of Object.keys(methods)) {
+    Object.defineProperty(Buffer.prototype, name, {value: methods[name], writable: true, configurable: true});
+  }
+
+  const native = global.__native;
+
+  // The natives answer unsupported input with an empty string.
+  function checked(result, algorithm) {
+    if (result === '') {
+      throw new Error('Unsupported digest ' + algorithm);
+    }
+    return Buffer.from(result, 'hex');
+  }
+
+  function hashFunction(algorithm, key) {
+    const chunks = [];
+    return {
+      update(data, encoding) {
+        chunks.push(Buffer.from(data, encoding));
+        return this;
+      },
+      digest(encoding) {
+        const data = toHex(Buffer.concat(chunks));
+        const digest = checked(key === undefined
+          ? native.hash(algorithm, data)
+          : native.hmac(algorithm, key, data), algorithm);
+        return encoding ? digest.toString(encoding) : digest;
+      },
+    };
+  }
+
+  const crypto = {
+    createHash(algorithm) {
+      return hashFunction(algorithm.toLowerCase());
+    },
+    createHmac(algorithm, key) {
+      return hashFunction(algorithm.toLowerCase(), toHex(Buffer.from(key)));
+    },
+    randomBytes(size) {
+      return checked(native.randomBytes(size), 'random');
+    },
+  };
+
+  function assert(value, message) {
+    if (!value) {
+      throw new Error(message || 'Assertion failed');
+    }
+  }
+  assert.ok = assert;
+  assert.equal = (actual, expected, message) => assert(actual == expected, message);
+  assert.strictEqual = (actual, expected, message) => assert(actual === expected, message);
+  assert.notEqual = (actual, expected, message) => assert(actual != expected, message);
+  assert.deepEqual = (actual, expected, message) =>
+    assert(JSON.stringify(actual) === JSON.stringify(expected), message);
+
+  const util = {
+    inherits(constructor, superConstructor) {
+      Object.setPrototypeOf(constructor.prototype, superConstructor.prototype);
+      Object.setPrototypeOf(constructor, superConstructor);
+      constructor.super_ = superConstructor;
+    },
+    deprecate(fn) {
+      return fn;
+    },
+    inspect(value) {
+      return JSON.stringify(value);
+    },
+  };
+
+  const modules = {
+    assert,
+    buffer: {Buffer},
+    crypto,
+    util,
+  };
+
+  global.global = global;
+  global.__dirname = '.';
+  global.__filename = './xpring.js';
+  global.Buffer = Buffer;
+  global.process = {argv: [], env: {}, browser: false, version: '', versions: {}, nextTick: (f, ...args) => f(...args)};
+  global.require = name => {
+    if (!(name in modules)) {
+      throw new Error('Cannot find module ' + name);
+    }
+    return modules[name];
+  };
+  global.module = {exports: {}};
+
+  // Same dispatch as the node.js bridge in index.js, from a JSON request to a JSON response.
+  global.__xpringCall = request => {
+    const message = JSON.parse(request);
+    const funcargs = message.func.split('.').map(item => item.trim());
+    let response;
+    try {
+      response = [global.module.exports[funcargs[0]][funcargs[1]].apply(null, message.arguments), null, message.id];
+    } catch (e) {
+      response = [null, {message: e.message, validation: e.name === 'ValidationError'}, message.id];
+    }
+    return JSON.stringify(response);
+  };
+})(globalThis);
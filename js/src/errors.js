@@ -0,0 +1,11 @@
+// Thrown when xpring.js rejects its input. Rust reports these as validation errors and any
+// other exception as a JavaScript error. The name is checked rather than the class, because the
+// embedded engine dispatches calls from outside the bundle.
+class ValidationError extends Error {
+  constructor(message) {
+    super(message);
+    this.name = 'ValidationError';
+  }
+}
+
+module.exports = {ValidationError};
@@ -1,36 +1,36 @@
-const net = require('net');
 const wallet = require('./wallet');
 const utils = require('./utils');
 const signer = require('./signer');
 
+function handle(message) {
+  const funcargs = message.func.split('.').map(item => item.trim());
+  try {
+    return [ipcExports[funcargs[0]][funcargs[1]].apply(null, message.arguments), null, message.id];
+  } catch (e) {
+    return [null, {message: e.message, validation: e.name === 'ValidationError'}, message.id];
+  }
+}
+
 function main() {
-  // The first argument will be the port used for ipc.
-  const port = parseInt(process.argv[2], 10);
-  const client = new net.Socket();
-  let buffers = [];
-  let bufferLength = 0;
-  client.connect(port, '127.0.0.1');
-  client.on('data', data => {
-    buffers.push(data);
-    bufferLength += data.length;
-    // Data arrives in chunks. Only process if this was the last chunk of the message.
-    if (data[data.length - 1] === 0x04) {
-      const buffer = Buffer.concat(buffers, bufferLength - 1);
-      const message = JSON.parse(buffer);
-      buffers = [];
-      bufferLength = 0;
-      let response = null;
-      const funcargs = message.func.split('.').map(item => item.trim());
-      try {
-        response = [ipcExports[funcargs[0]][funcargs[1]].apply(null, message.arguments), null];
-      } catch (e) {
-        response = [null, e.message];
-      }
-      // \0x04 is used as a separator between messages it marks the end of a message.
-      client.write(JSON.stringify(response) + '\x04', 'utf-8');
+  // Requests arrive on stdin and responses leave on stdout, both ending with \x04. The nonce
+  // proves to rust that it is talking to the process it started.
+  const nonce = process.env.XPRING_BRIDGE_NONCE;
+  delete process.env.XPRING_BRIDGE_NONCE;
+  // stdout carries the bridge protocol, so anything logged goes to stderr.
+  console.log = console.info = console.debug = console.error;
+  process.stdout.write(JSON.stringify({nonce}) + '\x04', 'utf-8');
+  let pending = Buffer.alloc(0);
+  process.stdin.on('data', data => {
+    pending = Buffer.concat([pending, data]);
+    let end;
+    while ((end = pending.indexOf(0x04)) !== -1) {
+      const message = JSON.parse(pending.slice(0, end));
+      pending = pending.slice(end + 1);
+      // The request id is echoed so rust can match the response to its request.
+      process.stdout.write(JSON.stringify(handle(message)) + '\x04', 'utf-8');
     }
   });
-  client.on('close', () => { process.exit(0); });
+  process.stdin.on('end', () => { process.exit(0); });
 }
 
 // Export the functions that should be available to rust.
@@ -40,4 +40,9 @@ function main() {
   signer
  };
 
-main();
+// Without a nonce the bundle was loaded by the embedded engine, which calls the exports directly.
+if (process.env.XPRING_BRIDGE_NONCE !== undefined) {
+  main();
+}
+
+module.exports = ipcExports;
@@ -1,60 +1,163 @@
-const {Wallet, Signer, Serializer, Utils} = require('xpring-common-js');
-const {AccountAddress,CurrencyAmount,XRPDropsAmount} = require('../node_modules/xpring-common-js/build/generated/rpc/v1/amount_pb');
-const {Payment, Transaction} = require('../node_modules/xpring-common-js/build/generated/rpc/v1/transaction_pb');
+const {Wallet, Utils} = require('xpring-common-js');
 const rippleCodec = require("ripple-binary-codec");
+const {ValidationError} = require('./errors');
 
-function signTransaction(transaction, wallet) {
-
-  wallet = new Wallet(wallet.public_key, wallet.private_key);
-
-  const t = transaction.transaction;
-  const value = t.payment.xrp_amount.drops;
-  const destination = t.payment.destination;
-  const fee = t.fee.drops;
-  const sequence = t.sequence;
-  const account = t.account;
+function isSet(value) {
+  return value !== undefined && value !== null;
+}
 
-  const paymentAmount = new XRPDropsAmount();
-  paymentAmount.setDrops(value);
+function toClassicAddress(address) {
+  if (!Utils.isValidXAddress(address)) {
+    return { address: address, tag: undefined };
+  }
+  return Utils.decodeXAddress(address);
+}
 
-  const currencyAmount = new CurrencyAmount();
-  currencyAmount.setXrpAmount(paymentAmount);
+function amountToJSON(amount) {
+  if (isSet(amount.xrp_amount)) {
+    return `${amount.xrp_amount.drops}`;
+  }
+  const issued = amount.issued_currency_amount;
+  return {
+    currency: issued.currency,
+    issuer: toClassicAddress(issued.issuer).address,
+    value: issued.value
+  };
+}
 
-  const destinationAddress = new AccountAddress();
-  destinationAddress.setAddress(destination);
+function pathElementToJSON(element) {
+  const e = {};
+  if (isSet(element.account)) {
+    e.account = element.account;
+  }
+  if (isSet(element.currency)) {
+    e.currency = element.currency;
+  }
+  if (isSet(element.issuer)) {
+    e.issuer = element.issuer;
+  }
+  return e;
+}
 
-  const payment = new Payment();
-  payment.setDestination(destinationAddress);
-  payment.setAmount(currencyAmount);
+function memoToJSON(memo) {
+  const m = {};
+  if (isSet(memo.memo_type)) {
+    m.MemoType = memo.memo_type;
+  }
+  if (isSet(memo.memo_format)) {
+    m.MemoFormat = memo.memo_format;
+  }
+  if (isSet(memo.memo_data)) {
+    m.MemoData = memo.memo_data;
+  }
+  return { Memo: m };
+}
 
-  const transactionFee = new XRPDropsAmount();
-  transactionFee.setDrops(fee);
+function transactionToJSON(t) {
+  const account = toClassicAddress(t.account);
+  // Accounts cannot have a tag.
+  if (isSet(account.tag)) {
+    throw new ValidationError('Invalid Account');
+  }
+  const destination = toClassicAddress(t.payment.destination);
+  const json = {
+    TransactionType: 'Payment',
+    Account: account.address,
+    Destination: destination.address,
+    Amount: amountToJSON(t.payment.amount),
+    Fee: `${t.fee.drops}`,
+    Sequence: t.sequence,
+    SigningPubKey: t.signing_public_key_hex,
+    LastLedgerSequence: t.last_ledger_sequence,
+  };
+  if (isSet(destination.tag)) {
+    json.DestinationTag = destination.tag;
+  }
+  if (isSet(t.payment.destination_tag)) {
+    if (isSet(json.DestinationTag) && json.DestinationTag !== t.payment.destination_tag) {
+      throw new ValidationError('Destination Tag Conflict');
+    }
+    json.DestinationTag = t.payment.destination_tag;
+  }
+  if (isSet(t.payment.invoice_id)) {
+    json.InvoiceID = t.payment.invoice_id;
+  }
+  if (isSet(t.payment.send_max)) {
+    json.SendMax = amountToJSON(t.payment.send_max);
+  }
+  if (isSet(t.payment.deliver_min)) {
+    json.DeliverMin = amountToJSON(t.payment.deliver_min);
+  }
+  if (isSet(t.payment.paths) && t.payment.paths.length > 0) {
+    json.Paths = t.payment.paths.map(path => path.map(pathElementToJSON));
+  }
+  if (isSet(t.flags) && t.flags !== 0) {
+    json.Flags = t.flags;
+  }
+  if (isSet(t.source_tag)) {
+    json.SourceTag = t.source_tag;
+  }
+  if (isSet(t.memos) && t.memos.length > 0) {
+    json.Memos = t.memos.map(memoToJSON);
+  }
+  return json;
+}
 
-  const sender = new AccountAddress();
-  sender.setAddress(account);
+function signTransaction(transaction, wallet) {
 
-  const trx = new Transaction();
-  trx.setAccount(sender);
-  trx.setFee(transactionFee);
-  trx.setSequence(sequence);
-  trx.setPayment(payment);
-  trx.setSigningPublicKey(Utils.toBytes(t.signing_public_key_hex));
-  trx.setLastLedgerSequence(t.last_ledger_sequence);
+  wallet = new Wallet(wallet.public_key, wallet.private_key);
 
-  const a = Signer.signTransaction(trx, wallet);
-  if (a) {
+  const trx = transactionToJSON(transaction.transaction);
+  const signature = wallet.sign(rippleCodec.encodeForSigning(trx));
+  if (signature) {
+    trx.TxnSignature = signature;
+    const result = rippleCodec.encode(trx);
     const r = {
-      transaction: Serializer.transactionToJSON(trx),
-      result: Utils.toHex(a)
+      transaction: trx,
+      result,
+      hash: Utils.transactionBlobToTransactionHash(result)
     };
     return r;
   } else {
-    throw Error('Invalid Parameters');
+    throw new ValidationError('Invalid Parameters');
+  }
+}
+
+function decodeSignedTransaction(transactionBlobHex) {
+  let transaction;
+  try {
+    transaction = rippleCodec.decode(transactionBlobHex);
+  } catch (e) {
+    throw new ValidationError('Invalid Transaction');
+  }
+  const unsigned = Object.assign({}, transaction);
+  delete unsigned.TxnSignature;
+  let signatureValid = false;
+  let signingAddress = null;
+  if (isSet(transaction.SigningPubKey) && isSet(transaction.TxnSignature)) {
+    try {
+      const wallet = new Wallet(transaction.SigningPubKey, null, false);
+      signingAddress = Utils.decodeXAddress(wallet.getAddress()).address;
+      signatureValid = wallet.verify(
+        rippleCodec.encodeForSigning(unsigned),
+        transaction.TxnSignature,
+        transaction.SigningPubKey
+      );
+    } catch (e) {
+      signatureValid = false;
+    }
   }
+  return {
+    transaction,
+    hash: Utils.transactionBlobToTransactionHash(transactionBlobHex),
+    signing_address: signingAddress,
+    signature_valid: signatureValid === true
+  };
 }
 
 const signerExports = {
   signTransaction,
+  decodeSignedTransaction,
 };
 
-module.exports = signerExports;
\ No newline at end of file
+module.exports = signerExports;
@@ -1,4 +1,6 @@
 const {Utils} = require('xpring-common-js');
+const addressCodec = require('ripple-address-codec');
+const {ValidationError} = require('./errors');
 
 function isValidAddress(address) {
   return  Utils.isValidAddress(address);
@@ -9,7 +11,7 @@ function encodeXAddress(addresOptions) {
     if (a) {
         return a;
     } else {
-        throw Error('Invalid Parameters');
+        throw new ValidationError('Invalid Parameters');
     }
 }
 
@@ -18,7 +20,7 @@ function decodeXAddress(addresOptions) {
     if (a) {
         return a;
     } else {
-        throw Error('Invalid Address');
+        throw new ValidationError('Invalid Address');
     }
 }
 
@@ -27,10 +29,17 @@ function transactionBlobToTransactionHash(transactionBlobHex) {
     if (a) {
         return a;
     } else {
-        throw Error('Invalid Input');
+        throw new ValidationError('Invalid Input');
     }
 }
 
+// Index of the AccountRoot ledger object of a classic address.
+function accountRootIndex(address) {
+  const accountId = Buffer.from(addressCodec.decodeAccountID(address)).toString('hex');
+  const index = Utils.sha512Half(Buffer.from('0061' + accountId, 'hex'));
+  return Buffer.from(index).toString('hex').toUpperCase();
+}
+
 function isValidXAddress(address) {
   return  Utils.isValidXAddress(address);
 }
@@ -46,6 +55,7 @@ const utilExports = {
   isValidXAddress,
   isValidClassicAddress,
   transactionBlobToTransactionHash,
+  accountRootIndex,
 };
 
 module.exports = utilExports;
\ No newline at end of file
@@ -1,4 +1,14 @@
 const {Wallet} = require('xpring-common-js');
+const addressCodec = require('ripple-address-codec');
+const elliptic = require('elliptic');
+const crypto = require('crypto');
+const bip32 = require('bip32');
+const bip39 = require('bip39');
+const rippleKeyPair = require('ripple-keypairs');
+const {ValidationError} = require('./errors');
+
+const Ed25519 = elliptic.eddsa('ed25519');
+const Secp256k1 = elliptic.ec('secp256k1');
 
 function generateRandomWallet(genOptions) {
     const w = Wallet.generateRandomWallet(genOptions.entropy, genOptions.test);
@@ -6,7 +16,7 @@ function generateRandomWallet(genOptions) {
         w.wallet = enhance(w.wallet);
         return w;
     } else {
-        throw Error('Invalid Entropy');
+        throw new ValidationError('Invalid Entropy');
     }
 }
 
@@ -15,7 +25,7 @@ function generateWalletFromMnemonic(genOptions) {
     if (w) {
         return enhance(w);
     } else {
-        throw Error('Invalid mnemonic');
+        throw new ValidationError('Invalid mnemonic');
     }
 }
 
@@ -24,8 +34,239 @@ function generateWalletFromSeed(genOptions) {
     if (w) {
         return enhance(w);
     } else {
-        throw Error('Invalid Seed');
+        throw new ValidationError('Invalid Seed');
+    }
+}
+
+// Derives the wallets at m/44'/144'/account'/0/index for `count` indexes from `start`. The seed of
+// the mnemonic and the account node are only computed once.
+function deriveWallets(deriveOptions) {
+    if (!bip39.validateMnemonic(deriveOptions.mnemonic)) {
+        throw new ValidationError('Invalid mnemonic');
+    }
+    const account = deriveOptions.account;
+    const chain = bip32
+        .fromSeed(bip39.mnemonicToSeedSync(deriveOptions.mnemonic))
+        .derivePath(`m/44'/144'/${account}'/0`);
+    const wallets = [];
+    for (let index = deriveOptions.start; index < deriveOptions.start + deriveOptions.count; index++) {
+        const node = chain.derive(index);
+        const publicKey = Wallet.hexFromBuffer(node.publicKey);
+        const privateKey = '00' + Wallet.hexFromBuffer(node.privateKey);
+        wallets.push({
+            wallet: enhance(new Wallet(publicKey, privateKey, deriveOptions.test)),
+            classic_address: rippleKeyPair.deriveAddress(publicKey),
+            account,
+            index,
+            derivation_path: `m/44'/144'/${account}'/0/${index}`
+        });
+    }
+    return wallets;
+}
+
+function isOnCurve(publicKey) {
+    try {
+        return Secp256k1.keyFromPublic(publicKey, 'hex').validate().result;
+    } catch (e) {
+        return false;
+    }
+}
+
+// The public part of a wallet, for watch-only wallets.
+function watchOnlyFromPublicKey(keyOptions) {
+    const key = keyOptions.public_key.toUpperCase();
+    if (!/^(02|03|ED)[0-9A-F]{64}$/.test(key)) {
+        throw new ValidationError('Invalid Public Key');
+    }
+    if (!key.startsWith('ED') && !isOnCurve(key)) {
+        throw new ValidationError('Invalid Public Key');
+    }
+    return {
+        address: new Wallet(key, null, keyOptions.test).getAddress(),
+        classic_address: rippleKeyPair.deriveAddress(key),
+        public_key: key,
+        test: keyOptions.test
+    };
+}
+
+// Encodes 16 bytes of entropy, random ones when they are omitted, as a family seed.
+function seedFromEntropy(seedOptions) {
+    if (seedOptions.entropy === undefined) {
+        return addressCodec.encodeSeed(crypto.randomBytes(16), seedOptions.algorithm);
+    }
+    const entropy = Buffer.from(seedOptions.entropy, 'hex');
+    if (entropy.length !== 16 || entropy.toString('hex') !== seedOptions.entropy.toLowerCase()) {
+        throw new ValidationError('Invalid Entropy');
+    }
+    return addressCodec.encodeSeed(entropy, seedOptions.algorithm);
+}
+
+const SECP256K1_ORDER = BigInt('0x' + Secp256k1.n.toString(16));
+
+function uint32(value) {
+    const bytes = Buffer.alloc(4);
+    bytes.writeUInt32BE(value);
+    return bytes;
+}
+
+function scalarToHex(scalar) {
+    return scalar.toString(16).padStart(64, '0');
+}
+
+// The first half of SHA-512 of `bytes`, the optional `discriminator` and a counter, for the
+// first counter that gives a valid secp256k1 private key.
+function deriveScalar(bytes, discriminator) {
+    for (let i = 0; i <= 0xFFFFFFFF; i++) {
+        const hash = crypto.createHash('sha512').update(bytes);
+        if (discriminator !== undefined) {
+            hash.update(uint32(discriminator));
+        }
+        const scalar = BigInt('0x' + hash.update(uint32(i)).digest('hex').slice(0, 64));
+        if (scalar > BigInt(0) && scalar < SECP256K1_ORDER) {
+            return scalar;
+        }
+    }
+    throw new Error('No secp256k1 scalar for the seed');
+}
+
+// The public key of the seed `entropy`, derived like ripple-keypairs does but without signing a
+// test message, which is most of its cost. A secp256k1 key is the key of the first account of
+// the root generator of the seed.
+function publicKeyFromEntropy(entropy, algorithm) {
+    if (algorithm === 'ed25519') {
+        const secret = crypto.createHash('sha512').update(entropy).digest().slice(0, 32);
+        return 'ED' + Buffer.from(Ed25519.keyFromSecret(Array.from(secret)).pubBytes()).toString('hex');
+    }
+    const root = deriveScalar(entropy);
+    const generator = Secp256k1.keyFromPrivate(scalarToHex(root), 'hex').getPublic(true, 'hex');
+    const account = (deriveScalar(Buffer.from(generator, 'hex'), 0) + root) % SECP256K1_ORDER;
+    return Secp256k1.keyFromPrivate(scalarToHex(account), 'hex').getPublic(true, 'hex');
+}
+
+// Tries up to `attempts` random seeds and returns the first one whose address, classic or X,
+// starts with `prefix` or matches `regex`, with the number of seeds tried.
+function vanitySearch(vanityOptions) {
+    let regex = null;
+    if (vanityOptions.regex !== undefined) {
+        try {
+            regex = new RegExp(vanityOptions.regex);
+        } catch (e) {
+            throw new ValidationError('Invalid Regular Expression');
+        }
+    }
+    for (let attempt = 1; attempt <= vanityOptions.attempts; attempt++) {
+        const entropy = crypto.randomBytes(16);
+        let address = rippleKeyPair.deriveAddress(publicKeyFromEntropy(entropy, vanityOptions.algorithm));
+        if (vanityOptions.x_address) {
+            address = addressCodec.classicAddressToXAddress(address, false, vanityOptions.test);
+        }
+        if (regex !== null ? regex.test(address) : address.startsWith(vanityOptions.prefix)) {
+            return {seed: addressCodec.encodeSeed(entropy, vanityOptions.algorithm), attempts: attempt};
+        }
+    }
+    return {seed: null, attempts: vanityOptions.attempts};
+}
+
+function entropyFromSeed(seedOptions) {
+    let decoded;
+    try {
+        decoded = addressCodec.decodeSeed(seedOptions.seed);
+    } catch (e) {
+        throw new ValidationError('Invalid Seed');
+    }
+    return {
+        entropy: Buffer.from(decoded.bytes).toString('hex').toUpperCase(),
+        algorithm: decoded.type
+    };
+}
+
+function entropyFromMnemonic(mnemonicOptions) {
+    if (!bip39.validateMnemonic(mnemonicOptions.mnemonic)) {
+        throw new ValidationError('Invalid mnemonic');
+    }
+    return bip39.mnemonicToEntropy(mnemonicOptions.mnemonic).toUpperCase();
+}
+
+function mnemonicFromEntropy(entropyOptions) {
+    try {
+        return bip39.entropyToMnemonic(entropyOptions.entropy.toLowerCase());
+    } catch (e) {
+        throw new ValidationError('Invalid Entropy');
+    }
+}
+
+// Accepts the private keys of derived wallets, 33 bytes with a 0x00 (secp256k1) or 0xED
+// (ed25519) prefix, and bare 32 byte secp256k1 keys. Keys come back in the derived form.
+function generateWalletFromPrivateKey(keyOptions) {
+    const key = keyOptions.private_key.toUpperCase();
+    if (!/^([0-9A-F]{2})?[0-9A-F]{64}$/.test(key)) {
+        throw new ValidationError('Invalid Private Key');
+    }
+    let privateKey;
+    let publicKey;
+    if (key.length === 66 && key.startsWith('ED')) {
+        privateKey = key;
+        const secret = Array.from(Buffer.from(key.slice(2), 'hex'));
+        publicKey = 'ED' + Buffer.from(Ed25519.keyFromSecret(secret).pubBytes()).toString('hex').toUpperCase();
+    } else {
+        if (key.length === 66 && !key.startsWith('00')) {
+            throw new ValidationError('Invalid Private Key');
+        }
+        const raw = key.slice(-64);
+        // elliptic reduces keys modulo the order of the curve, so check the range first.
+        const order = Secp256k1.n.toString(16, 64).toUpperCase();
+        if (/^0+$/.test(raw) || raw >= order) {
+            throw new ValidationError('Invalid Private Key');
+        }
+        const pair = Secp256k1.keyFromPrivate(raw, 'hex');
+        privateKey = '00' + raw;
+        publicKey = Buffer.from(pair.getPublic().encodeCompressed()).toString('hex').toUpperCase();
+    }
+    return enhance(new Wallet(publicKey, privateKey, keyOptions.test));
+}
+
+// Signs a 32 byte digest as it is: secp256k1 does not hash it again and only makes low-S
+// signatures, ed25519 signs the digest as its message.
+function signDigest(signOptions) {
+    const key = signOptions.private_key.toUpperCase();
+    const digest = Array.from(Buffer.from(signOptions.digest, 'hex'));
+    if (signOptions.algorithm === 'ed25519') {
+        if (!/^(ED)?[0-9A-F]{64}$/.test(key)) {
+            throw new ValidationError('Invalid ed25519 Private Key');
+        }
+        const pair = Ed25519.keyFromSecret(Array.from(Buffer.from(key.slice(-64), 'hex')));
+        return {
+            signature: pair.sign(digest).toHex().toUpperCase(),
+            public_key: 'ED' + Buffer.from(pair.pubBytes()).toString('hex').toUpperCase()
+        };
+    }
+    const order = Secp256k1.n.toString(16, 64).toUpperCase();
+    const raw = key.slice(-64);
+    if (!/^(00)?[0-9A-F]{64}$/.test(key) || /^0+$/.test(raw) || raw >= order) {
+        throw new ValidationError('Invalid secp256k1 Private Key');
+    }
+    const pair = Secp256k1.keyFromPrivate(raw, 'hex');
+    return {
+        signature: Buffer.from(pair.sign(digest, {canonical: true}).toDER()).toString('hex').toUpperCase(),
+        public_key: Buffer.from(pair.getPublic().encodeCompressed()).toString('hex').toUpperCase()
+    };
+}
+
+// The signature is already parsed: `r` and `s` for secp256k1, 64 bytes of hex for ed25519.
+function verifyDigest(verifyOptions) {
+    const key = verifyOptions.public_key.toUpperCase();
+    const digest = Array.from(Buffer.from(verifyOptions.digest, 'hex'));
+    if (key.startsWith('ED')) {
+        try {
+            return Ed25519.verify(digest, verifyOptions.signature, key.slice(2));
+        } catch (e) {
+            throw new ValidationError('Invalid Public Key');
+        }
+    }
+    if (!isOnCurve(key)) {
+        throw new ValidationError('Invalid Public Key');
     }
+    return Secp256k1.verify(digest, {r: verifyOptions.r, s: verifyOptions.s}, key, 'hex');
 }
 
 function sign(signOptions) {
@@ -34,7 +275,7 @@ function sign(signOptions) {
     if (s) {
         return s;
     } else {
-        throw Error('Invalid Message or Key');
+        throw new ValidationError('Invalid Message or Key');
     }
 }
 
@@ -52,8 +293,18 @@ const walletExports = {
     generateRandomWallet,
     generateWalletFromMnemonic,
     generateWalletFromSeed,
+    generateWalletFromPrivateKey,
+    deriveWallets,
+    watchOnlyFromPublicKey,
+    seedFromEntropy,
+    entropyFromSeed,
+    entropyFromMnemonic,
+    mnemonicFromEntropy,
+    vanitySearch,
     sign,
-    verify
+    verify,
+    signDigest,
+    verifyDigest
 };
 
 module.exports = walletExports;
\ No newline at end of file
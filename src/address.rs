@@ -1,5 +1,5 @@
+use crate::error::XpringError as Error;
 use crate::javascript::{JavaScript, JsCall};
-use anyhow::Error;
 use fehler::throws;
 use serde::{Deserialize, Serialize};
 
@@ -10,14 +10,14 @@ struct XAddressOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     x_address: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    tag: Option<u16>,
+    tag: Option<u32>,
     test: bool,
 }
 
 #[derive(PartialEq, Debug, Deserialize)]
 pub struct XClassicAddress {
     pub address: String,
-    pub tag: Option<u16>,
+    pub tag: Option<u32>,
     pub test: bool,
 }
 
@@ -41,23 +41,23 @@ impl<'a> XAddressOptions {
         self
     }
 
-    pub(crate) fn tag(&'a mut self, tag: u16) -> &'a mut XAddressOptions {
+    pub(crate) fn tag(&'a mut self, tag: u32) -> &'a mut XAddressOptions {
         self.tag = Some(tag);
         self
     }
 }
 
 #[throws(_)]
-pub(crate) fn is_valid_address(jscontext: &mut JavaScript, address: &str) -> bool {
+pub(crate) fn is_valid_address(jscontext: &JavaScript, address: &str) -> bool {
     let result = js!(jscontext.utils.isValidAddress::<bool>(address))?;
     result
 }
 
 #[throws(_)]
 pub(crate) fn encode_classic_address(
-    jscontext: &mut JavaScript,
+    jscontext: &JavaScript,
     classic_address: &str,
-    tag: Option<u16>,
+    tag: Option<u32>,
     test: Option<bool>,
 ) -> String {
     let mut address = XAddressOptions::new(test.unwrap_or(false));
@@ -70,19 +70,19 @@ pub(crate) fn encode_classic_address(
 }
 
 #[throws(_)]
-pub(crate) fn is_valid_x_address(jscontext: &mut JavaScript, address: &str) -> bool {
+pub(crate) fn is_valid_x_address(jscontext: &JavaScript, address: &str) -> bool {
     let result = js!(jscontext.utils.isValidXAddress::<bool>(address))?;
     result
 }
 
 #[throws(_)]
-pub(crate) fn is_valid_classic_address(jscontext: &mut JavaScript, address: &str) -> bool {
+pub(crate) fn is_valid_classic_address(jscontext: &JavaScript, address: &str) -> bool {
     let result = js!(jscontext.utils.isValidClassicAddress::<bool>(address))?;
     result
 }
 
 #[throws(_)]
-pub(crate) fn decode_x_address(jscontext: &mut JavaScript, x_address: &str) -> XClassicAddress {
+pub(crate) fn decode_x_address(jscontext: &JavaScript, x_address: &str) -> XClassicAddress {
     let mut address = XAddressOptions::new(false);
     address.x_address(x_address.to_owned());
     let result = js!(jscontext.utils.decodeXAddress::<XClassicAddress>(address))?;
@@ -99,9 +99,9 @@ mod tests {
     #[test]
     fn test_valid_classic_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_address(&mut jscontext, "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1").unwrap(),
+            is_valid_address(&jscontext, "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1").unwrap(),
             true
         );
     }
@@ -110,10 +110,10 @@ mod tests {
     #[test]
     fn test_valid_x_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             is_valid_address(
-                &mut jscontext,
+                &jscontext,
                 "XVLhHMPHU98es4dbozjVtdWzVrDjtV18pX8yuPT7y4xaEHi"
             )
             .unwrap(),
@@ -125,9 +125,9 @@ mod tests {
     #[test]
     fn test_address_wrong_alphabet() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_address(&mut jscontext, "1EAG1MwmzkG6gRZcYqcRMfC17eMt8TDTit").unwrap(),
+            is_valid_address(&jscontext, "1EAG1MwmzkG6gRZcYqcRMfC17eMt8TDTit").unwrap(),
             false
         );
     }
@@ -136,9 +136,9 @@ mod tests {
     #[test]
     fn test_classic_address_wrong_checksum() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_address(&mut jscontext, "rU6K7V3Po4sBBBBBaU29sesqs2qTQJWDw1").unwrap(),
+            is_valid_address(&jscontext, "rU6K7V3Po4sBBBBBaU29sesqs2qTQJWDw1").unwrap(),
             false
         );
     }
@@ -147,10 +147,10 @@ mod tests {
     #[test]
     fn test_x_address_wrong_checksum() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             is_valid_address(
-                &mut jscontext,
+                &jscontext,
                 "XVLhHMPHU98es4dbozjVtdWzVrDjtV18pX8yuPT7y4xaEHI"
             )
             .unwrap(),
@@ -162,9 +162,9 @@ mod tests {
     #[test]
     fn test_address_invalid_character() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_address(&mut jscontext, "rU6K7V3Po4sBBBBBaU@#$%qs2qTQJWDw1").unwrap(),
+            is_valid_address(&jscontext, "rU6K7V3Po4sBBBBBaU@#$%qs2qTQJWDw1").unwrap(),
             false
         );
     }
@@ -173,10 +173,10 @@ mod tests {
     #[test]
     fn test_address_invalid_too_long() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             is_valid_address(
-                &mut jscontext,
+                &jscontext,
                 "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1"
             )
             .unwrap(),
@@ -188,9 +188,9 @@ mod tests {
     #[test]
     fn test_address_invalid_too_short() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_address(&mut jscontext, "rU6K7V3Po4s2qTQJWDw1").unwrap(),
+            is_valid_address(&jscontext, "rU6K7V3Po4s2qTQJWDw1").unwrap(),
             false
         );
     }
@@ -199,10 +199,10 @@ mod tests {
     #[test]
     fn test_encode_x_address_mainnet_and_tag() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             encode_classic_address(
-                &mut jscontext,
+                &jscontext,
                 "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1",
                 Some(12345),
                 None
@@ -216,10 +216,10 @@ mod tests {
     #[test]
     fn test_encode_x_address_testnet_and_tag() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             encode_classic_address(
-                &mut jscontext,
+                &jscontext,
                 "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1",
                 Some(12345),
                 None
@@ -233,10 +233,10 @@ mod tests {
     #[test]
     fn test_encode_x_address_only() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             encode_classic_address(
-                &mut jscontext,
+                &jscontext,
                 "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1",
                 None,
                 Some(false)
@@ -250,20 +250,16 @@ mod tests {
     #[test]
     fn test_encode_x_address_invalid_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let x_address =
-            encode_classic_address(&mut jscontext, "xrp", None, Some(false)).unwrap_err();
-        assert_eq!(
-            "Invalid Parameters",
-            x_address.downcast_ref::<String>().unwrap()
-        );
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let x_address = encode_classic_address(&jscontext, "xrp", None, Some(false)).unwrap_err();
+        assert!(matches!(x_address, Error::Validation(ref m) if m == "Invalid Parameters"));
     }
 
     #[throws(_)]
     #[test]
     fn test_decode_x_address_valid_mainnet_with_tag() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let expected = XClassicAddress {
             address: "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1".to_owned(),
             tag: Some(12345),
@@ -271,7 +267,7 @@ mod tests {
         };
         assert_eq!(
             decode_x_address(
-                &mut jscontext,
+                &jscontext,
                 "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUvtU3HnooQDgBnUpQT"
             )
             .unwrap(),
@@ -283,7 +279,7 @@ mod tests {
     #[test]
     fn test_decode_x_address_valid_testnet_with_tag() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let expected = XClassicAddress {
             address: "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1".to_owned(),
             tag: Some(12345),
@@ -291,7 +287,7 @@ mod tests {
         };
         assert_eq!(
             decode_x_address(
-                &mut jscontext,
+                &jscontext,
                 "TVsBZmcewpEHgajPi1jApLeYnHPJw82v9JNYf7dkGmWphmh"
             )
             .unwrap(),
@@ -303,7 +299,7 @@ mod tests {
     #[test]
     fn test_decode_x_address_valid_testnet_without_tag() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let expected = XClassicAddress {
             address: "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1".to_owned(),
             tag: None,
@@ -311,7 +307,7 @@ mod tests {
         };
         assert_eq!(
             decode_x_address(
-                &mut jscontext,
+                &jscontext,
                 "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUFyQVMzRrMGUZpokKH"
             )
             .unwrap(),
@@ -323,21 +319,18 @@ mod tests {
     #[test]
     fn test_decode_x_address_invalid_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let x_address = decode_x_address(&mut jscontext, "xrp").unwrap_err();
-        assert_eq!(
-            "Invalid Address",
-            x_address.downcast_ref::<String>().unwrap()
-        );
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let x_address = decode_x_address(&jscontext, "xrp").unwrap_err();
+        assert!(matches!(x_address, Error::Validation(ref m) if m == "Invalid Address"));
     }
 
     #[throws(_)]
     #[test]
     fn test_is_valid_x_address_with_classic_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_x_address(&mut jscontext, "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1").unwrap(),
+            is_valid_x_address(&jscontext, "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1").unwrap(),
             false
         );
     }
@@ -346,18 +339,18 @@ mod tests {
     #[test]
     fn test_is_valid_x_address_with_invalid_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        assert_eq!(is_valid_x_address(&mut jscontext, "xrp").unwrap(), false);
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        assert_eq!(is_valid_x_address(&jscontext, "xrp").unwrap(), false);
     }
 
     #[throws(_)]
     #[test]
     fn test_is_valid_x_address_with_valid_x_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             is_valid_x_address(
-                &mut jscontext,
+                &jscontext,
                 "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUvtU3HnooQDgBnUpQT"
             )
             .unwrap(),
@@ -369,9 +362,9 @@ mod tests {
     #[test]
     fn test_is_valid_classic_address_with_valid_classic_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_classic_address(&mut jscontext, "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1").unwrap(),
+            is_valid_classic_address(&jscontext, "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1").unwrap(),
             true
         );
     }
@@ -380,10 +373,7 @@ mod tests {
     #[test]
     fn test_is_valid_classic_address_with_invalid_classic_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        assert_eq!(
-            is_valid_classic_address(&mut jscontext, "xrp").unwrap(),
-            false
-        );
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        assert_eq!(is_valid_classic_address(&jscontext, "xrp").unwrap(), false);
     }
 }
@@ -0,0 +1,222 @@
+//! Installation of the xpring.js bundle embedded in the library, so node.js can load it.
+use crate::error::XpringError as Error;
+use fehler::{throw, throws};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const BUNDLE: &str = include_str!("../js/dist/xpring.js");
+
+/// Where the embedded xpring.js bundle is placed for node.js to load it.
+///
+/// node.js runs with the private keys of the process, so the bundle must not be writable by
+/// other users: `install` refuses copies and directories that are.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum JsBundle {
+    /// `xpring-<hash>.js` in a directory of the system temporary directory that only the current
+    /// user can access, written when missing.
+    #[default]
+    TempDir,
+    /// `xpring-<hash>.js` in the given directory, written when missing. The directory must be
+    /// owned by the current user.
+    Directory(PathBuf),
+    /// An existing copy of the bundle, for read-only installations. It is checked against the
+    /// embedded bundle but never written. It may also be owned by root.
+    Path(PathBuf),
+}
+
+fn bundle_error<E: std::fmt::Display>(path: &Path, error: E) -> Error {
+    Error::JavaScript(format!("xpring.js at {}: {}", path.display(), error))
+}
+
+fn digest(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+/// Whether the file at `path` holds the embedded bundle.
+fn is_intact(path: &Path, expected: &str) -> bool {
+    matches!(fs::read(path), Ok(content) if digest(&content) == expected)
+}
+
+/// Checks that nobody but the current user, or root when `allow_root` is set, can change the
+/// file or directory at `path`, so the bundle cannot be swapped after it was checked.
+/// `private` directories must not be accessible by other users at all.
+#[cfg(unix)]
+#[throws(_)]
+fn check_owner(path: &Path, allow_root: bool, private: bool) {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = fs::symlink_metadata(path).map_err(|e| bundle_error(path, e))?;
+    // Safe: geteuid cannot fail and has no side effects.
+    let uid = unsafe { libc::geteuid() };
+    if metadata.file_type().is_symlink() {
+        throw!(bundle_error(path, "is a symbolic link"));
+    }
+    if metadata.uid() != uid && !(allow_root && metadata.uid() == 0) {
+        throw!(bundle_error(path, "owned by another user"));
+    }
+    let forbidden = if private { 0o077 } else { 0o022 };
+    if metadata.mode() & forbidden != 0 {
+        throw!(bundle_error(path, "accessible by other users"));
+    }
+}
+
+#[cfg(not(unix))]
+#[throws(_)]
+fn check_owner(_path: &Path, _allow_root: bool, _private: bool) {}
+
+/// The directory of the current user in the system temporary directory, created if needed.
+#[throws(_)]
+fn user_directory() -> PathBuf {
+    #[cfg(unix)]
+    let directory = {
+        use std::os::unix::fs::DirBuilderExt;
+        // Safe: geteuid cannot fail and has no side effects.
+        let directory = std::env::temp_dir().join(format!("xpring-{}", unsafe { libc::geteuid() }));
+        match fs::DirBuilder::new().mode(0o700).create(&directory) {
+            Ok(()) => (),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => (),
+            Err(e) => throw!(bundle_error(&directory, e)),
+        }
+        directory
+    };
+    // Elsewhere the temporary directory already belongs to the user.
+    #[cfg(not(unix))]
+    let directory = std::env::temp_dir().join("xpring");
+    fs::create_dir_all(&directory).map_err(|e| bundle_error(&directory, e))?;
+    check_owner(&directory, false, true)?;
+    directory
+}
+
+/// Returns the path of a copy of the embedded bundle, writing it first if needed.
+#[throws(_)]
+pub(crate) fn install(bundle: &JsBundle) -> PathBuf {
+    let expected = digest(BUNDLE.as_bytes());
+    let directory = match bundle {
+        JsBundle::Path(path) => {
+            let path = fs::canonicalize(path).map_err(|e| bundle_error(path, e))?;
+            if let Some(parent) = path.parent() {
+                check_owner(parent, true, false)?;
+            }
+            check_owner(&path, true, false)?;
+            if !is_intact(&path, &expected) {
+                throw!(bundle_error(
+                    &path,
+                    "not the bundle of this version of xpring-rs"
+                ));
+            }
+            return path;
+        }
+        JsBundle::TempDir => user_directory()?,
+        JsBundle::Directory(directory) => {
+            fs::create_dir_all(directory).map_err(|e| bundle_error(directory, e))?;
+            check_owner(directory, false, false)?;
+            directory.clone()
+        }
+    };
+    // The content hash in the name keeps different versions of the library apart.
+    let path = directory.join(format!("xpring-{}.js", &expected[..16]));
+    if check_owner(&path, false, false).is_ok() && is_intact(&path, &expected) {
+        return path;
+    }
+    // Write to a new file of our own and rename it, so concurrent processes never load a
+    // partially written bundle and a replaced copy never keeps its old permissions.
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    let partial = directory.join(format!(
+        "xpring-{}.{}.{}.tmp",
+        &expected[..16],
+        std::process::id(),
+        nanos
+    ));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o644);
+    let written = options
+        .open(&partial)
+        .and_then(|mut file| file.write_all(BUNDLE.as_bytes()))
+        .and_then(|()| fs::rename(&partial, &path));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&partial);
+        throw!(bundle_error(&path, e));
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn directory(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("xpring-{}-{}", name, std::process::id()))
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_install_in_directory() {
+        let directory = directory("bundle");
+        let path = install(&JsBundle::Directory(directory.clone()))?;
+        assert!(path.starts_with(&directory));
+        assert_eq!(fs::read_to_string(&path).unwrap(), BUNDLE);
+
+        // A damaged copy is replaced.
+        fs::write(&path, "tampered").unwrap();
+        assert_eq!(install(&JsBundle::Directory(directory.clone()))?, path);
+        assert_eq!(fs::read_to_string(&path).unwrap(), BUNDLE);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[throws(_)]
+    #[test]
+    fn test_install_checks_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = install(&JsBundle::TempDir)?;
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(path.parent().unwrap()), 0o700);
+
+        // Directories and copies other users can write to are refused.
+        let directory = directory("shared");
+        fs::create_dir_all(&directory).unwrap();
+        fs::set_permissions(&directory, fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(matches!(
+            install(&JsBundle::Directory(directory.clone())),
+            Err(Error::JavaScript(_))
+        ));
+        fs::set_permissions(&directory, fs::Permissions::from_mode(0o755)).unwrap();
+        let path = install(&JsBundle::Directory(directory.clone()))?;
+        assert_eq!(mode(&path), 0o644);
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o666)).unwrap();
+        assert!(matches!(
+            install(&JsBundle::Path(path.clone())),
+            Err(Error::JavaScript(_))
+        ));
+        // A copy written by someone else is replaced by a new file of ours.
+        assert_eq!(install(&JsBundle::Directory(directory.clone()))?, path);
+        assert_eq!(mode(&path), 0o644);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_install_from_path() {
+        let directory = directory("explicit");
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("xpring.js");
+        assert!(matches!(
+            install(&JsBundle::Path(path.clone())),
+            Err(Error::JavaScript(_))
+        ));
+        fs::write(&path, "tampered").unwrap();
+        assert!(matches!(
+            install(&JsBundle::Path(path.clone())),
+            Err(Error::JavaScript(_))
+        ));
+        fs::write(&path, BUNDLE).unwrap();
+        assert_eq!(install(&JsBundle::Path(path.clone())).unwrap(), path);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,292 @@
+//! Proof of address ownership by challenge and response.
+//!
+//! A server issues a `XOwnershipChallenge`: a random nonce bound to the address a client claims
+//! and to an expiry time. The client signs it with `XrplPool::prove_ownership` and sends back the
+//! `XOwnershipProof`. The server checks with `Xrpl::verify_ownership` that the proof answers the
+//! challenge it issued before it expired, that the signature is valid, and that the public key
+//! is the master key of the address or the regular key set on its account.
+//!
+//! Challenges are signed with `message::sign_message` in the domain of the server, so a proof
+//! cannot be replayed to another server or as a transaction signature. Servers keep track of
+//! the challenges they issued and accept each one once.
+use crate::address;
+use crate::error::XpringError as Error;
+use crate::javascript::JavaScript;
+use crate::message::{self, XSignatureVerification};
+use crate::wallet::{self, KeyAlgorithm, XWallet};
+use fehler::{throw, throws};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// `lsfDisableMaster`: the account does not accept signatures of its master key.
+pub const LSF_DISABLE_MASTER: u32 = 0x0010_0000;
+
+const NONCE_BYTES: usize = 32;
+
+/// A challenge to prove the ownership of `address`.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct XOwnershipChallenge {
+    /// Signing domain of the server, such as its host name.
+    pub domain: String,
+    /// The claimed address, classic or X-Address, as the client gave it.
+    pub address: String,
+    /// 32 random bytes, hex encoded.
+    pub nonce: String,
+    /// Expiry as seconds since the Unix epoch.
+    pub expires_at: u64,
+    pub test: bool,
+}
+
+impl XOwnershipChallenge {
+    /// The text the client signs.
+    pub fn message(&self) -> String {
+        format!(
+            "{} asks to prove the ownership of the XRP Ledger address {}.\nNonce: {}\nExpires: {}",
+            self.domain, self.address, self.nonce, self.expires_at
+        )
+    }
+}
+
+/// The answer of a client to a challenge.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct XOwnershipProof {
+    pub challenge: XOwnershipChallenge,
+    pub public_key: String,
+    pub signature: String,
+}
+
+/// The key that signed a proof of ownership.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum XOwnershipKey {
+    /// The key the address is derived from.
+    Master,
+    /// The regular key set on the account.
+    Regular,
+}
+
+/// Outcome of the verification of a proof of ownership.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum XOwnershipVerification {
+    /// The signer controls the account at `classic_address`.
+    Verified {
+        classic_address: String,
+        key: XOwnershipKey,
+    },
+    /// The proof answers another challenge than the one issued.
+    ChallengeMismatch,
+    /// The challenge expired before the proof was verified.
+    Expired,
+    /// The signature is not a valid signature of the challenge by the public key.
+    InvalidSignature(XSignatureVerification),
+    /// The public key is neither the master key of the address nor the regular key of its
+    /// account.
+    UnauthorizedKey,
+    /// The public key is the master key of the address, but the account disabled it.
+    MasterKeyDisabled,
+}
+
+impl XOwnershipVerification {
+    /// Returns true for `Verified` proofs only.
+    pub fn is_verified(&self) -> bool {
+        matches!(self, XOwnershipVerification::Verified { .. })
+    }
+}
+
+/// A proof whose challenge and signature passed, before the account is looked up.
+pub(crate) enum XSignedProof {
+    Failed(XOwnershipVerification),
+    Signed {
+        /// Classic address of the claimed address.
+        claimed: String,
+        /// Classic address derived from the public key of the proof.
+        signer: String,
+    },
+}
+
+#[throws(_)]
+fn classic_address(jscontext: &JavaScript, address: &str) -> String {
+    if address::is_valid_x_address(jscontext, address)? {
+        address::decode_x_address(jscontext, address)?.address
+    } else if address::is_valid_classic_address(jscontext, address)? {
+        address.to_owned()
+    } else {
+        throw!(Error::Validation(format!("Invalid address {}", address)));
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[throws(_)]
+pub(crate) fn issue(
+    jscontext: &JavaScript,
+    address: &str,
+    domain: &str,
+    lifetime: Duration,
+    test: bool,
+) -> XOwnershipChallenge {
+    if domain.is_empty() || domain.contains('\n') {
+        throw!(Error::Validation(
+            "The domain must be a non-empty single line".to_owned()
+        ));
+    }
+    // Rejects domains that cannot be signed in before handing out the challenge.
+    message::message_digest(domain, b"")?;
+    if address::is_valid_x_address(jscontext, address)?
+        && address::decode_x_address(jscontext, address)?.test != test
+    {
+        throw!(Error::Validation(format!(
+            "The X-Address is not for the {} network",
+            if test { "test" } else { "main" }
+        )));
+    }
+    classic_address(jscontext, address)?;
+    let mut nonce = [0u8; NONCE_BYTES];
+    getrandom::getrandom(&mut nonce)
+        .map_err(|e| Error::Validation(format!("Could not generate a nonce: {}", e)))?;
+    XOwnershipChallenge {
+        domain: domain.to_owned(),
+        address: address.to_owned(),
+        nonce: hex::encode_upper(nonce),
+        expires_at: now().saturating_add(lifetime.as_secs()),
+        test,
+    }
+}
+
+#[throws(_)]
+pub(crate) fn prove(
+    jscontext: &JavaScript,
+    challenge: &XOwnershipChallenge,
+    wallet: &XWallet,
+) -> XOwnershipProof {
+    let algorithm = if wallet.public_key.to_uppercase().starts_with("ED") {
+        KeyAlgorithm::Ed25519
+    } else {
+        KeyAlgorithm::Secp256k1
+    };
+    let signed = message::sign_message(
+        jscontext,
+        challenge.message().as_bytes(),
+        &challenge.domain,
+        &wallet.private_key,
+        algorithm,
+    )?;
+    XOwnershipProof {
+        challenge: challenge.clone(),
+        public_key: signed.public_key,
+        signature: signed.signature,
+    }
+}
+
+/// Checks everything but the account: the challenge, its expiry at `now` and the signature.
+#[throws(_)]
+pub(crate) fn check_signature(
+    jscontext: &JavaScript,
+    proof: &XOwnershipProof,
+    issued: &XOwnershipChallenge,
+    now: u64,
+) -> XSignedProof {
+    if proof.challenge != *issued {
+        return XSignedProof::Failed(XOwnershipVerification::ChallengeMismatch);
+    }
+    if now >= issued.expires_at {
+        return XSignedProof::Failed(XOwnershipVerification::Expired);
+    }
+    let verification = message::verify_message(
+        jscontext,
+        issued.message().as_bytes(),
+        &issued.domain,
+        &proof.signature,
+        &proof.public_key,
+    )?;
+    if !verification.is_valid() {
+        return XSignedProof::Failed(XOwnershipVerification::InvalidSignature(verification));
+    }
+    let signer =
+        wallet::watch_only_from_public_key(jscontext, proof.public_key.clone(), issued.test)?;
+    XSignedProof::Signed {
+        claimed: classic_address(jscontext, &issued.address)?,
+        signer: signer.classic_address,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const SEED: &str = "sn3UJSLzAEeAGcrK3nsQTDZW6KT92";
+    const CLASSIC_ADDRESS: &str = "r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4";
+    const X_ADDRESS: &str = "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs";
+
+    #[throws(_)]
+    #[test]
+    fn test_check_signature() {
+        let jscontext =
+            JavaScript::new(format!("{}/xpring.js", std::env::var("OUT_DIR").unwrap()))?;
+        let wallet = wallet::from_seed(&jscontext, SEED.to_owned(), None, true)?;
+        let challenge = issue(
+            &jscontext,
+            X_ADDRESS,
+            "example.com",
+            Duration::from_secs(300),
+            true,
+        )?;
+        assert_eq!(challenge.nonce.len(), 2 * NONCE_BYTES);
+        let proof = prove(&jscontext, &challenge, &wallet)?;
+        let now = challenge.expires_at - 1;
+        match check_signature(&jscontext, &proof, &challenge, now)? {
+            XSignedProof::Signed { claimed, signer } => {
+                assert_eq!(claimed, CLASSIC_ADDRESS);
+                assert_eq!(signer, CLASSIC_ADDRESS);
+            }
+            XSignedProof::Failed(failure) => panic!("Unexpected failure {:?}", failure),
+        }
+        let failure = |result| match result {
+            XSignedProof::Failed(failure) => failure,
+            XSignedProof::Signed { .. } => panic!("The proof was accepted"),
+        };
+        let expired = check_signature(&jscontext, &proof, &challenge, challenge.expires_at)?;
+        assert_eq!(failure(expired), XOwnershipVerification::Expired);
+        let other = issue(
+            &jscontext,
+            X_ADDRESS,
+            "example.com",
+            Duration::from_secs(300),
+            true,
+        )?;
+        let mismatch = check_signature(&jscontext, &proof, &other, now)?;
+        assert_eq!(failure(mismatch), XOwnershipVerification::ChallengeMismatch);
+        // The signature of another domain does not answer the challenge.
+        let mut forged = proof.clone();
+        forged.challenge.domain = "example.org".to_owned();
+        forged.signature = prove(&jscontext, &forged.challenge, &wallet)?.signature;
+        forged.challenge.domain = "example.com".to_owned();
+        assert_eq!(
+            failure(check_signature(&jscontext, &forged, &challenge, now)?),
+            XOwnershipVerification::InvalidSignature(XSignatureVerification::Mismatch)
+        );
+        // X-Addresses of the other network and invalid addresses are rejected.
+        assert!(issue(
+            &jscontext,
+            X_ADDRESS,
+            "example.com",
+            Duration::from_secs(1),
+            false
+        )
+        .is_err());
+        assert!(issue(
+            &jscontext,
+            "rXRP",
+            "example.com",
+            Duration::from_secs(1),
+            true
+        )
+        .is_err());
+    }
+}
@@ -1 +1,55 @@
 pub const MAX_LEDGER_VERSION_OFFSET: u32 = 4;
+
+use crate::bundle::JsBundle;
+use crate::javascript::{JsBridgeOptions, JsEngine};
+
+/// Settings for `Xrpl::with_config` and `XrplPool::with_config`.
+///
+/// # Example
+///
+/// ```
+/// # use xpring::{JsBundle, XrplConfig};
+/// let mut config = XrplConfig::new();
+/// config
+///     .test(true)
+///     .js_bundle(JsBundle::Directory(std::env::temp_dir().join("my-app")));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct XrplConfig {
+    pub(crate) test: bool,
+    pub(crate) js_engine: JsEngine,
+    pub(crate) js_bundle: JsBundle,
+    pub(crate) js_bridge_options: JsBridgeOptions,
+}
+
+impl<'a> XrplConfig {
+    /// MainNet, with node.js running the bundle from the temporary directory and the default
+    /// bridge options.
+    pub fn new() -> XrplConfig {
+        XrplConfig::default()
+    }
+
+    /// true for TestNet, false for MainNet.
+    pub fn test(&'a mut self, test: bool) -> &'a mut XrplConfig {
+        self.test = test;
+        self
+    }
+
+    /// Which JavaScript engine runs xpring.js.
+    pub fn js_engine(&'a mut self, js_engine: JsEngine) -> &'a mut XrplConfig {
+        self.js_engine = js_engine;
+        self
+    }
+
+    /// Where the xpring.js bundle is placed for node.js. The embedded engine does not need it.
+    pub fn js_bundle(&'a mut self, js_bundle: JsBundle) -> &'a mut XrplConfig {
+        self.js_bundle = js_bundle;
+        self
+    }
+
+    /// How the node.js processes are supervised.
+    pub fn js_bridge_options(&'a mut self, options: JsBridgeOptions) -> &'a mut XrplConfig {
+        self.js_bridge_options = options;
+        self
+    }
+}
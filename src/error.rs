@@ -0,0 +1,136 @@
+use std::fmt;
+
+/// Class of a transaction engine result, given by the prefix of its code.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum EngineResultCategory {
+    /// `tec`: the transaction failed but was included in a ledger and claimed a fee.
+    Tec,
+    /// `tef`: the transaction failed and cannot succeed in the current ledger state.
+    Tef,
+    /// `tel`: the transaction was rejected locally by the server that received it.
+    Tel,
+    /// `tem`: the transaction is malformed.
+    Tem,
+    /// `ter`: the transaction could not be applied yet and may succeed later.
+    Ter,
+}
+
+impl EngineResultCategory {
+    /// Returns the category of an engine result code such as `tecUNFUNDED_PAYMENT`.
+    pub fn from_code(code: &str) -> Option<EngineResultCategory> {
+        match code.get(..3)? {
+            "tec" => Some(EngineResultCategory::Tec),
+            "tef" => Some(EngineResultCategory::Tef),
+            "tel" => Some(EngineResultCategory::Tel),
+            "tem" => Some(EngineResultCategory::Tem),
+            "ter" => Some(EngineResultCategory::Ter),
+            _ => None,
+        }
+    }
+}
+
+/// Errors returned by xpring-rs.
+#[derive(Debug)]
+pub enum XpringError {
+    /// The connection to the XRP Ledger node or ILP connector failed.
+    Transport(String),
+    /// The node or connector answered with a non-OK gRPC status.
+    Rpc { code: tonic::Code, message: String },
+    /// The node rejected a transaction with the engine result `code` (e.g. `temBAD_FEE`).
+    EngineResult { code: String, message: String },
+    /// The JavaScript bridge could not be started, failed to answer, or xpring.js failed.
+    JavaScript(String),
+    /// An argument was rejected, either by xpring-rs or by xpring.js.
+    Validation(String),
+    /// A response could not be decoded.
+    Decode(String),
+    /// A keystore could not be read or written, or a wallet in it could not be decrypted.
+    Keystore(String),
+    /// The node does not hold the ledger history needed to answer.
+    IncompleteHistory(String),
+}
+
+impl XpringError {
+    /// Returns the engine result category of an `EngineResult` error.
+    pub fn engine_result_category(&self) -> Option<EngineResultCategory> {
+        match self {
+            XpringError::EngineResult { code, .. } => EngineResultCategory::from_code(code),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for XpringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            XpringError::Transport(message) => write!(f, "{}", message),
+            XpringError::Rpc { code, message } => {
+                write!(f, "rpc error ({:?}): {}", code, message)
+            }
+            XpringError::EngineResult { code, message } => write!(f, "{}: {}", code, message),
+            XpringError::JavaScript(message) => write!(f, "javascript error: {}", message),
+            XpringError::Validation(message) => write!(f, "{}", message),
+            XpringError::Decode(message) => write!(f, "{}", message),
+            XpringError::Keystore(message) => write!(f, "keystore error: {}", message),
+            XpringError::IncompleteHistory(message) => {
+                write!(f, "incomplete history: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for XpringError {}
+
+impl From<tonic::transport::Error> for XpringError {
+    fn from(error: tonic::transport::Error) -> Self {
+        XpringError::Transport(error.to_string())
+    }
+}
+
+impl From<tonic::Status> for XpringError {
+    fn from(status: tonic::Status) -> Self {
+        XpringError::Rpc {
+            code: status.code(),
+            message: status.message().to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_engine_result_category() {
+        let error = XpringError::EngineResult {
+            code: "tecUNFUNDED_PAYMENT".to_owned(),
+            message: "Insufficient XRP balance to send.".to_owned(),
+        };
+        assert_eq!(
+            error.engine_result_category(),
+            Some(EngineResultCategory::Tec)
+        );
+        assert_eq!(
+            EngineResultCategory::from_code("temBAD_FEE"),
+            Some(EngineResultCategory::Tem)
+        );
+        assert_eq!(EngineResultCategory::from_code("tesSUCCESS"), None);
+        assert_eq!(
+            XpringError::Validation("x".to_owned()).engine_result_category(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rpc_status_conversion() {
+        let error: XpringError = tonic::Status::not_found("account not found").into();
+        match error {
+            XpringError::Rpc { code, message } => {
+                assert_eq!(code, tonic::Code::NotFound);
+                assert_eq!(message, "account not found");
+            }
+            _ => panic!("expected an rpc error"),
+        }
+    }
+}
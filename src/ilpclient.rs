@@ -1,6 +1,6 @@
+use crate::error::XpringError as Error;
 use crate::i::prelude::*;
-use anyhow::{bail, Error};
-use fehler::throws;
+use fehler::{throw, throws};
 use tokio::runtime::{Builder, Runtime};
 use tonic::{metadata::MetadataValue, transport::Channel, Request};
 
@@ -38,20 +38,23 @@ pub struct IlpClient {
 
 impl IlpClient {
     #[throws(_)]
-    pub(crate) fn connect(url: &'static str, account_id: &'static str, token: &str) -> Self {
+    pub(crate) fn connect(url: &str, account_id: &'static str, token: &str) -> Self {
         let mut rt = Builder::new()
             .basic_scheduler()
             .enable_all()
             .build()
-            .unwrap();
+            .map_err(|e| Error::Transport(e.to_string()))?;
         if token.is_empty() {
-            bail!("token cannot be empty");
+            throw!(Error::Validation("token cannot be empty".to_owned()));
         }
-        let channel = rt.block_on(Channel::from_static(url).connect())?;
+        let endpoint = Channel::from_shared(url.to_owned())
+            .map_err(|e| Error::Validation(format!("Invalid url: {}", e)))?;
+        let channel = rt.block_on(endpoint.connect())?;
         let bearer = format!("Bearer {}", token);
         //TODO is there a better way than creating two clients?
-        let bal_token = MetadataValue::from_str(&bearer)?;
-        let ilp_token = MetadataValue::from_str(&bearer)?;
+        let bal_token = MetadataValue::from_str(&bearer)
+            .map_err(|_| Error::Validation("token is not a valid header value".to_owned()))?;
+        let ilp_token = bal_token.clone();
         let ilp_client = IlpOverHttpServiceClient::with_interceptor(
             channel.clone(),
             move |mut ilp_req: Request<()>| {
@@ -96,7 +99,7 @@ impl IlpClient {
             }
             Err(error) => {
                 // error returned is Unknown, not a lot of information...
-                bail!(format!("balance request failed: {:?}", error.code()));
+                throw!(Error::from(error));
             }
         }
     }
@@ -130,10 +133,9 @@ impl IlpClient {
                     amount_sent: result.amount_sent,
                 }
             }
-            Err(_error) => {
+            Err(error) => {
                 // error returned is Unknown, not a lot of information...
-                println!("*************************_error {:#?}", _error);
-                bail!("payment send failed");
+                throw!(Error::from(error));
             }
         }
     }
@@ -143,13 +145,26 @@ impl IlpClient {
 mod tests {
 
     use super::*;
+    use crate::testing::{MockConnector, MockIlpAccount, MockServer};
 
-    pub const DEFAULT_SERVER_URL: &str = "http://hermes-grpc.ilpv4.dev";
+    /// Connector with the accounts used by these tests.
+    #[throws(_)]
+    fn mock_connector() -> (MockConnector, MockServer) {
+        let connector = MockConnector::new();
+        connector
+            .account("test", MockIlpAccount::new("password", "XRP", 9))
+            .account("sdk_account1", MockIlpAccount::new("password", "XRP", 9))
+            .account("sdk_account2", MockIlpAccount::new("password", "XRP", 9))
+            .account("test.foo.bar", MockIlpAccount::new("password", "XRP", 9));
+        let server = connector.serve()?;
+        (connector, server)
+    }
 
     #[throws(_)]
     #[test]
     fn test_ilp_client_no_password() {
-        match IlpClient::connect(DEFAULT_SERVER_URL, "test", "") {
+        let (_connector, server) = mock_connector()?;
+        match IlpClient::connect(server.url(), "test", "") {
             Ok(_result) => {
                 assert!(false);
             }
@@ -162,7 +177,8 @@ mod tests {
     #[throws(_)]
     #[test]
     fn test_ilp_client_ok() {
-        match IlpClient::connect(DEFAULT_SERVER_URL, "test", "password") {
+        let (_connector, server) = mock_connector()?;
+        match IlpClient::connect(server.url(), "test", "password") {
             Ok(_result) => {
                 assert!(true);
             }
@@ -191,7 +207,8 @@ mod tests {
     #[throws(_)]
     #[test]
     fn test_send() {
-        let mut client = IlpClient::connect(DEFAULT_SERVER_URL, "sdk_account1", "password")?;
+        let (_connector, server) = mock_connector()?;
+        let mut client = IlpClient::connect(server.url(), "sdk_account1", "password")?;
         let original_amount = 12;
         match client.send(
             "$money.ilpv4.dev/sdk_account2".to_owned(),
@@ -213,7 +230,8 @@ mod tests {
     #[throws(_)]
     #[test]
     fn test_send_wrong_account() {
-        let mut client = IlpClient::connect(DEFAULT_SERVER_URL, "test.foo.bar", "password")?;
+        let (_connector, server) = mock_connector()?;
+        let mut client = IlpClient::connect(server.url(), "test.foo.bar", "password")?;
         let original_amount = 12;
         match client.send("$money/baz".to_owned(), original_amount.clone(), 10) {
             Ok(_result) => {
@@ -228,7 +246,8 @@ mod tests {
     #[throws(_)]
     #[test]
     fn test_ilp_client_get_balance() {
-        let mut client = IlpClient::connect(DEFAULT_SERVER_URL, "sdk_account1", "password")?;
+        let (_connector, server) = mock_connector()?;
+        let mut client = IlpClient::connect(server.url(), "sdk_account1", "password")?;
         match client.get_balance() {
             Ok(_result) => {
                 assert!(true);
@@ -238,4 +257,46 @@ mod tests {
             }
         }
     }
+
+    #[throws(_)]
+    #[test]
+    fn test_ilp_client_invalid_token() {
+        let (_connector, server) = mock_connector()?;
+        let mut client = IlpClient::connect(server.url(), "sdk_account1", "wrong")?;
+        match client.get_balance() {
+            Err(Error::Rpc { code, .. }) => assert_eq!(code, tonic::Code::Unauthenticated),
+            result => panic!("expected an authentication error, got {:?}", result),
+        }
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_send_exchange_rate() {
+        let (connector, server) = mock_connector()?;
+        connector
+            .account("euro", MockIlpAccount::new("password", "EUR", 2))
+            .rate("XRP", "EUR", 0.5);
+        let mut client = IlpClient::connect(server.url(), "sdk_account1", "password")?;
+        let result = client.send("$money.ilpv4.dev/euro".to_owned(), 101, 10)?;
+        assert_eq!(result.amount_sent, 101);
+        assert_eq!(result.amount_delivered, 50);
+        assert_eq!(connector.balance("sdk_account1"), Some(-101));
+        assert_eq!(connector.balance("euro"), Some(50));
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_send_failures() {
+        let (connector, server) = mock_connector()?;
+        let mut client = IlpClient::connect(server.url(), "sdk_account1", "password")?;
+        connector.reject_payments(true);
+        let result = client.send("$money.ilpv4.dev/sdk_account2".to_owned(), 12, 10)?;
+        assert_eq!(result.payment_status, IlpPaymentStatus::FAILED);
+        assert_eq!(connector.balance("sdk_account2"), Some(0));
+        connector.fail_with(Some(tonic::Code::Unavailable));
+        match client.send("$money.ilpv4.dev/sdk_account2".to_owned(), 12, 10) {
+            Err(Error::Rpc { code, .. }) => assert_eq!(code, tonic::Code::Unavailable),
+            result => panic!("expected an unavailable error, got {:?}", result),
+        }
+    }
 }
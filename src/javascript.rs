@@ -1,11 +1,16 @@
-use anyhow::{bail, Error};
-use fehler::throws;
-use serde::Serialize;
+use crate::error::XpringError as Error;
+use fehler::{throw, throws};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::io::prelude::*;
 use std::io::BufReader;
-use std::net::{Ipv4Addr, Shutdown, SocketAddrV4, TcpListener, TcpStream};
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
 
 macro_rules! js {
     ($context:ident . $pack:ident . $func:ident ( $($argument:tt),* )) => {
@@ -13,15 +18,17 @@ macro_rules! js {
     };
     ($context:ident . $pack:ident . $func:ident :: < $ty:tt > ( $($argument:tt),* )) => {
         {
-            let result: Result<$ty, anyhow::Error> = {
+            let result: Result<$ty, $crate::error::XpringError> = {
                 let message = JsCall {
                     func: stringify!($pack.$func).to_owned(),
-                    arguments: vec![$(serde_json::to_value($argument)?),*],
+                    arguments: vec![$(serde_json::to_value($argument)
+                        .map_err($crate::javascript::bridge_error)?),*],
                 };
                 let result = $context.execute(message);
                 match result {
                     Ok(value) => {
-                        let ty: $ty = serde_json::from_value(value)?;
+                        let ty: $ty = serde_json::from_value(value)
+                            .map_err($crate::javascript::bridge_error)?;
                         Ok(ty)
                     }
                     Err(err) => Err(err),
@@ -32,65 +39,621 @@ macro_rules! js {
     }
 }
 
+#[cfg(feature = "embedded-js")]
+mod embedded;
+
+pub(crate) fn bridge_error<E: fmt::Display>(error: E) -> Error {
+    Error::JavaScript(error.to_string())
+}
+
+/// How the JavaScript bridge supervises its node.js process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsBridgeOptions {
+    /// Time node.js has to start and connect to the bridge.
+    pub start_timeout: Duration,
+    /// Time a single call has to answer. A process that does not answer in time is killed and a
+    /// new one is started for the next call.
+    pub call_timeout: Duration,
+    /// Times the process is restarted, and the call retried, when it dies during a call.
+    pub max_restarts: u32,
+}
+
+impl Default for JsBridgeOptions {
+    fn default() -> Self {
+        JsBridgeOptions {
+            start_timeout: Duration::from_secs(10),
+            call_timeout: Duration::from_secs(30),
+            max_restarts: 2,
+        }
+    }
+}
+
+/// The JavaScript engine that runs xpring.js.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum JsEngine {
+    /// A node.js process per worker, which must be installed on the host.
+    #[default]
+    Node,
+    /// QuickJS, linked into the binary. Each worker is a thread with its own runtime, and no
+    /// file is written.
+    #[cfg(feature = "embedded-js")]
+    Embedded,
+}
+
 #[derive(Serialize)]
 pub struct JsCall {
     pub func: String,
     pub arguments: Vec<serde_json::Value>,
 }
 
+/// A framed request: the call and the id its response must carry.
+#[derive(Serialize)]
+struct JsRequest<'a> {
+    id: u64,
+    func: &'a str,
+    arguments: &'a [serde_json::Value],
+}
+
+/// Why a call did not get an answer.
+enum CallFailure {
+    Crashed(String),
+    TimedOut,
+    Invalid(String),
+}
+
+/// An exception thrown by xpring.js.
+#[derive(Deserialize)]
+struct JsFailure {
+    message: String,
+    /// Whether xpring.js rejected its input, as opposed to failing while handling it.
+    validation: bool,
+}
+
+/// Environment variable that passes the authentication nonce to node.js. Unlike arguments, the
+/// environment of a process is not visible to other users.
+const NONCE_VARIABLE: &str = "XPRING_BRIDGE_NONCE";
+
+/// The first message of node.js, which must carry the nonce it was started with.
+#[derive(Deserialize)]
+struct Hello {
+    nonce: Option<String>,
+}
+
+/// A node.js process talking to the bridge over its stdin and stdout. Nothing else can reach
+/// these pipes, so private keys never leave the two processes. It is killed when dropped.
+struct Process {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    /// Messages read from stdout, without separator, by a thread so reads can time out.
+    messages: Receiver<Vec<u8>>,
+}
+
+impl Process {
+    #[throws(_)]
+    fn spawn(path: &Path, options: &JsBridgeOptions) -> Process {
+        let mut nonce = [0u8; 32];
+        getrandom::getrandom(&mut nonce).map_err(bridge_error)?;
+        let nonce = hex::encode(nonce);
+        let mut child = Command::new("node")
+            .env("NODE_NO_WARNINGS", "1")
+            .env(NONCE_VARIABLE, &nonce)
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(bridge_error)?;
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+        let (sender, messages) = mpsc::channel();
+        let mut process = Process {
+            child,
+            stdin,
+            messages,
+        };
+        let stdout = stdout.ok_or_else(|| bridge_error("node.js has no stdout"))?;
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            loop {
+                let mut buffer = vec![];
+                // Read until message seperator.
+                match reader.read_until(0x04, &mut buffer) {
+                    Ok(_) if buffer.last() == Some(&0x04) => {
+                        // Remove message seperator from buffer.
+                        buffer.pop();
+                        if sender.send(buffer).is_err() {
+                            break;
+                        }
+                    }
+                    _ => break,
+                }
+            }
+        });
+        let hello = match process.messages.recv_timeout(options.start_timeout) {
+            Ok(hello) => hello,
+            Err(RecvTimeoutError::Timeout) => throw!(Error::JavaScript(format!(
+                "node.js did not connect within {:?}",
+                options.start_timeout
+            ))),
+            Err(RecvTimeoutError::Disconnected) => throw!(Error::JavaScript(format!(
+                "{} before connecting",
+                process.exit_reason()
+            ))),
+        };
+        match serde_json::from_slice::<Hello>(&hello) {
+            Ok(Hello { nonce: Some(echo) }) if echo == nonce => process,
+            _ => throw!(Error::JavaScript(
+                "node.js did not authenticate to the bridge".to_owned()
+            )),
+        }
+    }
+
+    /// Sends a framed request and reads framed responses until the one for request `id`.
+    fn call(
+        &mut self,
+        id: u64,
+        request: &[u8],
+        timeout: Duration,
+    ) -> Result<(serde_json::Value, Option<JsFailure>), CallFailure> {
+        let stdin = match self.stdin.as_mut() {
+            Some(stdin) => stdin,
+            None => return Err(CallFailure::Crashed("not connected".to_owned())),
+        };
+        stdin
+            .write_all(request)
+            .and_then(|_| stdin.flush())
+            .map_err(|e| CallFailure::Crashed(e.to_string()))?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let buffer = match self.messages.recv_timeout(remaining) {
+                Ok(buffer) => buffer,
+                Err(RecvTimeoutError::Timeout) => return Err(CallFailure::TimedOut),
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(CallFailure::Crashed(self.exit_reason()))
+                }
+            };
+            let (ok, err, response_id): (serde_json::Value, Option<JsFailure>, u64) =
+                serde_json::from_slice(&buffer).map_err(|e| CallFailure::Invalid(e.to_string()))?;
+            // Responses to earlier requests are stale, skip them.
+            if response_id == id {
+                return Ok((ok, err));
+            }
+        }
+    }
+
+    /// Describes why the process closed its stdout.
+    fn exit_reason(&mut self) -> String {
+        // Give the process a moment to exit after closing stdout.
+        for _ in 0..20 {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                return format!("node.js exited with {}", status);
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        "node.js closed its stdout".to_owned()
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        // node.js shuts itself down once stdin is closed, but a stuck process would not notice,
+        // so it is killed as well.
+        self.stdin = None;
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// What the workers of a bridge run.
+#[derive(Clone)]
+enum Backend {
+    /// node.js on the bundle at this path.
+    Node(PathBuf),
+    #[cfg(feature = "embedded-js")]
+    Embedded,
+}
+
+/// A started worker.
+enum Runner {
+    Node(Process),
+    #[cfg(feature = "embedded-js")]
+    Embedded(embedded::Engine),
+}
+
+impl Runner {
+    #[throws(_)]
+    fn start(backend: &Backend, options: &JsBridgeOptions) -> Runner {
+        match backend {
+            Backend::Node(path) => Runner::Node(Process::spawn(path, options)?),
+            #[cfg(feature = "embedded-js")]
+            Backend::Embedded => Runner::Embedded(embedded::Engine::spawn(options)?),
+        }
+    }
+
+    fn call(
+        &mut self,
+        id: u64,
+        request: &[u8],
+        timeout: Duration,
+    ) -> Result<(serde_json::Value, Option<JsFailure>), CallFailure> {
+        match self {
+            Runner::Node(process) => process.call(id, request, timeout),
+            #[cfg(feature = "embedded-js")]
+            Runner::Embedded(engine) => engine.call(id, request, timeout),
+        }
+    }
+}
+
+/// Workers of a bridge. A worker is `None` until it is started, or after it died.
+struct Pool {
+    backend: Backend,
+    options: Mutex<JsBridgeOptions>,
+    idle: Mutex<Vec<Option<Runner>>>,
+    available: Condvar,
+    next_id: AtomicU64,
+    workers: usize,
+}
+
+impl Pool {
+    fn lock_idle(&self) -> MutexGuard<'_, Vec<Option<Runner>>> {
+        self.idle.lock().expect("JavaScript bridge pool poisoned")
+    }
+
+    /// Waits for an idle worker.
+    fn checkout(&self) -> Worker<'_> {
+        let mut idle = self.lock_idle();
+        loop {
+            if let Some(runner) = idle.pop() {
+                return Worker { pool: self, runner };
+            }
+            idle = self
+                .available
+                .wait(idle)
+                .expect("JavaScript bridge pool poisoned");
+        }
+    }
+}
+
+/// A worker checked out of the pool. It is returned to the pool when dropped.
+struct Worker<'a> {
+    pool: &'a Pool,
+    runner: Option<Runner>,
+}
+
+impl Drop for Worker<'_> {
+    fn drop(&mut self) {
+        let runner = self.runner.take();
+        let mut idle = self.pool.lock_idle();
+        // Running workers are handed out before idle slots.
+        if runner.is_some() {
+            idle.push(runner);
+        } else {
+            idle.insert(0, runner);
+        }
+        self.pool.available.notify_one();
+    }
+}
+
+/// Handle to the workers running xpring.js.
+///
+/// Clones share the same workers. Each call is sent to an idle worker, so a bridge with several
+/// workers can be used from several threads at once.
+#[derive(Clone)]
 pub struct JavaScript {
-    stream: TcpStream,
+    pool: Arc<Pool>,
 }
 
 impl JavaScript {
+    #[cfg(any(test, feature = "testing"))]
     #[throws(_)]
     pub fn new<P: AsRef<Path>>(path: P) -> Self {
-        let loopback = Ipv4Addr::new(127, 0, 0, 1);
-        // Request an unused port for ipc from the system.
-        let socket = SocketAddrV4::new(loopback, 0);
-        let listener = TcpListener::bind(socket)?;
-        let address = listener.local_addr()?;
-        Command::new("node")
-            .arg(path.as_ref())
-            .arg(address.port().to_string())
-            .spawn()?;
+        JavaScript::with_options(path, JsBridgeOptions::default())?
+    }
+
+    /// Starts node.js on the bundle at `path`, supervised according to `options`.
+    #[cfg(any(test, feature = "testing"))]
+    #[throws(_)]
+    pub fn with_options<P: AsRef<Path>>(path: P, options: JsBridgeOptions) -> Self {
+        JavaScript::pool(path, 1, options)?
+    }
+
+    /// Creates a bridge with `workers` node.js processes. The first one is started right away,
+    /// the others when calls need them.
+    #[throws(_)]
+    pub fn pool<P: AsRef<Path>>(path: P, workers: usize, options: JsBridgeOptions) -> Self {
+        JavaScript::start(Backend::Node(path.as_ref().to_path_buf()), workers, options)?
+    }
+
+    /// Creates a bridge with `workers` embedded QuickJS engines, started like node.js workers.
+    #[cfg(feature = "embedded-js")]
+    #[throws(_)]
+    pub fn embedded(workers: usize, options: JsBridgeOptions) -> Self {
+        JavaScript::start(Backend::Embedded, workers, options)?
+    }
+
+    #[throws(_)]
+    fn start(backend: Backend, workers: usize, options: JsBridgeOptions) -> Self {
+        let runner = Runner::start(&backend, &options)?;
+        let mut idle: Vec<Option<Runner>> = (1..workers.max(1)).map(|_| None).collect();
+        idle.push(Some(runner));
         Self {
-            // All communication will be done over the connection of the first client.
-            stream: listener.accept()?.0,
+            pool: Arc::new(Pool {
+                backend,
+                options: Mutex::new(options),
+                idle: Mutex::new(idle),
+                available: Condvar::new(),
+                next_id: AtomicU64::new(0),
+                workers: workers.max(1),
+            }),
         }
     }
 
+    /// Creates a separate bridge with `workers` workers running what this bridge runs, with its
+    /// options. Its workers stop when it is dropped.
     #[throws(_)]
-    pub fn execute(&mut self, message: JsCall) -> serde_json::Value {
-        serde_json::to_writer(&mut self.stream, &message)?;
+    pub(crate) fn with_workers(&self, workers: usize) -> Self {
+        let options = self
+            .pool
+            .options
+            .lock()
+            .expect("JavaScript bridge pool poisoned")
+            .clone();
+        JavaScript::start(self.pool.backend.clone(), workers, options)?
+    }
+
+    /// Number of calls that can run at once.
+    pub(crate) fn workers(&self) -> usize {
+        self.pool.workers
+    }
+
+    pub(crate) fn set_options(&self, options: JsBridgeOptions) {
+        *self
+            .pool
+            .options
+            .lock()
+            .expect("JavaScript bridge pool poisoned") = options;
+    }
+
+    #[throws(_)]
+    pub fn execute(&self, message: JsCall) -> serde_json::Value {
+        let id = self.pool.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut request = serde_json::to_vec(&JsRequest {
+            id,
+            func: &message.func,
+            arguments: &message.arguments,
+        })
+        .map_err(bridge_error)?;
         // Write message seperator.
-        self.stream.write_all(&[0x04])?;
-        let mut reader = BufReader::new(&mut self.stream);
-        let mut buffer = vec![];
-        // Read until message seperator.
-        reader.read_until(0x04, &mut buffer)?;
-        // Remove message seperator from buffer.
-        buffer.pop();
-        if buffer.is_empty() {
-            serde_json::Value::Null
-        } else {
-            let mut buffer = std::io::Cursor::new(buffer);
-            let (ok, err): (serde_json::Value, Option<String>) =
-                serde_json::from_reader(&mut buffer)?;
-            if let Some(err) = err {
-                bail!(Error::msg(err));
-            } else {
-                serde_json::from_value(ok)?
+        request.push(0x04);
+        let options = self
+            .pool
+            .options
+            .lock()
+            .expect("JavaScript bridge pool poisoned")
+            .clone();
+        let mut worker = self.pool.checkout();
+        let mut restarts = 0;
+        let (ok, err) = loop {
+            let runner = match worker.runner.as_mut() {
+                Some(runner) => runner,
+                None => worker
+                    .runner
+                    .get_or_insert(Runner::start(&self.pool.backend, &options)?),
+            };
+            match runner.call(id, &request, options.call_timeout) {
+                Ok(response) => break response,
+                Err(CallFailure::TimedOut) => {
+                    // Dropping the worker stops it, the next call starts a new one.
+                    worker.runner = None;
+                    throw!(Error::JavaScript(format!(
+                        "{} did not answer within {:?}",
+                        message.func, options.call_timeout
+                    )));
+                }
+                Err(CallFailure::Invalid(reason)) => {
+                    worker.runner = None;
+                    throw!(Error::JavaScript(format!(
+                        "Invalid response to {}: {}",
+                        message.func, reason
+                    )));
+                }
+                Err(CallFailure::Crashed(reason)) => {
+                    worker.runner = None;
+                    if restarts >= options.max_restarts {
+                        throw!(Error::JavaScript(format!(
+                            "{} failed after {} restarts: {}",
+                            message.func, restarts, reason
+                        )));
+                    }
+                    restarts += 1;
+                }
             }
+        };
+        drop(worker);
+        match err {
+            Some(JsFailure {
+                message,
+                validation: true,
+            }) => throw!(Error::Validation(message)),
+            Some(JsFailure { message, .. }) => throw!(Error::JavaScript(message)),
+            None => ok,
         }
     }
 }
 
-impl Drop for JavaScript {
-    fn drop(&mut self) {
-        // By closing the connection to the only client TcpListener will be dropped as well and
-        // which shuts down the server. The node.js client also shuts itself down once the
-        // connection was closed by the server.
-        self.stream.shutdown(Shutdown::Both).unwrap();
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Writes a stand-in for xpring.js that authenticates with `nonce` and then runs `on_data`
+    /// for every chunk it receives, as `data`. It answers through `client`.
+    fn script_with_nonce(name: &str, nonce: &str, on_data: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("xpring-{}-{}.js", name, std::process::id()));
+        let source = format!(
+            "const client = process.stdout;\n\
+             client.write(JSON.stringify({{nonce: {}}}) + '\\x04');\n\
+             process.stdin.on('data', data => {{ {} }});\n\
+             process.stdin.on('end', () => process.exit(0));\n",
+            nonce, on_data
+        );
+        std::fs::write(&path, source).unwrap();
+        path
+    }
+
+    fn script(name: &str, on_data: &str) -> PathBuf {
+        script_with_nonce(name, "process.env.XPRING_BRIDGE_NONCE", on_data)
+    }
+
+    fn call() -> JsCall {
+        JsCall {
+            func: "utils.isValidAddress".to_owned(),
+            arguments: vec![serde_json::json!("rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1")],
+        }
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_restart_after_crash() {
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        assert_eq!(jscontext.execute(call())?, serde_json::json!(true));
+        if let Some(Some(Runner::Node(process))) = jscontext.pool.lock_idle().last_mut() {
+            process.child.kill().unwrap();
+        }
+        assert_eq!(jscontext.execute(call())?, serde_json::json!(true));
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_error_kinds() {
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let invalid = JsCall {
+            func: "wallet.generateWalletFromSeed".to_owned(),
+            arguments: vec![serde_json::json!({"seed": "invalid", "test": true})],
+        };
+        match jscontext.execute(invalid) {
+            Err(Error::Validation(message)) => assert_eq!(message, "Invalid Seed"),
+            result => panic!("expected a validation error, got {:?}", result),
+        }
+        let missing = JsCall {
+            func: "wallet.missing".to_owned(),
+            arguments: vec![],
+        };
+        match jscontext.execute(missing) {
+            Err(Error::JavaScript(_)) => {}
+            result => panic!("expected a JavaScript error, got {:?}", result),
+        }
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_with_workers() {
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let search = jscontext.with_workers(3)?;
+        assert_eq!(search.workers(), 3);
+        assert_eq!(jscontext.workers(), 1);
+        assert_eq!(search.execute(call())?, serde_json::json!(true));
+        drop(search);
+        assert_eq!(jscontext.execute(call())?, serde_json::json!(true));
+    }
+
+    #[test]
+    fn test_bounded_restarts() {
+        let path = script("crash", "process.exit(3);");
+        let options = JsBridgeOptions {
+            max_restarts: 1,
+            ..Default::default()
+        };
+        let jscontext = JavaScript::with_options(&path, options).unwrap();
+        match jscontext.execute(call()) {
+            Err(Error::JavaScript(message)) => assert!(message.contains("after 1 restarts")),
+            result => panic!("expected a bridge error, got {:?}", result),
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_call_timeout() {
+        let path = script("hang", "");
+        let options = JsBridgeOptions {
+            call_timeout: Duration::from_millis(200),
+            ..Default::default()
+        };
+        let jscontext = JavaScript::with_options(&path, options).unwrap();
+        match jscontext.execute(call()) {
+            Err(Error::JavaScript(message)) => assert!(message.contains("did not answer")),
+            result => panic!("expected a timeout, got {:?}", result),
+        }
+        assert!(jscontext.pool.lock_idle().iter().all(Option::is_none));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_exit_before_connecting() {
+        let path = std::env::temp_dir().join(format!("xpring-exit-{}.js", std::process::id()));
+        std::fs::write(&path, "process.exit(2);\n").unwrap();
+        match JavaScript::new(&path) {
+            Err(Error::JavaScript(message)) => assert!(message.contains("before connecting")),
+            Err(e) => panic!("expected a bridge error, got {:?}", e),
+            Ok(_) => panic!("expected a bridge error"),
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_authentication() {
+        let path = script_with_nonce("impostor", "'00'", "");
+        match JavaScript::new(&path) {
+            Err(Error::JavaScript(message)) => assert!(message.contains("did not authenticate")),
+            Err(e) => panic!("expected a bridge error, got {:?}", e),
+            Ok(_) => panic!("expected a bridge error"),
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_stale_responses_are_skipped() {
+        let path = script(
+            "stale",
+            "const id = JSON.parse(data.slice(0, -1)).id;\n\
+             client.write(JSON.stringify([false, null, id + 1000]) + '\\x04');\n\
+             client.write(JSON.stringify([true, null, id]) + '\\x04');",
+        );
+        let jscontext = JavaScript::new(&path)?;
+        assert_eq!(jscontext.execute(call())?, serde_json::json!(true));
+        assert_eq!(jscontext.execute(call())?, serde_json::json!(true));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_pool_from_threads() {
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::pool(
+            format!("{}/xpring.js", out_dir),
+            3,
+            JsBridgeOptions::default(),
+        )?;
+        let threads: Vec<_> = (0..6)
+            .map(|i| {
+                let jscontext = jscontext.clone();
+                thread::spawn(move || {
+                    let address = if i % 2 == 0 {
+                        "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1"
+                    } else {
+                        "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw2"
+                    };
+                    crate::address::is_valid_address(&jscontext, address)
+                })
+            })
+            .collect();
+        for (i, thread) in threads.into_iter().enumerate() {
+            assert_eq!(thread.join().unwrap()?, i % 2 == 0);
+        }
+        assert_eq!(jscontext.pool.lock_idle().len(), 3);
     }
 }
@@ -0,0 +1,286 @@
+//! xpring.js running in QuickJS, linked into the binary, for hosts where node.js cannot be
+//! installed. Each worker is a thread that owns its own runtime.
+use super::{bridge_error, CallFailure, JsBridgeOptions, JsFailure};
+use crate::error::XpringError as Error;
+use fehler::{throw, throws};
+use hmac::{Hmac, Mac, NewMac};
+use ripemd160::Ripemd160;
+use rquickjs::context::EvalOptions;
+use rquickjs::{Context, Ctx, Function, Object, Runtime};
+use sha2::{Digest, Sha256, Sha512};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Provides the node.js globals and modules xpring.js expects, on top of the natives below.
+const PRELUDE: &str = include_str!("../../js/embedded.js");
+const BUNDLE: &str = include_str!("../../js/dist/xpring.js");
+
+// The natives return an empty string for input they do not support, and the prelude turns that
+// into an exception.
+
+fn hash(algorithm: String, data: String) -> String {
+    let data = match hex::decode(data) {
+        Ok(data) => data,
+        Err(_) => return String::new(),
+    };
+    match algorithm.as_str() {
+        "sha256" => hex::encode(Sha256::digest(&data)),
+        "sha512" => hex::encode(Sha512::digest(&data)),
+        "rmd160" | "ripemd160" => hex::encode(Ripemd160::digest(&data)),
+        _ => String::new(),
+    }
+}
+
+fn hmac(algorithm: String, key: String, data: String) -> String {
+    let (key, data) = match (hex::decode(key), hex::decode(data)) {
+        (Ok(key), Ok(data)) => (key, data),
+        _ => return String::new(),
+    };
+    match algorithm.as_str() {
+        "sha256" => Hmac::<Sha256>::new_varkey(&key).map(|mut mac| {
+            mac.update(&data);
+            hex::encode(mac.finalize().into_bytes())
+        }),
+        "sha512" => Hmac::<Sha512>::new_varkey(&key).map(|mut mac| {
+            mac.update(&data);
+            hex::encode(mac.finalize().into_bytes())
+        }),
+        _ => return String::new(),
+    }
+    .unwrap_or_default()
+}
+
+fn random_bytes(size: u32) -> String {
+    let mut bytes = vec![0; size as usize];
+    match getrandom::getrandom(&mut bytes) {
+        Ok(()) => hex::encode(bytes),
+        Err(_) => String::new(),
+    }
+}
+
+/// Describes a failed evaluation, with the message of the JavaScript exception if there is one.
+fn describe(ctx: &Ctx<'_>, error: rquickjs::Error) -> String {
+    if let rquickjs::Error::Exception = error {
+        let exception = ctx.catch();
+        if let Some(message) = exception.as_exception().and_then(|e| e.message()) {
+            return message;
+        }
+        return format!("{:?}", exception);
+    }
+    error.to_string()
+}
+
+fn load(ctx: &Ctx<'_>) -> rquickjs::Result<()> {
+    let native = Object::new(ctx.clone())?;
+    native.set("hash", Function::new(ctx.clone(), hash)?)?;
+    native.set("hmac", Function::new(ctx.clone(), hmac)?)?;
+    native.set("randomBytes", Function::new(ctx.clone(), random_bytes)?)?;
+    ctx.globals().set("__native", native)?;
+    // Parts of the bundled libraries are not strict mode code.
+    let options = || {
+        let mut options = EvalOptions::default();
+        options.strict = false;
+        options
+    };
+    ctx.eval_with_options::<(), _>(PRELUDE, options())?;
+    ctx.eval_with_options::<(), _>(BUNDLE, options())?;
+    Ok(())
+}
+
+/// Loads xpring.js, reports whether that worked and then answers requests until the engine is
+/// dropped.
+fn run(
+    requests: Receiver<String>,
+    responses: Sender<Result<String, String>>,
+    interrupt: Arc<AtomicBool>,
+) {
+    let (runtime, context) = match Runtime::new().and_then(|runtime| {
+        let context = Context::full(&runtime)?;
+        Ok((runtime, context))
+    }) {
+        Ok(engine) => engine,
+        Err(e) => {
+            let _ = responses.send(Err(e.to_string()));
+            return;
+        }
+    };
+    // Lets a call that took too long be aborted from the calling thread.
+    runtime.set_interrupt_handler(Some(Box::new(move || interrupt.load(Ordering::Relaxed))));
+    let loaded = context.with(|ctx| load(&ctx).map_err(|e| describe(&ctx, e)));
+    let failed = loaded.is_err();
+    if responses.send(loaded.map(|_| String::new())).is_err() || failed {
+        return;
+    }
+    for request in requests {
+        let response = context.with(|ctx| {
+            ctx.globals()
+                .get::<_, Function>("__xpringCall")
+                .and_then(|call| call.call::<_, String>((request,)))
+                .map_err(|e| describe(&ctx, e))
+        });
+        if responses.send(response).is_err() {
+            break;
+        }
+    }
+}
+
+/// A QuickJS runtime with xpring.js loaded, on a thread of its own. The runtime is stopped when
+/// the engine is dropped.
+pub(super) struct Engine {
+    requests: Option<Sender<String>>,
+    responses: Receiver<Result<String, String>>,
+    interrupt: Arc<AtomicBool>,
+}
+
+impl Engine {
+    #[throws(_)]
+    pub(super) fn spawn(options: &JsBridgeOptions) -> Engine {
+        let (requests, pending) = mpsc::channel();
+        let (answers, responses) = mpsc::channel();
+        let interrupt = Arc::new(AtomicBool::new(false));
+        let handler = interrupt.clone();
+        thread::Builder::new()
+            .name("xpring-js".to_owned())
+            .spawn(move || run(pending, answers, handler))
+            .map_err(bridge_error)?;
+        let engine = Engine {
+            requests: Some(requests),
+            responses,
+            interrupt,
+        };
+        match engine.responses.recv_timeout(options.start_timeout) {
+            Ok(Ok(_)) => engine,
+            Ok(Err(reason)) => throw!(Error::JavaScript(format!(
+                "xpring.js failed to load: {}",
+                reason
+            ))),
+            Err(RecvTimeoutError::Timeout) => throw!(Error::JavaScript(format!(
+                "xpring.js did not load within {:?}",
+                options.start_timeout
+            ))),
+            Err(RecvTimeoutError::Disconnected) => throw!(Error::JavaScript(
+                "The JavaScript engine stopped while loading xpring.js".to_owned()
+            )),
+        }
+    }
+
+    /// Runs a request framed like the ones sent to node.js and waits for its response.
+    pub(super) fn call(
+        &mut self,
+        id: u64,
+        request: &[u8],
+        timeout: Duration,
+    ) -> Result<(serde_json::Value, Option<JsFailure>), CallFailure> {
+        let request = request.strip_suffix(&[0x04]).unwrap_or(request);
+        let request =
+            String::from_utf8(request.to_vec()).map_err(|e| CallFailure::Invalid(e.to_string()))?;
+        let stopped = || CallFailure::Crashed("The JavaScript engine stopped".to_owned());
+        self.requests
+            .as_ref()
+            .and_then(|requests| requests.send(request).ok())
+            .ok_or_else(stopped)?;
+        let response = match self.responses.recv_timeout(timeout) {
+            Ok(Ok(response)) => response,
+            Ok(Err(reason)) => return Err(CallFailure::Crashed(reason)),
+            Err(RecvTimeoutError::Timeout) => return Err(CallFailure::TimedOut),
+            Err(RecvTimeoutError::Disconnected) => return Err(stopped()),
+        };
+        let (ok, err, response_id): (serde_json::Value, Option<JsFailure>, u64) =
+            serde_json::from_str(&response).map_err(|e| CallFailure::Invalid(e.to_string()))?;
+        // Calls are answered in order and a timed out engine is dropped, so this is a bug.
+        if response_id != id {
+            return Err(CallFailure::Invalid(format!(
+                "response to request {} while waiting for {}",
+                response_id, id
+            )));
+        }
+        Ok((ok, err))
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        // Aborts a running call, then closing the channel ends the thread.
+        self.interrupt.store(true, Ordering::Relaxed);
+        self.requests = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::{JavaScript, JsCall};
+    use super::*;
+    use crate::{address, wallet};
+
+    #[throws(_)]
+    #[test]
+    fn test_embedded_wallet() {
+        let jscontext = JavaScript::embedded(1, JsBridgeOptions::default())?;
+        let wallet = wallet::from_seed(
+            &jscontext,
+            "sn3UJSLzAEeAGcrK3nsQTDZW6KT92".to_owned(),
+            None,
+            true,
+        )?;
+        assert_eq!(
+            wallet.address.as_deref(),
+            Some("T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs")
+        );
+        let signature = wallet::sign(
+            &jscontext,
+            "6d657373616765".to_owned(),
+            wallet.private_key.expose_secret().to_owned(),
+        )?;
+        assert!(wallet::verify(
+            &jscontext,
+            "6d657373616765".to_owned(),
+            signature,
+            wallet.public_key
+        )?);
+        assert!(address::is_valid_address(
+            &jscontext,
+            "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1"
+        )?);
+        let random = wallet::generate_random(&jscontext, None, true)?;
+        assert_eq!(random.mnemonic.expose_secret().split(' ').count(), 12);
+    }
+
+    #[test]
+    fn test_embedded_errors() {
+        let jscontext = JavaScript::embedded(1, JsBridgeOptions::default()).unwrap();
+        let call = JsCall {
+            func: "wallet.generateWalletFromSeed".to_owned(),
+            arguments: vec![serde_json::json!({"seed": "invalid", "test": true})],
+        };
+        assert!(matches!(
+            jscontext.execute(call),
+            Err(Error::Validation(message)) if message == "Invalid Seed"
+        ));
+        let call = JsCall {
+            func: "wallet.missing".to_owned(),
+            arguments: vec![],
+        };
+        assert!(matches!(jscontext.execute(call), Err(Error::JavaScript(_))));
+    }
+
+    #[test]
+    fn test_embedded_call_timeout() {
+        let options = JsBridgeOptions {
+            call_timeout: Duration::from_millis(1),
+            ..Default::default()
+        };
+        let jscontext = JavaScript::embedded(1, options).unwrap();
+        let call = JsCall {
+            func: "wallet.generateRandomWallet".to_owned(),
+            arguments: vec![serde_json::json!({"test": true})],
+        };
+        assert!(matches!(
+            jscontext.execute(call),
+            Err(Error::JavaScript(message)) if message.contains("did not answer")
+        ));
+    }
+}
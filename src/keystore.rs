@@ -0,0 +1,462 @@
+//! Encrypted storage for wallets.
+//!
+//! A keystore is a versioned JSON file of named wallets. Addresses and public keys are stored in
+//! the clear, private keys are encrypted with XChaCha20-Poly1305 under a key derived from the
+//! password of the wallet with scrypt. Each wallet has its own password, salt and nonce.
+use crate::error::XpringError as Error;
+use crate::secret::PrivateKey;
+use crate::wallet::XWallet;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use fehler::{throw, throws};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// Version of the keystore and export formats written by this library.
+pub const KEYSTORE_VERSION: u32 = 1;
+
+#[cfg(not(test))]
+const SCRYPT_LOG_N: u8 = 15;
+// Keeps the tests fast. The parameters are stored with each wallet.
+#[cfg(test)]
+const SCRYPT_LOG_N: u8 = 4;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+// Upper bounds on the parameters read from files, which are not trusted: scrypt needs
+// 128 * r * 2^log_n bytes, 256 MiB at most, and p rounds of it.
+const MAX_SCRYPT_LOG_N: u8 = 18;
+const MAX_SCRYPT_R: u32 = 8;
+const MAX_SCRYPT_P: u32 = 4;
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+struct KdfParams {
+    log_n: u8,
+    r: u32,
+    p: u32,
+    salt: String,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+struct Crypto {
+    kdf: String,
+    kdf_params: KdfParams,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+struct EncryptedWallet {
+    address: Option<String>,
+    public_key: String,
+    test: bool,
+    crypto: Crypto,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeystoreFile {
+    version: u32,
+    wallets: BTreeMap<String, EncryptedWallet>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedWallet {
+    version: u32,
+    wallet: EncryptedWallet,
+}
+
+fn keystore_error<E: std::fmt::Display>(error: E) -> Error {
+    Error::Keystore(error.to_string())
+}
+
+#[throws(_)]
+fn random<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(keystore_error)?;
+    bytes
+}
+
+#[throws(_)]
+fn check_version(version: u32) {
+    if version != KEYSTORE_VERSION {
+        throw!(Error::Keystore(format!(
+            "unsupported keystore version {}",
+            version
+        )));
+    }
+}
+
+impl KdfParams {
+    #[throws(_)]
+    fn check(&self) {
+        if self.log_n > MAX_SCRYPT_LOG_N || self.r > MAX_SCRYPT_R || self.p > MAX_SCRYPT_P {
+            throw!(Error::Keystore(format!(
+                "scrypt parameters log_n={}, r={}, p={} exceed the maximum log_n={}, r={}, p={}",
+                self.log_n, self.r, self.p, MAX_SCRYPT_LOG_N, MAX_SCRYPT_R, MAX_SCRYPT_P
+            )));
+        }
+    }
+}
+
+impl EncryptedWallet {
+    /// The public part of the wallet is authenticated along with the private key, so it cannot
+    /// be changed in the file without the wallet failing to decrypt.
+    fn associated_data(&self) -> String {
+        format!(
+            "{}:{}:{}",
+            self.address.as_deref().unwrap_or(""),
+            self.public_key,
+            self.test
+        )
+    }
+
+    #[throws(_)]
+    fn encrypt(wallet: &XWallet, password: &str) -> EncryptedWallet {
+        let salt = random::<32>()?;
+        let nonce = random::<24>()?;
+        let mut encrypted = EncryptedWallet {
+            address: wallet.address.clone(),
+            public_key: wallet.public_key.clone(),
+            test: wallet.test,
+            crypto: Crypto {
+                kdf: "scrypt".to_owned(),
+                kdf_params: KdfParams {
+                    log_n: SCRYPT_LOG_N,
+                    r: SCRYPT_R,
+                    p: SCRYPT_P,
+                    salt: hex::encode(salt),
+                },
+                cipher: "xchacha20poly1305".to_owned(),
+                nonce: hex::encode(nonce),
+                ciphertext: String::new(),
+            },
+        };
+        let cipher = encrypted.cipher(password)?;
+        let aad = encrypted.associated_data();
+        let ciphertext = cipher
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: wallet.private_key.expose_secret().as_bytes(),
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(keystore_error)?;
+        encrypted.crypto.ciphertext = hex::encode(ciphertext);
+        encrypted
+    }
+
+    #[throws(_)]
+    fn cipher(&self, password: &str) -> XChaCha20Poly1305 {
+        let crypto = &self.crypto;
+        if crypto.kdf != "scrypt" || crypto.cipher != "xchacha20poly1305" {
+            throw!(Error::Keystore(format!(
+                "unsupported encryption {} with {}",
+                crypto.cipher, crypto.kdf
+            )));
+        }
+        let kdf = &crypto.kdf_params;
+        kdf.check()?;
+        let params = scrypt::Params::new(kdf.log_n, kdf.r, kdf.p, 32).map_err(keystore_error)?;
+        let salt = hex::decode(&kdf.salt).map_err(keystore_error)?;
+        // The cipher zeroes its copy of the key when it is dropped.
+        let mut key = Zeroizing::new([0u8; 32]);
+        scrypt::scrypt(password.as_bytes(), &salt, &params, &mut *key).map_err(keystore_error)?;
+        XChaCha20Poly1305::new_from_slice(&*key).map_err(keystore_error)?
+    }
+
+    #[throws(_)]
+    fn decrypt(&self, name: &str, password: &str) -> XWallet {
+        let nonce = hex::decode(&self.crypto.nonce).map_err(keystore_error)?;
+        let ciphertext = hex::decode(&self.crypto.ciphertext).map_err(keystore_error)?;
+        if nonce.len() != 24 {
+            throw!(Error::Keystore(format!(
+                "invalid nonce for wallet {}",
+                name
+            )));
+        }
+        let aad = self.associated_data();
+        let private_key = Zeroizing::new(
+            self.cipher(password)?
+                .decrypt(
+                    XNonce::from_slice(&nonce),
+                    Payload {
+                        msg: &ciphertext,
+                        aad: aad.as_bytes(),
+                    },
+                )
+                .map_err(|_| {
+                    Error::Keystore(format!(
+                        "wrong password for wallet {}, or the wallet was modified",
+                        name
+                    ))
+                })?,
+        );
+        let private_key = std::str::from_utf8(&private_key).map_err(keystore_error)?;
+        XWallet {
+            public_key: self.public_key.clone(),
+            private_key: PrivateKey::new(private_key),
+            test: self.test,
+            address: self.address.clone(),
+        }
+    }
+}
+
+/// Named wallets, encrypted in a JSON file. Every change is written to the file right away.
+///
+/// # Example
+///
+/// ```no_run
+/// # use xpring::Xrpl;
+/// # use xpring::keystore::Keystore;
+/// # fn main() -> Result<(), xpring::error::XpringError> {
+/// # let mut xrpl = Xrpl::new("http://test.xrp.xpring.io:50051", true)?;
+/// let wallet = xrpl.pool().wallet_from_seed("snYP7oArxKepd3GPDcrjMsJYiJeJB", None)?;
+/// let mut keystore = Keystore::open("wallets.json")?;
+/// keystore.add("treasury", &wallet, "correct horse battery staple")?;
+/// let wallet = keystore.wallet("treasury", "correct horse battery staple")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Keystore {
+    path: PathBuf,
+    wallets: BTreeMap<String, EncryptedWallet>,
+}
+
+impl Keystore {
+    /// Opens the keystore at `path`. A missing file is an empty keystore, created on the first
+    /// change.
+    #[throws(_)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Keystore {
+        let path = path.as_ref().to_path_buf();
+        let wallets = if path.exists() {
+            let content = fs::read(&path).map_err(keystore_error)?;
+            let file: KeystoreFile = serde_json::from_slice(&content).map_err(keystore_error)?;
+            check_version(file.version)?;
+            file.wallets
+        } else {
+            BTreeMap::new()
+        };
+        Keystore { path, wallets }
+    }
+
+    /// Names of the wallets, in alphabetical order.
+    pub fn names(&self) -> Vec<&str> {
+        self.wallets.keys().map(String::as_str).collect()
+    }
+
+    /// Address of the wallet `name`, which does not need its password.
+    pub fn address(&self, name: &str) -> Option<&str> {
+        self.wallets.get(name)?.address.as_deref()
+    }
+
+    #[throws(_)]
+    fn entry(&self, name: &str) -> &EncryptedWallet {
+        self.wallets
+            .get(name)
+            .ok_or_else(|| Error::Keystore(format!("no wallet named {}", name)))?
+    }
+
+    #[throws(_)]
+    fn insert(&mut self, name: &str, wallet: EncryptedWallet) {
+        if self.wallets.contains_key(name) {
+            throw!(Error::Keystore(format!("a wallet named {} exists", name)));
+        }
+        self.wallets.insert(name.to_owned(), wallet);
+        self.save()?;
+    }
+
+    /// Encrypts `wallet` with `password` and stores it as `name`, which must not be taken.
+    #[throws(_)]
+    pub fn add(&mut self, name: &str, wallet: &XWallet, password: &str) {
+        let encrypted = EncryptedWallet::encrypt(wallet, password)?;
+        self.insert(name, encrypted)?;
+    }
+
+    /// Decrypts the wallet `name`.
+    #[throws(_)]
+    pub fn wallet(&self, name: &str, password: &str) -> XWallet {
+        self.entry(name)?.decrypt(name, password)?
+    }
+
+    /// Deletes the wallet `name`.
+    #[throws(_)]
+    pub fn remove(&mut self, name: &str) {
+        if self.wallets.remove(name).is_none() {
+            throw!(Error::Keystore(format!("no wallet named {}", name)));
+        }
+        self.save()?;
+    }
+
+    /// Encrypts the wallet `name` again with `new_password`, with a new salt and nonce.
+    #[throws(_)]
+    pub fn change_password(&mut self, name: &str, old_password: &str, new_password: &str) {
+        let wallet = self.wallet(name, old_password)?;
+        let encrypted = EncryptedWallet::encrypt(&wallet, new_password)?;
+        self.wallets.insert(name.to_owned(), encrypted);
+        self.save()?;
+    }
+
+    /// The wallet `name`, still encrypted, as JSON that `import` accepts in another keystore.
+    #[throws(_)]
+    pub fn export(&self, name: &str) -> String {
+        serde_json::to_string_pretty(&ExportedWallet {
+            version: KEYSTORE_VERSION,
+            wallet: self.entry(name)?.clone(),
+        })
+        .map_err(keystore_error)?
+    }
+
+    /// Stores a wallet exported with `export` as `name`, which must not be taken. It keeps the
+    /// password it was exported with.
+    #[throws(_)]
+    pub fn import(&mut self, name: &str, exported: &str) {
+        let exported: ExportedWallet = serde_json::from_str(exported).map_err(keystore_error)?;
+        check_version(exported.version)?;
+        exported.wallet.crypto.kdf_params.check()?;
+        self.insert(name, exported.wallet)?;
+    }
+
+    /// Writes the keystore to a new file that replaces the old one, so a failed write never
+    /// leaves a damaged keystore.
+    #[throws(_)]
+    fn save(&self) {
+        let content = serde_json::to_vec_pretty(&KeystoreFile {
+            version: KEYSTORE_VERSION,
+            wallets: self.wallets.clone(),
+        })
+        .map_err(keystore_error)?;
+        let mut partial = self.path.clone().into_os_string();
+        partial.push(".tmp");
+        // A file left by a failed save keeps its permissions when opened again, so it is
+        // replaced by a new one.
+        match fs::remove_file(&partial) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => throw!(keystore_error(e)),
+            _ => (),
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&partial).map_err(keystore_error)?;
+        file.write_all(&content).map_err(keystore_error)?;
+        file.sync_all().map_err(keystore_error)?;
+        fs::rename(&partial, &self.path).map_err(keystore_error)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn wallet() -> XWallet {
+        XWallet {
+            public_key: "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE"
+                .to_owned(),
+            private_key: "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4"
+                .into(),
+            test: true,
+            address: Some("TVHLFWLKvbMv1LFzd6FA2Bf9MPpcy4mRto4VFAAxLuNpvdW".to_owned()),
+        }
+    }
+
+    fn path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "xpring-keystore-{}-{}.json",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_keystore_round_trip() {
+        let path = path("round-trip");
+        let mut keystore = Keystore::open(&path)?;
+        keystore.add("treasury", &wallet(), "secret")?;
+        assert!(keystore.add("treasury", &wallet(), "other").is_err());
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains(wallet().private_key.expose_secret()));
+
+        let mut keystore = Keystore::open(&path)?;
+        assert_eq!(keystore.names(), vec!["treasury"]);
+        assert_eq!(keystore.address("treasury"), wallet().address.as_deref());
+        assert_eq!(keystore.wallet("treasury", "secret")?, wallet());
+        assert!(matches!(
+            keystore.wallet("treasury", "wrong"),
+            Err(Error::Keystore(_))
+        ));
+
+        keystore.change_password("treasury", "secret", "new secret")?;
+        assert!(keystore.wallet("treasury", "secret").is_err());
+        assert_eq!(keystore.wallet("treasury", "new secret")?, wallet());
+
+        let exported = keystore.export("treasury")?;
+        keystore.remove("treasury")?;
+        assert!(keystore.names().is_empty());
+        keystore.import("imported", &exported)?;
+        assert_eq!(
+            Keystore::open(&path)?.wallet("imported", "new secret")?,
+            wallet()
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[throws(_)]
+    #[test]
+    fn test_keystore_replaces_stale_partial_file() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = path("stale");
+        let mut partial = path.clone().into_os_string();
+        partial.push(".tmp");
+        fs::write(&partial, "stale").unwrap();
+        fs::set_permissions(&partial, fs::Permissions::from_mode(0o666)).unwrap();
+        Keystore::open(&path)?.add("treasury", &wallet(), "secret")?;
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_keystore_tampering_and_versions() {
+        let path = path("tampering");
+        let mut keystore = Keystore::open(&path)?;
+        keystore.add("treasury", &wallet(), "secret")?;
+        let content = fs::read_to_string(&path).unwrap();
+
+        // The address is authenticated with the private key.
+        let tampered = content.replace(
+            "TVHLFWLKvbMv1LFzd6FA2Bf9MPpcy4mRto4VFAAxLuNpvdW",
+            "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+        );
+        fs::write(&path, tampered).unwrap();
+        assert!(Keystore::open(&path)?.wallet("treasury", "secret").is_err());
+
+        // scrypt parameters that would exhaust the memory are refused before deriving.
+        let costly = content.replace("\"log_n\": 4", "\"log_n\": 19");
+        fs::write(&path, &costly).unwrap();
+        assert!(matches!(
+            Keystore::open(&path)?.wallet("treasury", "secret"),
+            Err(Error::Keystore(_))
+        ));
+        let exported = keystore
+            .export("treasury")?
+            .replace("\"log_n\": 4", "\"log_n\": 19");
+        assert!(matches!(
+            keystore.import("costly", &exported),
+            Err(Error::Keystore(_))
+        ));
+
+        let future = content.replace("\"version\": 1", "\"version\": 2");
+        fs::write(&path, future).unwrap();
+        assert!(matches!(Keystore::open(&path), Err(Error::Keystore(_))));
+        fs::remove_file(path).unwrap();
+    }
+}
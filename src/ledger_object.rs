@@ -0,0 +1,711 @@
+//! Ledger objects: public types for every message of `ledger_objects.proto`.
+//!
+//! Every field is optional, as in the protocol buffers: objects read from transaction metadata
+//! only carry the fields that changed. Account IDs are classic addresses, hashes and keys are
+//! upper case hex, and times are seconds since the Ripple epoch (2000-01-01T00:00:00Z).
+use crate::transaction::XCurrencyAmount;
+use crate::x::prelude::*;
+use crate::x::{
+    amendments, ledger_object, Amendments, Check, DepositPreauthObject, DirectoryNode, Escrow,
+    FeeSettings, LedgerHashes, LedgerObject, Offer, PayChannel, RippleState, SignerList,
+};
+use serde::{Deserialize, Serialize};
+
+/// The value of a wrapper message of a number.
+macro_rules! value {
+    ($field:expr) => {
+        $field.as_ref().map(|f| f.value)
+    };
+}
+
+/// The value of a wrapper message of bytes, as hex.
+macro_rules! hex_value {
+    ($field:expr) => {
+        $field.as_ref().map(|f| hex::encode_upper(&f.value))
+    };
+}
+
+/// The value of a wrapper message of an account.
+macro_rules! address {
+    ($field:expr) => {
+        $field
+            .as_ref()
+            .and_then(|f| f.value.as_ref())
+            .map(|a| a.address.clone())
+    };
+}
+
+/// The value of a wrapper message of an amount.
+macro_rules! amount {
+    ($field:expr) => {
+        $field
+            .as_ref()
+            .and_then(|f| f.value.as_ref())
+            .and_then(XCurrencyAmount::from_proto)
+    };
+}
+
+fn currency_code(currency: &Currency) -> String {
+    if currency.name.is_empty() {
+        hex::encode_upper(&currency.code)
+    } else {
+        currency.name.clone()
+    }
+}
+
+/// Type of a ledger object.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum XLedgerEntryType {
+    Unspecified,
+    AccountRoot,
+    Amendments,
+    Check,
+    DepositPreauth,
+    DirectoryNode,
+    Escrow,
+    FeeSettings,
+    LedgerHashes,
+    Offer,
+    PayChannel,
+    RippleState,
+    SignerList,
+}
+
+/// An account, its XRP balance and its settings.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XAccountRoot {
+    pub account: Option<String>,
+    pub balance: Option<XCurrencyAmount>,
+    pub sequence: Option<u32>,
+    pub flags: Option<u32>,
+    pub owner_count: Option<u32>,
+    pub previous_transaction_id: Option<String>,
+    pub previous_transaction_ledger_sequence: Option<u32>,
+    pub account_transaction_id: Option<String>,
+    pub domain: Option<String>,
+    pub email_hash: Option<String>,
+    pub message_key: Option<String>,
+    pub regular_key: Option<String>,
+    pub tick_size: Option<u32>,
+    pub transfer_rate: Option<u32>,
+}
+
+impl XAccountRoot {
+    pub(crate) fn from_proto(account_root: &AccountRoot) -> XAccountRoot {
+        XAccountRoot {
+            account: address!(account_root.account),
+            balance: amount!(account_root.balance),
+            sequence: value!(account_root.sequence),
+            flags: value!(account_root.flags),
+            owner_count: value!(account_root.owner_count),
+            previous_transaction_id: hex_value!(account_root.previous_transaction_id),
+            previous_transaction_ledger_sequence: value!(
+                account_root.previous_transaction_ledger_sequence
+            ),
+            account_transaction_id: hex_value!(account_root.account_transaction_id),
+            domain: account_root.domain.as_ref().map(|d| d.value.clone()),
+            email_hash: hex_value!(account_root.email_hash),
+            message_key: hex_value!(account_root.message_key),
+            regular_key: address!(account_root.regular_key),
+            tick_size: value!(account_root.tick_size),
+            transfer_rate: value!(account_root.transfer_rate),
+        }
+    }
+}
+
+/// An amendment with majority support, and since when.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XMajority {
+    pub amendment: Option<String>,
+    pub close_time: Option<u32>,
+}
+
+/// The enabled amendments and those with majority support.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XAmendments {
+    pub amendments: Vec<String>,
+    pub majorities: Vec<XMajority>,
+    pub flags: Option<u32>,
+}
+
+impl XAmendments {
+    pub(crate) fn from_proto(amendments: &Amendments) -> XAmendments {
+        let amendment = |a: &amendments::Amendment| hex::encode_upper(&a.value);
+        XAmendments {
+            amendments: amendments.amendments.iter().map(amendment).collect(),
+            majorities: amendments
+                .majorities
+                .iter()
+                .map(|m| XMajority {
+                    amendment: m.amendment.as_ref().map(amendment),
+                    close_time: value!(m.close_time),
+                })
+                .collect(),
+            flags: value!(amendments.flags),
+        }
+    }
+}
+
+/// A check that `destination` can cash for up to `send_max`.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XCheck {
+    pub account: Option<String>,
+    pub destination: Option<String>,
+    pub flags: Option<u32>,
+    pub owner_node: Option<u64>,
+    pub previous_transaction_id: Option<String>,
+    pub previous_transaction_ledger_sequence: Option<u32>,
+    pub send_max: Option<XCurrencyAmount>,
+    pub sequence: Option<u32>,
+    pub destination_node: Option<u64>,
+    pub destination_tag: Option<u32>,
+    pub expiration: Option<u32>,
+    pub invoice_id: Option<String>,
+    pub source_tag: Option<u32>,
+}
+
+impl XCheck {
+    pub(crate) fn from_proto(check: &Check) -> XCheck {
+        XCheck {
+            account: address!(check.account),
+            destination: address!(check.destination),
+            flags: value!(check.flags),
+            owner_node: value!(check.owner_node),
+            previous_transaction_id: hex_value!(check.previous_transaction_id),
+            previous_transaction_ledger_sequence: value!(
+                check.previous_transaction_ledger_sequence
+            ),
+            send_max: amount!(check.send_max),
+            sequence: value!(check.sequence),
+            destination_node: value!(check.destination_node),
+            destination_tag: value!(check.destination_tag),
+            expiration: value!(check.expiration),
+            invoice_id: hex_value!(check.invoice_id),
+            source_tag: value!(check.source_tag),
+        }
+    }
+}
+
+/// A preauthorization by `account` of payments from `authorize`.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XDepositPreauth {
+    pub account: Option<String>,
+    pub authorize: Option<String>,
+    pub flags: Option<u32>,
+    pub owner_node: Option<u64>,
+    pub previous_transaction_id: Option<String>,
+    pub previous_transaction_ledger_sequence: Option<u32>,
+}
+
+impl XDepositPreauth {
+    pub(crate) fn from_proto(preauth: &DepositPreauthObject) -> XDepositPreauth {
+        XDepositPreauth {
+            account: address!(preauth.account),
+            authorize: address!(preauth.authorize),
+            flags: value!(preauth.flags),
+            owner_node: value!(preauth.owner_node),
+            previous_transaction_id: hex_value!(preauth.previous_transaction_id),
+            previous_transaction_ledger_sequence: value!(
+                preauth.previous_transaction_ledger_sequence
+            ),
+        }
+    }
+}
+
+/// A page of an owner directory or of an order book directory.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XDirectoryNode {
+    pub flags: Option<u32>,
+    pub root_index: Option<String>,
+    pub indexes: Vec<String>,
+    pub index_next: Option<u64>,
+    pub index_previous: Option<u64>,
+    pub owner: Option<String>,
+    pub taker_pays_currency: Option<String>,
+    pub taker_pays_issuer: Option<String>,
+    pub taker_gets_currency: Option<String>,
+    pub taker_gets_issuer: Option<String>,
+}
+
+impl XDirectoryNode {
+    pub(crate) fn from_proto(directory: &DirectoryNode) -> XDirectoryNode {
+        let currency = |c: Option<&Currency>| c.map(currency_code);
+        XDirectoryNode {
+            flags: value!(directory.flags),
+            root_index: hex_value!(directory.root_index),
+            indexes: directory
+                .indexes
+                .iter()
+                .map(|i| hex::encode_upper(&i.value))
+                .collect(),
+            index_next: value!(directory.index_next),
+            index_previous: value!(directory.index_previous),
+            owner: address!(directory.owner),
+            taker_pays_currency: currency(
+                directory
+                    .taker_pays_currency
+                    .as_ref()
+                    .and_then(|c| c.value.as_ref()),
+            ),
+            taker_pays_issuer: hex_value!(directory.taker_pays_issuer),
+            taker_gets_currency: currency(
+                directory
+                    .taker_gets_currency
+                    .as_ref()
+                    .and_then(|c| c.value.as_ref()),
+            ),
+            taker_gets_issuer: hex_value!(directory.taker_gets_issuer),
+        }
+    }
+}
+
+/// XRP held until `finish_after` or a condition is fulfilled, or returned after `cancel_after`.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XEscrow {
+    pub account: Option<String>,
+    pub destination: Option<String>,
+    pub amount: Option<XCurrencyAmount>,
+    pub condition: Option<String>,
+    pub cancel_after: Option<u32>,
+    pub finish_after: Option<u32>,
+    pub flags: Option<u32>,
+    pub source_tag: Option<u32>,
+    pub destination_tag: Option<u32>,
+    pub owner_node: Option<u64>,
+    pub destination_node: Option<u64>,
+    pub previous_transaction_id: Option<String>,
+    pub previous_transaction_ledger_sequence: Option<u32>,
+}
+
+impl XEscrow {
+    pub(crate) fn from_proto(escrow: &Escrow) -> XEscrow {
+        XEscrow {
+            account: address!(escrow.account),
+            destination: address!(escrow.destination),
+            amount: amount!(escrow.amount),
+            condition: hex_value!(escrow.condition),
+            cancel_after: value!(escrow.cancel_after),
+            finish_after: value!(escrow.finish_after),
+            flags: value!(escrow.flags),
+            source_tag: value!(escrow.source_tag),
+            destination_tag: value!(escrow.destination_tag),
+            owner_node: value!(escrow.owner_node),
+            destination_node: value!(escrow.destination_node),
+            previous_transaction_id: hex_value!(escrow.previous_transaction_id),
+            previous_transaction_ledger_sequence: value!(
+                escrow.previous_transaction_ledger_sequence
+            ),
+        }
+    }
+}
+
+/// The fee and reserve settings of the network.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XFeeSettings {
+    pub base_fee: Option<u64>,
+    pub reference_fee_units: Option<u32>,
+    /// In drops.
+    pub reserve_base: Option<u32>,
+    /// In drops.
+    pub reserve_increment: Option<u32>,
+    pub flags: Option<u32>,
+}
+
+impl XFeeSettings {
+    pub(crate) fn from_proto(settings: &FeeSettings) -> XFeeSettings {
+        XFeeSettings {
+            base_fee: value!(settings.base_fee),
+            reference_fee_units: value!(settings.reference_fee_units),
+            reserve_base: value!(settings.reserve_base),
+            reserve_increment: value!(settings.reserve_increment),
+            flags: value!(settings.flags),
+        }
+    }
+}
+
+/// Hashes of previous ledgers.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XLedgerHashes {
+    pub last_ledger_sequence: Option<u32>,
+    pub hashes: Vec<String>,
+    pub flags: Option<u32>,
+}
+
+impl XLedgerHashes {
+    pub(crate) fn from_proto(hashes: &LedgerHashes) -> XLedgerHashes {
+        XLedgerHashes {
+            last_ledger_sequence: value!(hashes.last_ledger_sequence),
+            hashes: hashes
+                .hashes
+                .iter()
+                .map(|h| hex::encode_upper(&h.value))
+                .collect(),
+            flags: value!(hashes.flags),
+        }
+    }
+}
+
+/// An offer to exchange `taker_gets` for `taker_pays`.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XOffer {
+    pub account: Option<String>,
+    pub sequence: Option<u32>,
+    pub flags: Option<u32>,
+    pub taker_pays: Option<XCurrencyAmount>,
+    pub taker_gets: Option<XCurrencyAmount>,
+    pub book_directory: Option<String>,
+    pub book_node: Option<u64>,
+    pub owner_node: Option<u64>,
+    pub expiration: Option<u32>,
+    pub previous_transaction_id: Option<String>,
+    pub previous_transaction_ledger_sequence: Option<u32>,
+}
+
+impl XOffer {
+    pub(crate) fn from_proto(offer: &Offer) -> XOffer {
+        XOffer {
+            account: address!(offer.account),
+            sequence: value!(offer.sequence),
+            flags: value!(offer.flags),
+            taker_pays: amount!(offer.taker_pays),
+            taker_gets: amount!(offer.taker_gets),
+            book_directory: hex_value!(offer.book_directory),
+            book_node: value!(offer.book_node),
+            owner_node: value!(offer.owner_node),
+            expiration: value!(offer.expiration),
+            previous_transaction_id: hex_value!(offer.previous_transaction_id),
+            previous_transaction_ledger_sequence: value!(
+                offer.previous_transaction_ledger_sequence
+            ),
+        }
+    }
+}
+
+/// A payment channel from `account` to `destination`.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XPayChannel {
+    pub account: Option<String>,
+    pub destination: Option<String>,
+    pub amount: Option<XCurrencyAmount>,
+    pub balance: Option<XCurrencyAmount>,
+    pub public_key: Option<String>,
+    pub settle_delay: Option<u32>,
+    pub owner_node: Option<u64>,
+    pub previous_transaction_id: Option<String>,
+    pub previous_transaction_ledger_sequence: Option<u32>,
+    pub flags: Option<u32>,
+    pub expiration: Option<u32>,
+    pub cancel_after: Option<u32>,
+    pub source_tag: Option<u32>,
+    pub destination_tag: Option<u32>,
+}
+
+impl XPayChannel {
+    pub(crate) fn from_proto(channel: &PayChannel) -> XPayChannel {
+        XPayChannel {
+            account: address!(channel.account),
+            destination: address!(channel.destination),
+            amount: amount!(channel.amount),
+            balance: amount!(channel.balance),
+            public_key: hex_value!(channel.public_key),
+            settle_delay: value!(channel.settle_delay),
+            owner_node: value!(channel.owner_node),
+            previous_transaction_id: hex_value!(channel.previous_transaction_id),
+            previous_transaction_ledger_sequence: value!(
+                channel.previous_transaction_ledger_sequence
+            ),
+            flags: value!(channel.flags),
+            expiration: value!(channel.expiration),
+            cancel_after: value!(channel.cancel_after),
+            source_tag: value!(channel.source_tag),
+            destination_tag: value!(channel.destination_tag),
+        }
+    }
+}
+
+/// A trust line (`RippleState`) between the issuers of `low_limit` and `high_limit`. The
+/// balance is from the point of view of the low account.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XTrustLine {
+    pub balance: Option<XCurrencyAmount>,
+    pub flags: Option<u32>,
+    pub low_limit: Option<XCurrencyAmount>,
+    pub high_limit: Option<XCurrencyAmount>,
+    pub low_node: Option<u64>,
+    pub high_node: Option<u64>,
+    pub low_quality_in: Option<u32>,
+    pub low_quality_out: Option<u32>,
+    pub high_quality_in: Option<u32>,
+    pub high_quality_out: Option<u32>,
+    pub previous_transaction_id: Option<String>,
+    pub previous_transaction_ledger_sequence: Option<u32>,
+}
+
+impl XTrustLine {
+    pub(crate) fn from_proto(state: &RippleState) -> XTrustLine {
+        XTrustLine {
+            balance: amount!(state.balance),
+            flags: value!(state.flags),
+            low_limit: amount!(state.low_limit),
+            high_limit: amount!(state.high_limit),
+            low_node: value!(state.low_node),
+            high_node: value!(state.high_node),
+            low_quality_in: value!(state.low_quality_in),
+            low_quality_out: value!(state.low_quality_out),
+            high_quality_in: value!(state.high_quality_in),
+            high_quality_out: value!(state.high_quality_out),
+            previous_transaction_id: hex_value!(state.previous_transaction_id),
+            previous_transaction_ledger_sequence: value!(
+                state.previous_transaction_ledger_sequence
+            ),
+        }
+    }
+
+    /// Whether `account` is one of the two sides of the trust line.
+    pub fn involves(&self, account: &str) -> bool {
+        let issuer = |limit: &Option<XCurrencyAmount>| match limit {
+            Some(XCurrencyAmount::Issued(issued)) => issued.issuer == account,
+            _ => false,
+        };
+        issuer(&self.low_limit) || issuer(&self.high_limit)
+    }
+}
+
+/// A member of a signer list and its weight.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XSignerEntry {
+    pub account: Option<String>,
+    pub signer_weight: Option<u32>,
+}
+
+/// The signers of an account for multi-signing, with the quorum of weights they need.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XSignerList {
+    pub flags: Option<u32>,
+    pub previous_transaction_id: Option<String>,
+    pub previous_transaction_ledger_sequence: Option<u32>,
+    pub owner_node: Option<u64>,
+    pub signer_entries: Vec<XSignerEntry>,
+    pub signer_list_id: Option<u32>,
+    pub signer_quorum: Option<u32>,
+}
+
+impl XSignerList {
+    pub(crate) fn from_proto(list: &SignerList) -> XSignerList {
+        XSignerList {
+            flags: value!(list.flags),
+            previous_transaction_id: hex_value!(list.previous_transaction_id),
+            previous_transaction_ledger_sequence: value!(list.previous_transaction_ledger_sequence),
+            owner_node: value!(list.owner_node),
+            signer_entries: list
+                .signer_entries
+                .iter()
+                .map(|e| XSignerEntry {
+                    account: address!(e.account),
+                    signer_weight: value!(e.signer_weight),
+                })
+                .collect(),
+            signer_list_id: value!(list.signer_list_id),
+            signer_quorum: value!(list.signer_quorum),
+        }
+    }
+}
+
+/// Any ledger object.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub enum XLedgerObject {
+    AccountRoot(XAccountRoot),
+    Amendments(XAmendments),
+    Check(XCheck),
+    DepositPreauth(XDepositPreauth),
+    DirectoryNode(XDirectoryNode),
+    Escrow(XEscrow),
+    FeeSettings(XFeeSettings),
+    LedgerHashes(XLedgerHashes),
+    Offer(XOffer),
+    PayChannel(XPayChannel),
+    RippleState(XTrustLine),
+    SignerList(XSignerList),
+}
+
+impl XLedgerObject {
+    pub(crate) fn from_proto(object: &LedgerObject) -> Option<XLedgerObject> {
+        Some(match object.object.as_ref()? {
+            ledger_object::Object::AccountRoot(o) => {
+                XLedgerObject::AccountRoot(XAccountRoot::from_proto(o))
+            }
+            ledger_object::Object::Amendments(o) => {
+                XLedgerObject::Amendments(XAmendments::from_proto(o))
+            }
+            ledger_object::Object::Check(o) => XLedgerObject::Check(XCheck::from_proto(o)),
+            ledger_object::Object::DepositPreauth(o) => {
+                XLedgerObject::DepositPreauth(XDepositPreauth::from_proto(o))
+            }
+            ledger_object::Object::DirectoryNode(o) => {
+                XLedgerObject::DirectoryNode(XDirectoryNode::from_proto(o))
+            }
+            ledger_object::Object::Escrow(o) => XLedgerObject::Escrow(XEscrow::from_proto(o)),
+            ledger_object::Object::FeeSettings(o) => {
+                XLedgerObject::FeeSettings(XFeeSettings::from_proto(o))
+            }
+            ledger_object::Object::LedgerHashes(o) => {
+                XLedgerObject::LedgerHashes(XLedgerHashes::from_proto(o))
+            }
+            ledger_object::Object::Offer(o) => XLedgerObject::Offer(XOffer::from_proto(o)),
+            ledger_object::Object::PayChannel(o) => {
+                XLedgerObject::PayChannel(XPayChannel::from_proto(o))
+            }
+            ledger_object::Object::RippleState(o) => {
+                XLedgerObject::RippleState(XTrustLine::from_proto(o))
+            }
+            ledger_object::Object::SignerList(o) => {
+                XLedgerObject::SignerList(XSignerList::from_proto(o))
+            }
+        })
+    }
+
+    /// The type of the object.
+    pub fn entry_type(&self) -> XLedgerEntryType {
+        match self {
+            XLedgerObject::AccountRoot(_) => XLedgerEntryType::AccountRoot,
+            XLedgerObject::Amendments(_) => XLedgerEntryType::Amendments,
+            XLedgerObject::Check(_) => XLedgerEntryType::Check,
+            XLedgerObject::DepositPreauth(_) => XLedgerEntryType::DepositPreauth,
+            XLedgerObject::DirectoryNode(_) => XLedgerEntryType::DirectoryNode,
+            XLedgerObject::Escrow(_) => XLedgerEntryType::Escrow,
+            XLedgerObject::FeeSettings(_) => XLedgerEntryType::FeeSettings,
+            XLedgerObject::LedgerHashes(_) => XLedgerEntryType::LedgerHashes,
+            XLedgerObject::Offer(_) => XLedgerEntryType::Offer,
+            XLedgerObject::PayChannel(_) => XLedgerEntryType::PayChannel,
+            XLedgerObject::RippleState(_) => XLedgerEntryType::RippleState,
+            XLedgerObject::SignerList(_) => XLedgerEntryType::SignerList,
+        }
+    }
+}
+
+/// A ledger object and its index, the key to name it in transactions (e.g. the `CheckID` of a
+/// `CheckCash` or the `Channel` of a `PaymentChannelClaim`).
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct XLedgerEntry<T> {
+    pub index: String,
+    pub object: T,
+}
+
+/// The objects an account owns or is the destination of.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XAccountObjects {
+    pub offers: Vec<XLedgerEntry<XOffer>>,
+    pub escrows: Vec<XLedgerEntry<XEscrow>>,
+    pub checks: Vec<XLedgerEntry<XCheck>>,
+    pub pay_channels: Vec<XLedgerEntry<XPayChannel>>,
+    pub trust_lines: Vec<XLedgerEntry<XTrustLine>>,
+    pub signer_list: Option<XSignerList>,
+    pub deposit_preauths: Vec<XLedgerEntry<XDepositPreauth>>,
+}
+
+impl XAccountObjects {
+    /// Files `object` under its type when `account` owns it or is its destination.
+    pub(crate) fn add(&mut self, account: &str, index: String, object: XLedgerObject) {
+        let is = |address: &Option<String>| address.as_deref() == Some(account);
+        match object {
+            XLedgerObject::Offer(o) if is(&o.account) => {
+                self.offers.push(XLedgerEntry { index, object: o })
+            }
+            XLedgerObject::Escrow(o) if is(&o.account) || is(&o.destination) => {
+                self.escrows.push(XLedgerEntry { index, object: o })
+            }
+            XLedgerObject::Check(o) if is(&o.account) || is(&o.destination) => {
+                self.checks.push(XLedgerEntry { index, object: o })
+            }
+            XLedgerObject::PayChannel(o) if is(&o.account) || is(&o.destination) => {
+                self.pay_channels.push(XLedgerEntry { index, object: o })
+            }
+            XLedgerObject::RippleState(o) if o.involves(account) => {
+                self.trust_lines.push(XLedgerEntry { index, object: o })
+            }
+            XLedgerObject::DepositPreauth(o) if is(&o.account) => self
+                .deposit_preauths
+                .push(XLedgerEntry { index, object: o }),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::x::{HighLimit, LowLimit, SignerEntry, SignerQuorum, SignerWeight};
+
+    fn account(address: &str) -> Option<Account> {
+        Some(Account {
+            value: Some(AccountAddress {
+                address: address.to_owned(),
+            }),
+        })
+    }
+
+    #[test]
+    fn test_from_proto() {
+        let object = LedgerObject {
+            object: Some(ledger_object::Object::SignerList(SignerList {
+                signer_entries: vec![SignerEntry {
+                    account: account("r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4"),
+                    signer_weight: Some(SignerWeight { value: 1 }),
+                }],
+                signer_quorum: Some(SignerQuorum { value: 1 }),
+                ..Default::default()
+            })),
+        };
+        let list = match XLedgerObject::from_proto(&object) {
+            Some(XLedgerObject::SignerList(list)) => list,
+            other => panic!("Unexpected object {:?}", other),
+        };
+        assert_eq!(list.signer_quorum, Some(1));
+        assert_eq!(list.flags, None);
+        assert_eq!(
+            list.signer_entries,
+            vec![XSignerEntry {
+                account: Some("r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4".to_owned()),
+                signer_weight: Some(1),
+            }]
+        );
+        assert_eq!(XLedgerObject::from_proto(&LedgerObject::default()), None);
+    }
+
+    #[test]
+    fn test_account_objects_add() {
+        let owner = "r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4";
+        let other = "rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7";
+        let line = |low: &str, high: &str| {
+            XLedgerObject::RippleState(XTrustLine::from_proto(&RippleState {
+                low_limit: Some(LowLimit {
+                    value: Some(XCurrencyAmount::issued("USD", low, "0").to_proto()),
+                }),
+                high_limit: Some(HighLimit {
+                    value: Some(XCurrencyAmount::issued("USD", high, "10").to_proto()),
+                }),
+                ..Default::default()
+            }))
+        };
+        let offer = |address: &str| {
+            XLedgerObject::Offer(XOffer {
+                account: Some(address.to_owned()),
+                ..Default::default()
+            })
+        };
+        let mut objects = XAccountObjects::default();
+        objects.add(owner, "01".to_owned(), line(owner, other));
+        objects.add(owner, "02".to_owned(), line(other, other));
+        objects.add(owner, "03".to_owned(), offer(owner));
+        objects.add(owner, "04".to_owned(), offer(other));
+        assert_eq!(objects.trust_lines.len(), 1);
+        assert_eq!(objects.trust_lines[0].index, "01");
+        assert_eq!(objects.offers.len(), 1);
+        assert_eq!(objects.offers[0].index, "03");
+        assert_eq!(
+            line(owner, other).entry_type(),
+            XLedgerEntryType::RippleState
+        );
+    }
+}
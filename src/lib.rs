@@ -17,20 +17,25 @@
 // Private modules
 #[macro_use]
 mod javascript;
+mod bundle;
 mod config;
+mod recording;
 mod util;
 mod xpring;
 mod x {
     tonic::include_proto!("org.xrpl.rpc.v1");
     pub mod prelude {
         pub use super::{
-            currency_amount, get_transaction_response, payment::Path as xPath,
+            currency_amount, get_account_transaction_history_request, get_transaction_response,
+            payment::Path as xPath, payment::PathElement as xPathElement,
             transaction::TransactionData, xrp_ledger_api_service_client::XrpLedgerApiServiceClient,
-            Account, AccountAddress, AccountRoot, Amount, CurrencyAmount, Destination,
-            GetAccountInfoRequest, GetFeeRequest, GetFeeResponse, GetTransactionRequest,
-            GetTransactionResponse, LastLedgerSequence, LedgerRange, Memo, Payment, Sequence,
-            Signer, SigningPublicKey, SubmitTransactionRequest, Transaction, TransactionResult,
-            XrpDropsAmount,
+            Account, AccountAddress, AccountRoot, Amount, Currency, CurrencyAmount, DeliverMin,
+            Destination, DestinationTag, Flags, GetAccountInfoRequest,
+            GetAccountTransactionHistoryRequest, GetFeeRequest, GetFeeResponse,
+            GetTransactionRequest, GetTransactionResponse, InvoiceId, IssuedCurrencyAmount,
+            LastLedgerSequence, LedgerRange, Memo, MemoData, MemoFormat, MemoType, Payment,
+            SendMax, Sequence, Signer, SigningPublicKey, SourceTag, SubmitTransactionRequest,
+            Transaction, TransactionResult, XrpDropsAmount,
         };
     }
 }
@@ -47,9 +52,22 @@ mod i {
 
 // Public modules
 pub mod address;
+pub mod challenge;
+pub mod error;
 pub mod ilpclient;
+pub mod keystore;
+pub mod ledger_object;
+pub mod message;
+pub mod secret;
+pub mod shamir;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod transaction;
+pub mod vanity;
 pub mod wallet;
 pub mod xrplclient;
 
-pub use crate::xpring::{Ilp, Xrpl};
+pub use crate::bundle::JsBundle;
+pub use crate::config::XrplConfig;
+pub use crate::javascript::{JsBridgeOptions, JsEngine};
+pub use crate::xpring::{Ilp, Xrpl, XrplPool};
@@ -0,0 +1,358 @@
+//! Signing of arbitrary messages and of prehashed digests.
+//!
+//! A message is never signed as it is. It is hashed with SHA-512Half under its own hash prefix
+//! and a domain chosen by the application, so a message signature cannot pass for a transaction
+//! signature, nor for a message signature of another application. Digests computed elsewhere
+//! can be signed directly with `sign_digest`.
+//!
+//! secp256k1 signatures are DER encoded and always low-S. Verification reports non-canonical
+//! signatures apart from valid ones, and rejects malformed keys and signatures with an error.
+use crate::error::XpringError as Error;
+use crate::javascript::{JavaScript, JsCall};
+use crate::secret::{self, PrivateKey};
+use crate::wallet::KeyAlgorithm;
+use fehler::{throw, throws};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+/// Hash prefix of signed messages, `MSG\0`. The XRP Ledger uses others, such as `STX\0` for
+/// transactions.
+pub const MESSAGE_HASH_PREFIX: [u8; 4] = *b"MSG\0";
+
+/// Half of the order of secp256k1: canonical signatures have an S at most this large.
+const SECP256K1_HALF_ORDER: [u8; 32] = [
+    0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0x5D, 0x57, 0x6E, 0x73, 0x57, 0xA4, 0x50, 0x1D, 0xDF, 0xE9, 0x2F, 0x46, 0x68, 0x1B, 0x20, 0xA0,
+];
+
+/// Order of the ed25519 group, big-endian: canonical signatures have an S below it.
+const ED25519_ORDER: [u8; 32] = [
+    0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x14, 0xDE, 0xF9, 0xDE, 0xA2, 0xF7, 0x9C, 0xD6, 0x58, 0x12, 0x63, 0x1A, 0x5C, 0xF5, 0xD3, 0xED,
+];
+
+/// A signature and the public key that verifies it, in upper case hex.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct XMessageSignature {
+    /// DER encoded for secp256k1, 64 bytes for ed25519.
+    pub signature: String,
+    pub public_key: String,
+    pub algorithm: KeyAlgorithm,
+}
+
+/// Outcome of the verification of a well-formed signature.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum XSignatureVerification {
+    /// The signature is canonical and made by the public key for the message.
+    Valid,
+    /// The signature matches but is not canonical: its S is high (secp256k1) or not reduced
+    /// (ed25519). Anyone can derive such a signature from a valid one, and the XRP Ledger
+    /// rejects them.
+    NonCanonical,
+    /// The signature was not made by the public key for the message.
+    Mismatch,
+}
+
+impl XSignatureVerification {
+    /// Returns true for `Valid` signatures only.
+    pub fn is_valid(&self) -> bool {
+        *self == XSignatureVerification::Valid
+    }
+}
+
+#[derive(Serialize)]
+struct XSignDigestOptions<'a> {
+    digest: String,
+    #[serde(serialize_with = "secret::expose")]
+    private_key: &'a PrivateKey,
+    algorithm: KeyAlgorithm,
+}
+
+#[derive(Deserialize)]
+struct XDigestSignature {
+    signature: String,
+    public_key: String,
+}
+
+#[derive(Serialize)]
+struct XVerifyDigestOptions<'a> {
+    digest: String,
+    public_key: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    r: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    s: Option<String>,
+}
+
+/// Hashes `message` for signing in `domain`, a name of at most 255 bytes for the application
+/// and purpose of the signature: SHA-512Half of the hash prefix, the length of the domain, the
+/// domain and the message.
+#[throws(_)]
+pub fn message_digest(domain: &str, message: &[u8]) -> [u8; 32] {
+    if domain.len() > u8::MAX as usize {
+        throw!(Error::Validation(format!(
+            "The signing domain is {} bytes long, at most 255 are allowed",
+            domain.len()
+        )));
+    }
+    let mut hasher = Sha512::new();
+    hasher.update(MESSAGE_HASH_PREFIX);
+    hasher.update([domain.len() as u8]);
+    hasher.update(domain.as_bytes());
+    hasher.update(message);
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&hasher.finalize()[..32]);
+    digest
+}
+
+/// Parses a strict DER secp256k1 signature into its R and S.
+#[throws(_)]
+fn parse_der(signature: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let invalid = || Error::Validation("Invalid DER signature".to_owned());
+    let integer = |bytes: &[u8]| -> Result<([u8; 32], usize), Error> {
+        let length = *bytes.get(1).ok_or_else(invalid)? as usize;
+        let value = bytes.get(2..2 + length).ok_or_else(invalid)?;
+        // Positive, minimally encoded and at most 32 bytes once the sign byte is removed.
+        if bytes[0] != 0x02
+            || value.is_empty()
+            || value[0] & 0x80 != 0
+            || (value.len() > 1 && value[0] == 0 && value[1] & 0x80 == 0)
+        {
+            return Err(invalid());
+        }
+        let value = if value[0] == 0 { &value[1..] } else { value };
+        if value.len() > 32 || value.iter().all(|b| *b == 0) {
+            return Err(invalid());
+        }
+        let mut padded = [0u8; 32];
+        padded[32 - value.len()..].copy_from_slice(value);
+        Ok((padded, 2 + length))
+    };
+    if signature.len() < 8 || signature[0] != 0x30 || signature[1] as usize != signature.len() - 2 {
+        throw!(invalid());
+    }
+    let (r, r_length) = integer(&signature[2..])?;
+    let (s, s_length) = integer(&signature[2 + r_length..])?;
+    if 2 + r_length + s_length != signature.len() {
+        throw!(invalid());
+    }
+    (r, s)
+}
+
+#[throws(_)]
+fn decode_hex(value: &str, what: &str) -> Vec<u8> {
+    hex::decode(value).map_err(|e| Error::Validation(format!("Invalid {}: {}", what, e)))?
+}
+
+#[throws(_)]
+pub(crate) fn sign_digest(
+    jscontext: &JavaScript,
+    digest: &[u8; 32],
+    private_key: &PrivateKey,
+    algorithm: KeyAlgorithm,
+) -> XMessageSignature {
+    let options = XSignDigestOptions {
+        digest: hex::encode_upper(digest),
+        private_key,
+        algorithm,
+    };
+    let signed = js!(jscontext.wallet.signDigest::<XDigestSignature>(options))?;
+    XMessageSignature {
+        signature: signed.signature,
+        public_key: signed.public_key,
+        algorithm,
+    }
+}
+
+#[throws(_)]
+pub(crate) fn sign_message(
+    jscontext: &JavaScript,
+    message: &[u8],
+    domain: &str,
+    private_key: &PrivateKey,
+    algorithm: KeyAlgorithm,
+) -> XMessageSignature {
+    sign_digest(
+        jscontext,
+        &message_digest(domain, message)?,
+        private_key,
+        algorithm,
+    )?
+}
+
+#[throws(_)]
+pub(crate) fn verify_digest(
+    jscontext: &JavaScript,
+    digest: &[u8; 32],
+    signature: &str,
+    public_key: &str,
+) -> XSignatureVerification {
+    let key = decode_hex(public_key, "public key")?;
+    let signature_bytes = decode_hex(signature, "signature")?;
+    let mut options = XVerifyDigestOptions {
+        digest: hex::encode_upper(digest),
+        public_key,
+        signature: None,
+        r: None,
+        s: None,
+    };
+    let canonical = match key.first() {
+        Some(0xED) if key.len() == 33 => {
+            if signature_bytes.len() != 64 {
+                throw!(Error::Validation(
+                    "An ed25519 signature is 64 bytes long".to_owned()
+                ));
+            }
+            options.signature = Some(signature);
+            let mut s: Vec<u8> = signature_bytes[32..].to_vec();
+            s.reverse();
+            s[..] < ED25519_ORDER[..]
+        }
+        Some(0x02) | Some(0x03) if key.len() == 33 => {
+            let (r, s) = parse_der(&signature_bytes)?;
+            options.r = Some(hex::encode(r));
+            options.s = Some(hex::encode(s));
+            s <= SECP256K1_HALF_ORDER
+        }
+        _ => throw!(Error::Validation(format!(
+            "Invalid public key {}",
+            public_key
+        ))),
+    };
+    let matches = js!(jscontext.wallet.verifyDigest::<bool>(options))?;
+    match (matches, canonical) {
+        (false, _) => XSignatureVerification::Mismatch,
+        (true, false) => XSignatureVerification::NonCanonical,
+        (true, true) => XSignatureVerification::Valid,
+    }
+}
+
+#[throws(_)]
+pub(crate) fn verify_message(
+    jscontext: &JavaScript,
+    message: &[u8],
+    domain: &str,
+    signature: &str,
+    public_key: &str,
+) -> XSignatureVerification {
+    verify_digest(
+        jscontext,
+        &message_digest(domain, message)?,
+        signature,
+        public_key,
+    )?
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const PRIVATE_KEY: &str = "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4";
+    const PUBLIC_KEY: &str = "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE";
+    const SECP256K1_ORDER: [u8; 32] = [
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        0xFE, 0xBA, 0xAE, 0xDC, 0xE6, 0xAF, 0x48, 0xA0, 0x3B, 0xBF, 0xD2, 0x5E, 0x8C, 0xD0, 0x36,
+        0x41, 0x41,
+    ];
+
+    fn jscontext() -> JavaScript {
+        JavaScript::new(format!("{}/xpring.js", std::env::var("OUT_DIR").unwrap())).unwrap()
+    }
+
+    /// Encodes R and N - S, the high-S twin of a low-S signature.
+    fn high_s(signature: &str) -> String {
+        let (r, s) = parse_der(&hex::decode(signature).unwrap()).unwrap();
+        let mut high = [0u8; 32];
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let mut byte = SECP256K1_ORDER[i] as i16 - s[i] as i16 - borrow;
+            borrow = (byte < 0) as i16;
+            if byte < 0 {
+                byte += 256;
+            }
+            high[i] = byte as u8;
+        }
+        let r: Vec<u8> = [&[0u8][..], &r[..]].concat();
+        let high: Vec<u8> = [&[0u8][..], &high[..]].concat();
+        let r = if r[1] & 0x80 != 0 { &r[..] } else { &r[1..] };
+        let mut der = vec![0x30, (4 + r.len() + high.len()) as u8, 0x02, r.len() as u8];
+        der.extend_from_slice(r);
+        der.extend_from_slice(&[0x02, high.len() as u8]);
+        der.extend_from_slice(&high);
+        hex::encode_upper(der)
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_message_digest() {
+        let digest = message_digest("example.com login", b"hello")?;
+        assert_ne!(digest, message_digest("example.com payment", b"hello")?);
+        assert_ne!(digest, message_digest("example.com logi", b"nhello")?);
+        assert!(message_digest(&"x".repeat(256), b"hello").is_err());
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_sign_and_verify_secp256k1() {
+        let jscontext = jscontext();
+        let key = PrivateKey::from(PRIVATE_KEY);
+        let signed = sign_message(&jscontext, b"hello", "test", &key, KeyAlgorithm::Secp256k1)?;
+        assert_eq!(signed.public_key, PUBLIC_KEY);
+        let (_, s) = parse_der(&hex::decode(&signed.signature).unwrap())?;
+        assert!(s <= SECP256K1_HALF_ORDER);
+        let verify = |jscontext: &JavaScript, message: &[u8], domain, signature: &str| {
+            verify_message(jscontext, message, domain, signature, PUBLIC_KEY)
+        };
+        assert_eq!(
+            verify(&jscontext, b"hello", "test", &signed.signature)?,
+            XSignatureVerification::Valid
+        );
+        assert_eq!(
+            verify(&jscontext, b"hello", "other", &signed.signature)?,
+            XSignatureVerification::Mismatch
+        );
+        assert_eq!(
+            verify(&jscontext, b"hello", "test", &high_s(&signed.signature))?,
+            XSignatureVerification::NonCanonical
+        );
+        match verify(&jscontext, b"hello", "test", "DEADBEEF") {
+            Err(Error::Validation(_)) => (),
+            other => panic!("Unexpected result {:?}", other),
+        }
+        // A bare 32 byte key is the same key.
+        let bare = PrivateKey::from(&PRIVATE_KEY[2..]);
+        let digest = message_digest("test", b"hello")?;
+        assert_eq!(
+            sign_digest(&jscontext, &digest, &bare, KeyAlgorithm::Secp256k1)?,
+            signed
+        );
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_sign_and_verify_ed25519() {
+        let jscontext = jscontext();
+        let digest = [7u8; 32];
+        let key = PrivateKey::from(format!("ED{}", "11".repeat(32)));
+        let signed = sign_digest(&jscontext, &digest, &key, KeyAlgorithm::Ed25519)?;
+        assert_eq!(signed.algorithm, KeyAlgorithm::Ed25519);
+        assert!(signed.public_key.starts_with("ED"));
+        assert!(
+            verify_digest(&jscontext, &digest, &signed.signature, &signed.public_key)?.is_valid()
+        );
+        assert_eq!(
+            verify_digest(
+                &jscontext,
+                &[8u8; 32],
+                &signed.signature,
+                &signed.public_key
+            )?,
+            XSignatureVerification::Mismatch
+        );
+        assert!(sign_digest(&jscontext, &digest, &key, KeyAlgorithm::Secp256k1).is_err());
+        assert!(verify_digest(&jscontext, &digest, "00", &signed.public_key).is_err());
+    }
+}
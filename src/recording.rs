@@ -0,0 +1,158 @@
+//! Record and replay of the gRPC exchanges of `XrplClient`.
+//!
+//! A recording is a text file with one JSON interaction per line: the RPC method, the protobuf
+//! encoded request and either the protobuf encoded response or the gRPC status, all hex encoded.
+use crate::error::XpringError as Error;
+use fehler::{throw, throws};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+struct RecordedStatus {
+    code: i32,
+    message: String,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Interaction {
+    method: String,
+    request: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    response: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<RecordedStatus>,
+}
+
+fn recording_error<E: std::fmt::Display>(error: E) -> Error {
+    Error::Transport(format!("Recording error: {}", error))
+}
+
+pub(crate) enum Cassette {
+    Record(BufWriter<File>),
+    Replay {
+        interactions: Vec<Interaction>,
+        position: usize,
+    },
+}
+
+impl Cassette {
+    /// Starts a new recording at `path`, replacing any previous one.
+    #[throws(_)]
+    pub(crate) fn record<P: AsRef<Path>>(path: P) -> Cassette {
+        Cassette::Record(BufWriter::new(File::create(path).map_err(recording_error)?))
+    }
+
+    /// Loads the recording at `path`.
+    #[throws(_)]
+    pub(crate) fn replay<P: AsRef<Path>>(path: P) -> Cassette {
+        let content = fs::read_to_string(path).map_err(recording_error)?;
+        let interactions = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<Interaction>, _>>()
+            .map_err(recording_error)?;
+        Cassette::Replay {
+            interactions,
+            position: 0,
+        }
+    }
+
+    pub(crate) fn is_replay(&self) -> bool {
+        matches!(self, Cassette::Replay { .. })
+    }
+
+    /// Appends an exchange to the recording.
+    #[throws(_)]
+    pub(crate) fn write(
+        &mut self,
+        method: &str,
+        request: &[u8],
+        response: Result<&[u8], &tonic::Status>,
+    ) {
+        if let Cassette::Record(writer) = self {
+            let interaction = Interaction {
+                method: method.to_owned(),
+                request: hex::encode(request),
+                response: response.ok().map(hex::encode),
+                status: response.err().map(|status| RecordedStatus {
+                    code: status.code() as i32,
+                    message: status.message().to_owned(),
+                }),
+            };
+            serde_json::to_writer(&mut *writer, &interaction).map_err(recording_error)?;
+            writer.write_all(b"\n").map_err(recording_error)?;
+            writer.flush().map_err(recording_error)?;
+        }
+    }
+
+    /// Returns the recorded answer to the next request, which must be the request that was
+    /// recorded at this point.
+    #[throws(_)]
+    pub(crate) fn next(&mut self, method: &str, request: &[u8]) -> Result<Vec<u8>, tonic::Status> {
+        let (interactions, position) = match self {
+            Cassette::Replay {
+                interactions,
+                position,
+            } => (interactions, position),
+            Cassette::Record(_) => throw!(recording_error("not replaying")),
+        };
+        let interaction = interactions.get(*position).ok_or_else(|| {
+            recording_error(format!(
+                "no interaction recorded for {} after {} interactions",
+                method, position
+            ))
+        })?;
+        if interaction.method != method || interaction.request != hex::encode(request) {
+            throw!(recording_error(format!(
+                "interaction {} does not match: {} was recorded but {} was requested with different arguments",
+                position, interaction.method, method
+            )));
+        }
+        *position += 1;
+        match (&interaction.response, &interaction.status) {
+            (Some(response), _) => Ok(hex::decode(response).map_err(recording_error)?),
+            (None, Some(status)) => Err(tonic::Status::new(
+                tonic::Code::from_i32(status.code),
+                status.message.clone(),
+            )),
+            (None, None) => throw!(recording_error(format!(
+                "interaction {} has neither a response nor a status",
+                position
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[throws(_)]
+    #[test]
+    fn test_record_and_replay() {
+        let path = std::env::temp_dir().join(format!("xpring-cassette-{}", std::process::id()));
+        let mut cassette = Cassette::record(&path)?;
+        assert!(!cassette.is_replay());
+        cassette.write("GetFee", &[], Ok(&[1, 2, 3]))?;
+        cassette.write(
+            "GetAccountInfo",
+            &[4],
+            Err(&tonic::Status::not_found("account not found")),
+        )?;
+        drop(cassette);
+
+        let mut cassette = Cassette::replay(&path)?;
+        assert!(cassette.is_replay());
+        assert_eq!(cassette.next("GetFee", &[])?.unwrap(), vec![1, 2, 3]);
+        assert!(cassette.next("GetAccountInfo", &[5]).is_err());
+        let status = cassette.next("GetAccountInfo", &[4])?.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        assert_eq!(status.message(), "account not found");
+        assert!(cassette.next("GetFee", &[]).is_err());
+        fs::remove_file(path).map_err(recording_error)?;
+    }
+}
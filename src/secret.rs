@@ -0,0 +1,147 @@
+//! Types for secret material: private keys, seeds, mnemonics and their shares.
+//!
+//! Their memory is zeroed when they are dropped, `Debug` and `Display` print `<redacted>` and
+//! they do not implement `Serialize`. The secret is read with `expose_secret`, and a field can
+//! opt in to serialization with `#[serde(serialize_with = "xpring::secret::expose")]`.
+use serde::{Deserialize, Deserializer, Serializer};
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// Secret material that can be read on request, for `expose`.
+pub trait ExposeSecret {
+    /// Returns the secret itself.
+    fn expose_secret(&self) -> &str;
+}
+
+impl<T: ExposeSecret + ?Sized> ExposeSecret for &T {
+    fn expose_secret(&self) -> &str {
+        (**self).expose_secret()
+    }
+}
+
+macro_rules! secret {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Clone, PartialEq, Eq)]
+        pub struct $name(Zeroizing<String>);
+
+        impl $name {
+            pub fn new<S: Into<String>>(secret: S) -> $name {
+                $name(Zeroizing::new(secret.into()))
+            }
+
+            /// Returns the secret itself.
+            pub fn expose_secret(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl ExposeSecret for $name {
+            fn expose_secret(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(secret: String) -> $name {
+                $name::new(secret)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(secret: &str) -> $name {
+                $name::new(secret)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}(<redacted>)", stringify!($name))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("<redacted>")
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$name, D::Error> {
+                String::deserialize(deserializer).map($name::new)
+            }
+        }
+    };
+}
+
+secret!(
+    /// A hex encoded private key.
+    PrivateKey
+);
+secret!(
+    /// A base58check encoded family seed (`s...`).
+    Seed
+);
+secret!(
+    /// A BIP39 mnemonic.
+    Mnemonic
+);
+secret!(
+    /// A SLIP-39 mnemonic share of a secret.
+    Share
+);
+
+/// Serializes a secret as its plain text, for fields that opt in with
+/// `#[serde(serialize_with = "xpring::secret::expose")]`.
+pub fn expose<T: ExposeSecret, S: Serializer>(
+    secret: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(secret.expose_secret())
+}
+
+/// Same as `expose`, for optional secrets.
+pub fn expose_option<T: ExposeSecret, S: Serializer>(
+    secret: &Option<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match secret {
+        Some(secret) => serializer.serialize_some(secret.expose_secret()),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct Exposed<'a> {
+        #[serde(serialize_with = "expose")]
+        private_key: &'a PrivateKey,
+        #[serde(serialize_with = "expose_option")]
+        seed: Option<Seed>,
+    }
+
+    #[test]
+    fn test_redacted_and_exposed() {
+        let private_key = PrivateKey::new("0090802A50AA84EF");
+        assert_eq!(format!("{:?}", private_key), "PrivateKey(<redacted>)");
+        assert_eq!(private_key.to_string(), "<redacted>");
+        assert_eq!(private_key.expose_secret(), "0090802A50AA84EF");
+
+        let mnemonic: Mnemonic = serde_json::from_str("\"abandon about\"").unwrap();
+        assert_eq!(mnemonic, Mnemonic::from("abandon about"));
+
+        let exposed = Exposed {
+            private_key: &private_key,
+            seed: Some("snYP7oArxKepd3GPDcrjMsJYiJeJB".into()),
+        };
+        assert_eq!(
+            serde_json::to_string(&exposed).unwrap(),
+            r#"{"private_key":"0090802A50AA84EF","seed":"snYP7oArxKepd3GPDcrjMsJYiJeJB"}"#
+        );
+    }
+}
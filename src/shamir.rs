@@ -0,0 +1,670 @@
+//! Shamir's secret sharing of seeds and mnemonics, with SLIP-39 mnemonic shares.
+//!
+//! The entropy behind a family seed or a BIP39 mnemonic is split into `share_count` shares, any
+//! `threshold` of which recover it. Shares are SLIP-39 mnemonics of 20 words (33 for 32 byte
+//! secrets) in a single group, so they can be recovered by other SLIP-39 implementations.
+//! Shares made by others with several groups are recovered as well.
+//!
+//! A share only holds entropy: recovering a family seed needs its key algorithm, and the wallet
+//! of a mnemonic its derivation path.
+use crate::error::XpringError as Error;
+use crate::javascript::{JavaScript, JsCall};
+use crate::secret::{Mnemonic, Seed, Share};
+use crate::wallet::{self, KeyAlgorithm};
+use fehler::{throw, throws};
+use hmac::{Hmac, Mac, NewMac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
+
+/// Most shares in a group, and most groups.
+pub const MAX_SHARE_COUNT: u8 = 16;
+
+/// PBKDF2 iterations of the passphrase encryption are `10000 << exponent`.
+pub const ITERATION_EXPONENT: u8 = 1;
+
+const WORDLIST: &str = include_str!("shamir/wordlist.txt");
+const RADIX_BITS: usize = 10;
+const HEADER_WORDS: usize = 4;
+const CHECKSUM_WORDS: usize = 3;
+const MIN_SECRET_LENGTH: usize = 16;
+// Each half of the secret is encrypted with a single block of PBKDF2-HMAC-SHA256.
+const MAX_SECRET_LENGTH: usize = 64;
+const DIGEST_LENGTH: usize = 4;
+const DIGEST_INDEX: u8 = 254;
+const SECRET_INDEX: u8 = 255;
+const BASE_ITERATION_COUNT: u32 = 10000;
+const ROUND_COUNT: u8 = 4;
+const CUSTOMIZATION: &[u8] = b"shamir";
+const EXTENDABLE_CUSTOMIZATION: &[u8] = b"shamir_extendable";
+
+/// The fields of a decoded share.
+#[derive(Clone)]
+struct ShareData {
+    identifier: u16,
+    extendable: bool,
+    iteration_exponent: u8,
+    group_index: u8,
+    group_threshold: u8,
+    group_count: u8,
+    member_index: u8,
+    member_threshold: u8,
+    value: Zeroizing<Vec<u8>>,
+}
+
+impl ShareData {
+    fn same_set(&self, other: &ShareData) -> bool {
+        self.identifier == other.identifier
+            && self.extendable == other.extendable
+            && self.iteration_exponent == other.iteration_exponent
+            && self.group_threshold == other.group_threshold
+            && self.group_count == other.group_count
+    }
+}
+
+fn words() -> Vec<&'static str> {
+    WORDLIST.split_whitespace().collect()
+}
+
+fn rs1024_polymod(values: impl IntoIterator<Item = u32>) -> u32 {
+    const GENERATOR: [u32; 10] = [
+        0x00E0_E040,
+        0x01C1_C080,
+        0x0383_8100,
+        0x0707_0200,
+        0x0E0E_0009,
+        0x1C0C_2412,
+        0x3808_6C24,
+        0x3090_FC48,
+        0x21B1_F890,
+        0x03F3_F120,
+    ];
+    let mut checksum = 1;
+    for value in values {
+        let top = checksum >> 20;
+        checksum = (checksum & 0xF_FFFF) << 10 ^ value;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn customization(extendable: bool) -> &'static [u8] {
+    if extendable {
+        EXTENDABLE_CUSTOMIZATION
+    } else {
+        CUSTOMIZATION
+    }
+}
+
+/// Log and exp tables of GF(256) with the Rijndael polynomial.
+fn field_tables() -> ([u8; 256], [u8; 255]) {
+    let mut log = [0u8; 256];
+    let mut exp = [0u8; 255];
+    let mut poly: u16 = 1;
+    for (i, e) in exp.iter_mut().enumerate() {
+        *e = poly as u8;
+        log[poly as usize] = i as u8;
+        poly = (poly << 1) ^ poly;
+        if poly & 0x100 != 0 {
+            poly ^= 0x11B;
+        }
+    }
+    (log, exp)
+}
+
+/// Evaluates at `x` the polynomial going through `shares`, byte by byte.
+fn interpolate(shares: &[(u8, &[u8])], x: u8) -> Zeroizing<Vec<u8>> {
+    if let Some((_, value)) = shares.iter().find(|(index, _)| *index == x) {
+        return Zeroizing::new(value.to_vec());
+    }
+    let (log, exp) = field_tables();
+    let log_product: u32 = shares
+        .iter()
+        .map(|(index, _)| log[(index ^ x) as usize] as u32)
+        .sum();
+    let mut result = Zeroizing::new(vec![0u8; shares[0].1.len()]);
+    for (index, value) in shares {
+        let others: u32 = shares
+            .iter()
+            .map(|(other, _)| log[(index ^ other) as usize] as u32)
+            .sum();
+        let log_basis =
+            (log_product + 255 * 2 * 16 - log[(index ^ x) as usize] as u32 - others) % 255;
+        for (byte, share_byte) in result.iter_mut().zip(value.iter()) {
+            if *share_byte != 0 {
+                *byte ^= exp[((log[*share_byte as usize] as u32 + log_basis) % 255) as usize];
+            }
+        }
+    }
+    result
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_varkey(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+#[throws(_)]
+fn random_bytes(length: usize) -> Zeroizing<Vec<u8>> {
+    let mut bytes = Zeroizing::new(vec![0u8; length]);
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| Error::Validation(format!("Could not generate random bytes: {}", e)))?;
+    bytes
+}
+
+#[throws(_)]
+fn split(threshold: u8, share_count: u8, secret: &[u8]) -> Vec<(u8, Zeroizing<Vec<u8>>)> {
+    if threshold == 1 {
+        return (0..share_count)
+            .map(|i| (i, Zeroizing::new(secret.to_vec())))
+            .collect();
+    }
+    let random_share_count = threshold - 2;
+    let mut shares = Vec::with_capacity(share_count as usize);
+    for i in 0..random_share_count {
+        shares.push((i, random_bytes(secret.len())?));
+    }
+    let random_part = random_bytes(secret.len() - DIGEST_LENGTH)?;
+    let mut digest_share =
+        Zeroizing::new(hmac_sha256(&random_part, secret)[..DIGEST_LENGTH].to_vec());
+    digest_share.extend_from_slice(&random_part);
+    let mut base: Vec<(u8, &[u8])> = shares.iter().map(|(i, value)| (*i, &value[..])).collect();
+    base.push((DIGEST_INDEX, &digest_share));
+    base.push((SECRET_INDEX, secret));
+    let rest: Vec<_> = (random_share_count..share_count)
+        .map(|i| (i, interpolate(&base, i)))
+        .collect();
+    shares.extend(rest);
+    shares
+}
+
+#[throws(_)]
+fn recover(threshold: u8, shares: &[(u8, &[u8])]) -> Zeroizing<Vec<u8>> {
+    if threshold == 1 {
+        return Zeroizing::new(shares[0].1.to_vec());
+    }
+    let secret = interpolate(shares, SECRET_INDEX);
+    let digest_share = interpolate(shares, DIGEST_INDEX);
+    let (digest, random_part) = digest_share.split_at(DIGEST_LENGTH);
+    if digest != &hmac_sha256(random_part, &secret)[..DIGEST_LENGTH] {
+        throw!(Error::Validation("Invalid digest of the shares".to_owned()));
+    }
+    secret
+}
+
+/// One round of the Feistel network: PBKDF2-HMAC-SHA256 of a half of the secret.
+fn round_function(round: u8, passphrase: &[u8], exponent: u8, salt: &[u8], half: &[u8]) -> Vec<u8> {
+    let mut password = vec![round];
+    password.extend_from_slice(passphrase);
+    let mut block_salt = salt.to_vec();
+    block_salt.extend_from_slice(half);
+    block_salt.extend_from_slice(&1u32.to_be_bytes());
+    let mut block = hmac_sha256(&password, &block_salt);
+    let mut output = block;
+    for _ in 1..(BASE_ITERATION_COUNT << exponent) / ROUND_COUNT as u32 {
+        block = hmac_sha256(&password, &block);
+        output
+            .iter_mut()
+            .zip(block.iter())
+            .for_each(|(o, b)| *o ^= b);
+    }
+    output[..half.len()].to_vec()
+}
+
+fn salt(identifier: u16, extendable: bool) -> Vec<u8> {
+    if extendable {
+        Vec::new()
+    } else {
+        let mut salt = CUSTOMIZATION.to_vec();
+        salt.extend_from_slice(&identifier.to_be_bytes());
+        salt
+    }
+}
+
+fn feistel(
+    secret: &[u8],
+    passphrase: &[u8],
+    exponent: u8,
+    identifier: u16,
+    extendable: bool,
+    rounds: &mut dyn Iterator<Item = u8>,
+) -> Zeroizing<Vec<u8>> {
+    let salt = salt(identifier, extendable);
+    let (left, right) = secret.split_at(secret.len() / 2);
+    let (mut left, mut right) = (
+        Zeroizing::new(left.to_vec()),
+        Zeroizing::new(right.to_vec()),
+    );
+    for round in rounds {
+        let f = round_function(round, passphrase, exponent, &salt, &right);
+        let mixed = Zeroizing::new(left.iter().zip(f.iter()).map(|(l, f)| l ^ f).collect());
+        left = std::mem::replace(&mut right, mixed);
+    }
+    right.extend_from_slice(&left);
+    right
+}
+
+#[throws(_)]
+fn check_secret_length(length: usize) {
+    if !(MIN_SECRET_LENGTH..=MAX_SECRET_LENGTH).contains(&length) || !length.is_multiple_of(2) {
+        throw!(Error::Validation(format!(
+            "The secret must be an even number of bytes, from {} to {}",
+            MIN_SECRET_LENGTH, MAX_SECRET_LENGTH
+        )));
+    }
+}
+
+#[throws(_)]
+fn check_passphrase(passphrase: &str) {
+    if !passphrase.bytes().all(|b| (32..=126).contains(&b)) {
+        throw!(Error::Validation(
+            "The passphrase must only contain printable ASCII characters".to_owned()
+        ));
+    }
+}
+
+fn encode(share: &ShareData) -> Share {
+    let words = words();
+    let header = (share.identifier as u64) << 25
+        | (share.extendable as u64) << 24
+        | (share.iteration_exponent as u64) << 20
+        | (share.group_index as u64) << 16
+        | ((share.group_threshold - 1) as u64) << 12
+        | ((share.group_count - 1) as u64) << 8
+        | (share.member_index as u64) << 4
+        | (share.member_threshold - 1) as u64;
+    let mut values: Vec<u32> = (0..HEADER_WORDS)
+        .rev()
+        .map(|i| ((header >> (RADIX_BITS * i)) & 1023) as u32)
+        .collect();
+    // The value is left padded with zero bits to a whole number of words.
+    let value_words = (share.value.len() * 8).div_ceil(RADIX_BITS);
+    let mut accumulator: u32 = 0;
+    let mut bits = value_words * RADIX_BITS - share.value.len() * 8;
+    for byte in share.value.iter() {
+        accumulator = accumulator << 8 | *byte as u32;
+        bits += 8;
+        if bits >= RADIX_BITS {
+            bits -= RADIX_BITS;
+            values.push((accumulator >> bits) & 1023);
+            accumulator &= (1 << bits) - 1;
+        }
+    }
+    let custom = customization(share.extendable).iter().map(|b| *b as u32);
+    let polymod = rs1024_polymod(
+        custom
+            .chain(values.iter().copied())
+            .chain(vec![0; CHECKSUM_WORDS]),
+    ) ^ 1;
+    values.extend(
+        (0..CHECKSUM_WORDS)
+            .rev()
+            .map(|i| (polymod >> (RADIX_BITS * i)) & 1023),
+    );
+    let mnemonic: Vec<&str> = values.iter().map(|v| words[*v as usize]).collect();
+    Share::new(mnemonic.join(" "))
+}
+
+#[throws(_)]
+fn decode(share: &Share) -> ShareData {
+    let words = words();
+    let values = share
+        .expose_secret()
+        .split_whitespace()
+        .map(|word| {
+            words
+                .binary_search(&word.to_lowercase().as_str())
+                .map(|i| i as u32)
+                .map_err(|_| Error::Validation(format!("Invalid share word {}", word)))
+        })
+        .collect::<Result<Vec<u32>, Error>>()?;
+    let words_for =
+        |length: usize| HEADER_WORDS + CHECKSUM_WORDS + (length * 8).div_ceil(RADIX_BITS);
+    let (min_words, max_words) = (words_for(MIN_SECRET_LENGTH), words_for(MAX_SECRET_LENGTH));
+    if values.len() < min_words || values.len() > max_words {
+        throw!(Error::Validation(format!(
+            "A share has from {} to {} words, found {}",
+            min_words,
+            max_words,
+            values.len()
+        )));
+    }
+    let header = values[..HEADER_WORDS]
+        .iter()
+        .fold(0u64, |h, v| h << RADIX_BITS | *v as u64);
+    let extendable = (header >> 24) & 1 == 1;
+    let custom = customization(extendable).iter().map(|b| *b as u32);
+    if rs1024_polymod(custom.chain(values.iter().copied())) != 1 {
+        throw!(Error::Validation("Invalid share checksum".to_owned()));
+    }
+    let value_words = &values[HEADER_WORDS..values.len() - CHECKSUM_WORDS];
+    let padding = value_words.len() * RADIX_BITS % 16;
+    if padding > 8 {
+        throw!(Error::Validation("Invalid share length".to_owned()));
+    }
+    let mut value = Zeroizing::new(Vec::with_capacity(value_words.len() * RADIX_BITS / 8));
+    let mut accumulator: u32 = 0;
+    let mut bits = 0;
+    for (i, word) in value_words.iter().enumerate() {
+        accumulator = accumulator << RADIX_BITS | word;
+        bits += RADIX_BITS;
+        if i == 0 {
+            if accumulator >> (RADIX_BITS - padding) != 0 {
+                throw!(Error::Validation("Invalid share padding".to_owned()));
+            }
+            bits -= padding;
+        }
+        while bits >= 8 {
+            bits -= 8;
+            value.push((accumulator >> bits) as u8);
+            accumulator &= (1 << bits) - 1;
+        }
+    }
+    check_secret_length(value.len())?;
+    let group_threshold = ((header >> 12) & 15) as u8 + 1;
+    let group_count = ((header >> 8) & 15) as u8 + 1;
+    if group_threshold > group_count {
+        throw!(Error::Validation(
+            "The group threshold of a share exceeds its group count".to_owned()
+        ));
+    }
+    ShareData {
+        identifier: (header >> 25) as u16,
+        extendable,
+        iteration_exponent: ((header >> 20) & 15) as u8,
+        group_index: ((header >> 16) & 15) as u8,
+        group_threshold,
+        group_count,
+        member_index: ((header >> 4) & 15) as u8,
+        member_threshold: (header & 15) as u8 + 1,
+        value,
+    }
+}
+
+/// Splits `secret` into `share_count` SLIP-39 shares, `threshold` of which recover it.
+///
+/// The secret is encrypted with `passphrase` first; recovering with another passphrase gives a
+/// different secret rather than an error. The secret is an even number of bytes, from 16 to 64,
+/// and a 1-of-1 split is the only one with a threshold of 1.
+#[throws(_)]
+pub fn split_secret(secret: &[u8], threshold: u8, share_count: u8, passphrase: &str) -> Vec<Share> {
+    check_secret_length(secret.len())?;
+    if threshold == 0 || threshold > share_count || share_count > MAX_SHARE_COUNT {
+        throw!(Error::Validation(format!(
+            "Invalid {}-of-{} split, there are at most {} shares",
+            threshold, share_count, MAX_SHARE_COUNT
+        )));
+    }
+    if threshold == 1 && share_count > 1 {
+        throw!(Error::Validation(
+            "A threshold of 1 only splits into a single share".to_owned()
+        ));
+    }
+    check_passphrase(passphrase)?;
+    let mut identifier = [0u8; 2];
+    getrandom::getrandom(&mut identifier)
+        .map_err(|e| Error::Validation(format!("Could not generate random bytes: {}", e)))?;
+    let identifier = u16::from_be_bytes(identifier) & 0x7FFF;
+    let encrypted = feistel(
+        secret,
+        passphrase.as_bytes(),
+        ITERATION_EXPONENT,
+        identifier,
+        false,
+        &mut (0..ROUND_COUNT),
+    );
+    split(threshold, share_count, &encrypted)?
+        .into_iter()
+        .map(|(member_index, value)| {
+            encode(&ShareData {
+                identifier,
+                extendable: false,
+                iteration_exponent: ITERATION_EXPONENT,
+                group_index: 0,
+                group_threshold: 1,
+                group_count: 1,
+                member_index,
+                member_threshold: threshold,
+                value,
+            })
+        })
+        .collect()
+}
+
+/// Recovers the secret of SLIP-39 shares, from enough shares of enough of their groups.
+#[throws(_)]
+pub fn combine_shares(shares: &[Share], passphrase: &str) -> Zeroizing<Vec<u8>> {
+    check_passphrase(passphrase)?;
+    let shares = shares
+        .iter()
+        .map(decode)
+        .collect::<Result<Vec<_>, Error>>()?;
+    let first = match shares.first() {
+        Some(first) => first.clone(),
+        None => throw!(Error::Validation("No shares to combine".to_owned())),
+    };
+    let mut groups: BTreeMap<u8, Vec<&ShareData>> = BTreeMap::new();
+    for share in &shares {
+        if !share.same_set(&first) || share.value.len() != first.value.len() {
+            throw!(Error::Validation(
+                "The shares do not belong to the same secret".to_owned()
+            ));
+        }
+        let group = groups.entry(share.group_index).or_default();
+        if let Some(other) = group
+            .iter()
+            .find(|other| other.member_index == share.member_index)
+        {
+            if other.value != share.value || other.member_threshold != share.member_threshold {
+                throw!(Error::Validation(
+                    "Two different shares have the same index".to_owned()
+                ));
+            }
+            continue;
+        }
+        group.push(share);
+    }
+    let mut group_secrets = Vec::new();
+    for (group_index, members) in &groups {
+        let threshold = members[0].member_threshold;
+        if members
+            .iter()
+            .any(|member| member.member_threshold != threshold)
+        {
+            throw!(Error::Validation(
+                "The shares of a group have different thresholds".to_owned()
+            ));
+        }
+        if members.len() >= threshold as usize {
+            let points: Vec<(u8, &[u8])> = members
+                .iter()
+                .map(|m| (m.member_index, &m.value[..]))
+                .collect();
+            group_secrets.push((*group_index, recover(threshold, &points)?));
+        }
+    }
+    if group_secrets.len() < first.group_threshold as usize {
+        throw!(Error::Validation(format!(
+            "Not enough shares: {} of the {} groups needed are complete",
+            group_secrets.len(),
+            first.group_threshold
+        )));
+    }
+    let points: Vec<(u8, &[u8])> = group_secrets.iter().map(|(i, v)| (*i, &v[..])).collect();
+    let encrypted = recover(first.group_threshold, &points)?;
+    feistel(
+        &encrypted,
+        passphrase.as_bytes(),
+        first.iteration_exponent,
+        first.identifier,
+        first.extendable,
+        &mut (0..ROUND_COUNT).rev(),
+    )
+}
+
+type HexEntropy = Zeroizing<String>;
+
+#[derive(Serialize)]
+struct XMnemonicOptions<'a> {
+    #[serde(serialize_with = "crate::secret::expose")]
+    mnemonic: &'a Mnemonic,
+}
+
+#[derive(Serialize)]
+struct XEntropyOptions {
+    entropy: Zeroizing<String>,
+}
+
+#[throws(_)]
+pub(crate) fn split_seed(
+    jscontext: &JavaScript,
+    seed: &Seed,
+    threshold: u8,
+    share_count: u8,
+    passphrase: &str,
+) -> Vec<Share> {
+    let decoded = wallet::entropy_from_seed(jscontext, seed)?;
+    split_secret(&*decoded.entropy, threshold, share_count, passphrase)?
+}
+
+#[throws(_)]
+pub(crate) fn recover_seed(
+    jscontext: &JavaScript,
+    shares: &[Share],
+    passphrase: &str,
+    algorithm: KeyAlgorithm,
+) -> Seed {
+    let secret = combine_shares(shares, passphrase)?;
+    let mut entropy = Zeroizing::new([0u8; 16]);
+    if secret.len() != entropy.len() {
+        throw!(Error::Validation(format!(
+            "The shares hold {} bytes, a seed has 16",
+            secret.len()
+        )));
+    }
+    entropy.copy_from_slice(&secret);
+    wallet::seed_from_entropy(jscontext, Some(&entropy), algorithm)?
+}
+
+#[throws(_)]
+pub(crate) fn split_mnemonic(
+    jscontext: &JavaScript,
+    mnemonic: &Mnemonic,
+    threshold: u8,
+    share_count: u8,
+    passphrase: &str,
+) -> Vec<Share> {
+    let options = XMnemonicOptions { mnemonic };
+    let entropy = js!(jscontext.wallet.entropyFromMnemonic::<HexEntropy>(options))?;
+    let entropy = Zeroizing::new(
+        hex::decode(&*entropy)
+            .map_err(|e| Error::Decode(format!("Invalid mnemonic entropy: {}", e)))?,
+    );
+    split_secret(&entropy, threshold, share_count, passphrase)?
+}
+
+#[throws(_)]
+pub(crate) fn recover_mnemonic(
+    jscontext: &JavaScript,
+    shares: &[Share],
+    passphrase: &str,
+) -> Mnemonic {
+    let secret = combine_shares(shares, passphrase)?;
+    let options = XEntropyOptions {
+        entropy: Zeroizing::new(hex::encode_upper(&*secret)),
+    };
+    let mnemonic = js!(jscontext.wallet.mnemonicFromEntropy::<Mnemonic>(options))?;
+    mnemonic
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::javascript::JavaScript;
+
+    const PASSPHRASE: &str = "TREZOR";
+
+    fn shares(mnemonics: &[&str]) -> Vec<Share> {
+        mnemonics.iter().map(|m| Share::from(*m)).collect()
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_slip39_vectors() {
+        let single = shares(&["duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard"]);
+        assert_eq!(
+            hex::encode(&*combine_shares(&single, PASSPHRASE)?),
+            "bb54aac4b89dc868ba37d9cc21b2cece"
+        );
+        let two_of_three = shares(&[
+            "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+            "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+        ]);
+        assert_eq!(
+            hex::encode(&*combine_shares(&two_of_three, PASSPHRASE)?),
+            "b43ceb7e57a0ea8766221624d01b0864"
+        );
+        let bad_checksum = shares(&["duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney"]);
+        assert!(combine_shares(&bad_checksum, PASSPHRASE).is_err());
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_split_and_combine() {
+        let secret: Vec<u8> = (0..32).collect();
+        let split = split_secret(&secret, 3, 5, "")?;
+        assert_eq!(split.len(), 5);
+        assert_eq!(split[0].expose_secret().split(' ').count(), 33);
+        assert_eq!(&*combine_shares(&split[2..], "")?, &secret[..]);
+        assert_eq!(
+            &*combine_shares(&[split[4].clone(), split[0].clone(), split[3].clone()], "")?,
+            &secret[..]
+        );
+        assert_ne!(&*combine_shares(&split[..3], "other")?, &secret[..]);
+        match combine_shares(&split[..2], "") {
+            Err(Error::Validation(_)) => (),
+            other => panic!("Unexpected result {:?}", other),
+        }
+        assert!(split_secret(&secret, 3, 2, "").is_err());
+        assert!(split_secret(&secret, 1, 2, "").is_err());
+        assert!(split_secret(&secret[..15], 1, 1, "").is_err());
+
+        // Secrets and shares longer than the Feistel cipher handles are errors.
+        let long: Vec<u8> = (0..66).collect();
+        assert!(matches!(
+            split_secret(&long, 2, 3, ""),
+            Err(Error::Validation(_))
+        ));
+        let longest = split_secret(&long[..64], 2, 3, "")?;
+        assert_eq!(&*combine_shares(&longest[1..], "")?, &long[..64]);
+        let crafted = Share::from(format!("{} academic academic", longest[0].expose_secret()));
+        assert!(matches!(
+            combine_shares(&[crafted, longest[1].clone()], ""),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_split_seed_and_mnemonic() {
+        let jscontext =
+            JavaScript::new(format!("{}/xpring.js", std::env::var("OUT_DIR").unwrap()))?;
+        let seed = Seed::from("sn3UJSLzAEeAGcrK3nsQTDZW6KT92");
+        let split = split_seed(&jscontext, &seed, 2, 3, "")?;
+        assert_eq!(split[0].expose_secret().split(' ').count(), 20);
+        let recovered = recover_seed(&jscontext, &split[1..], "", KeyAlgorithm::Secp256k1)?;
+        assert_eq!(recovered, seed);
+        let mnemonic = Mnemonic::from(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        );
+        let split = split_mnemonic(&jscontext, &mnemonic, 2, 2, "secret")?;
+        assert_eq!(recover_mnemonic(&jscontext, &split, "secret")?, mnemonic);
+    }
+}
@@ -0,0 +1,809 @@
+//! In-process stand-ins for the services xpring-rs talks to, so applications can be tested
+//! offline. Enabled with the `testing` feature.
+use crate::error::XpringError as Error;
+use crate::i::account_service_server::{AccountService, AccountServiceServer};
+use crate::i::balance_service_server::{BalanceService, BalanceServiceServer};
+use crate::i::ilp_over_http_service_server::{IlpOverHttpService, IlpOverHttpServiceServer};
+use crate::i::{
+    CreateAccountRequest, CreateAccountResponse, GetAccountRequest, GetAccountResponse,
+    GetBalanceRequest, GetBalanceResponse, SendPaymentRequest, SendPaymentResponse,
+};
+use crate::transaction::XCurrencyAmount;
+use crate::x::prelude::*;
+use crate::x::xrp_ledger_api_service_server::{XrpLedgerApiService, XrpLedgerApiServiceServer};
+use crate::x::{
+    Balance, DeliveredAmount, Fee, GetAccountInfoResponse, GetAccountTransactionHistoryResponse,
+    Meta, RegularKey, SubmitTransactionResponse,
+};
+use fehler::throws;
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{Ipv4Addr, SocketAddrV4, TcpListener};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use tokio::runtime::Builder;
+use tokio::sync::oneshot;
+use tonic::{transport::Server, Request, Response, Status};
+
+mod simulator;
+pub use simulator::SimulatedLedger;
+
+/// A server running on a background thread. It is shut down when dropped.
+pub struct MockServer {
+    url: String,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Url to pass to `Xrpl::new` or `Ilp::new`.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Runs the server future returned by `serve` on a free localhost port.
+#[throws(_)]
+pub(crate) fn spawn<F, Fut>(serve: F) -> MockServer
+where
+    F: FnOnce(tokio::net::TcpListener, oneshot::Receiver<()>) -> Fut + Send + 'static,
+    Fut: Future<Output = ()>,
+{
+    let socket = SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 0);
+    let listener = TcpListener::bind(socket).map_err(|e| Error::Transport(e.to_string()))?;
+    let url = format!(
+        "http://{}",
+        listener
+            .local_addr()
+            .map_err(|e| Error::Transport(e.to_string()))?
+    );
+    let (shutdown, signal) = oneshot::channel();
+    let thread = thread::spawn(move || {
+        let mut rt = Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .expect("Unable to start the mock server runtime");
+        rt.block_on(async move {
+            let listener = tokio::net::TcpListener::from_std(listener)
+                .expect("Unable to register the mock server listener");
+            serve(listener, signal).await
+        });
+    });
+    MockServer {
+        url,
+        shutdown: Some(shutdown),
+        thread: Some(thread),
+    }
+}
+
+/// A transaction known to a `MockLedger`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct MockTransaction {
+    pub result: String,
+    pub validated: bool,
+    pub ledger_index: u32,
+    pub last_ledger_sequence: u32,
+    pub delivered_amount: Option<XCurrencyAmount>,
+}
+
+impl MockTransaction {
+    /// A transaction validated with the engine result `result`.
+    pub fn validated(result: &str) -> MockTransaction {
+        MockTransaction {
+            result: result.to_owned(),
+            validated: true,
+            ledger_index: 0,
+            last_ledger_sequence: 0,
+            delivered_amount: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockSubmission {
+    result: String,
+    message: String,
+    hash: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct LedgerState {
+    accounts: HashMap<String, (u64, u32)>,
+    regular_keys: HashMap<String, String>,
+    account_flags: HashMap<String, u32>,
+    base_fee: u64,
+    open_ledger_fee: u64,
+    ledger_index: u32,
+    transactions: HashMap<Vec<u8>, MockTransaction>,
+    unknown_lookups: HashMap<Vec<u8>, u32>,
+    submission: MockSubmission,
+    submitted: Vec<Vec<u8>>,
+}
+
+impl Default for LedgerState {
+    fn default() -> Self {
+        LedgerState {
+            accounts: HashMap::new(),
+            regular_keys: HashMap::new(),
+            account_flags: HashMap::new(),
+            base_fee: 10,
+            open_ledger_fee: 10,
+            ledger_index: 1000,
+            transactions: HashMap::new(),
+            unknown_lookups: HashMap::new(),
+            submission: MockSubmission {
+                result: "tesSUCCESS".to_owned(),
+                message: "The transaction was applied. Only final in a validated ledger."
+                    .to_owned(),
+                hash: vec![0; 32],
+            },
+            submitted: vec![],
+        }
+    }
+}
+
+/// Scriptable in-memory XRP Ledger answering the gRPC API.
+///
+/// Accounts are keyed by classic address. Clones share the same ledger, so a test can keep
+/// scripting the ledger after the server was started.
+///
+/// # Example
+///
+/// ```
+/// # use xpring::testing::MockLedger;
+/// # use xpring::Xrpl;
+/// # fn main() -> Result<(), xpring::error::XpringError> {
+/// let ledger = MockLedger::new();
+/// ledger.account("rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7", 1_000_000_000, 1);
+/// let server = ledger.serve()?;
+/// let mut xrpl = Xrpl::new(server.url(), true)?;
+/// let balance = xrpl.get_balance("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct MockLedger {
+    state: Arc<Mutex<LedgerState>>,
+}
+
+impl MockLedger {
+    /// Creates an empty ledger with a base fee of 10 drops at ledger index 1000.
+    pub fn new() -> MockLedger {
+        MockLedger::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, LedgerState> {
+        self.state.lock().expect("Mock ledger state poisoned")
+    }
+
+    /// Creates or replaces an account.
+    pub fn account(&self, address: &str, balance_drops: u64, sequence: u32) -> &MockLedger {
+        self.state()
+            .accounts
+            .insert(address.to_owned(), (balance_drops, sequence));
+        self
+    }
+
+    /// Sets the regular key of an account, as a classic address.
+    pub fn regular_key(&self, address: &str, regular_key: &str) -> &MockLedger {
+        self.state()
+            .regular_keys
+            .insert(address.to_owned(), regular_key.to_owned());
+        self
+    }
+
+    /// Sets the flags of an account, such as `lsfDisableMaster`.
+    pub fn account_flags(&self, address: &str, flags: u32) -> &MockLedger {
+        self.state().account_flags.insert(address.to_owned(), flags);
+        self
+    }
+
+    /// Sets the base and open ledger fees in drops.
+    pub fn fees(&self, base_fee: u64, open_ledger_fee: u64) -> &MockLedger {
+        let mut state = self.state();
+        state.base_fee = base_fee;
+        state.open_ledger_fee = open_ledger_fee;
+        self
+    }
+
+    /// Sets the current ledger index.
+    pub fn ledger_index(&self, ledger_index: u32) -> &MockLedger {
+        self.state().ledger_index = ledger_index;
+        self
+    }
+
+    /// Makes a transaction available to transaction lookups. `hash` is hex encoded.
+    pub fn transaction(&self, hash: &str, transaction: MockTransaction) -> &MockLedger {
+        let hash = hex::decode(hash).expect("Transaction hash must be hex encoded");
+        self.state().transactions.insert(hash, transaction);
+        self
+    }
+
+    /// Answers the next `lookups` lookups of a transaction with `NotFound`, like a node that has
+    /// not applied a queued transaction yet. `hash` is hex encoded.
+    pub fn unknown_for(&self, hash: &str, lookups: u32) -> &MockLedger {
+        let hash = hex::decode(hash).expect("Transaction hash must be hex encoded");
+        self.state().unknown_lookups.insert(hash, lookups);
+        self
+    }
+
+    /// Sets the answer to submitted transactions: the engine result, its message and the hex
+    /// encoded transaction hash.
+    pub fn submit_result(&self, result: &str, message: &str, hash: &str) -> &MockLedger {
+        self.state().submission = MockSubmission {
+            result: result.to_owned(),
+            message: message.to_owned(),
+            hash: hex::decode(hash).expect("Transaction hash must be hex encoded"),
+        };
+        self
+    }
+
+    /// Balance in drops of an account.
+    pub fn balance(&self, address: &str) -> Option<u64> {
+        self.state().accounts.get(address).map(|a| a.0)
+    }
+
+    /// Signed transaction blobs submitted so far.
+    pub fn submitted(&self) -> Vec<Vec<u8>> {
+        self.state().submitted.clone()
+    }
+
+    /// Starts answering the gRPC API on a free localhost port.
+    #[throws(_)]
+    pub fn serve(&self) -> MockServer {
+        let ledger = self.clone();
+        spawn(move |mut listener, signal| async move {
+            let _ = Server::builder()
+                .add_service(XrpLedgerApiServiceServer::new(ledger))
+                .serve_with_incoming_shutdown(listener.incoming(), async {
+                    let _ = signal.await;
+                })
+                .await;
+        })?
+    }
+}
+
+fn xrp(drops: u64) -> CurrencyAmount {
+    CurrencyAmount {
+        amount: Some(currency_amount::Amount::XrpAmount(XrpDropsAmount { drops })),
+    }
+}
+
+#[tonic::async_trait]
+impl XrpLedgerApiService for MockLedger {
+    async fn get_account_info(
+        &self,
+        request: Request<GetAccountInfoRequest>,
+    ) -> Result<Response<GetAccountInfoResponse>, Status> {
+        let address = request
+            .into_inner()
+            .account
+            .map(|a| a.address)
+            .unwrap_or_default();
+        let state = self.state();
+        let (balance, sequence) = state
+            .accounts
+            .get(&address)
+            .ok_or_else(|| Status::not_found("account not found"))?;
+        Ok(Response::new(GetAccountInfoResponse {
+            account_data: Some(AccountRoot {
+                account: Some(Account {
+                    value: Some(AccountAddress {
+                        address: address.clone(),
+                    }),
+                }),
+                balance: Some(Balance {
+                    value: Some(xrp(*balance)),
+                }),
+                sequence: Some(Sequence { value: *sequence }),
+                flags: state
+                    .account_flags
+                    .get(&address)
+                    .map(|value| Flags { value: *value }),
+                regular_key: state.regular_keys.get(&address).map(|key| RegularKey {
+                    value: Some(AccountAddress {
+                        address: key.clone(),
+                    }),
+                }),
+                ..Default::default()
+            }),
+            ledger_index: state.ledger_index,
+            validated: true,
+            ..Default::default()
+        }))
+    }
+
+    async fn get_fee(
+        &self,
+        _request: Request<GetFeeRequest>,
+    ) -> Result<Response<GetFeeResponse>, Status> {
+        let state = self.state();
+        let drops = |drops| Some(XrpDropsAmount { drops });
+        Ok(Response::new(GetFeeResponse {
+            fee: Some(Fee {
+                base_fee: drops(state.base_fee),
+                median_fee: drops(state.base_fee),
+                minimum_fee: drops(state.base_fee),
+                open_ledger_fee: drops(state.open_ledger_fee),
+            }),
+            ledger_current_index: state.ledger_index,
+            ..Default::default()
+        }))
+    }
+
+    async fn submit_transaction(
+        &self,
+        request: Request<SubmitTransactionRequest>,
+    ) -> Result<Response<SubmitTransactionResponse>, Status> {
+        let mut state = self.state();
+        state
+            .submitted
+            .push(request.into_inner().signed_transaction);
+        let submission = state.submission.clone();
+        Ok(Response::new(SubmitTransactionResponse {
+            engine_result: Some(TransactionResult {
+                result_type: 0,
+                result: submission.result,
+            }),
+            engine_result_message: submission.message,
+            hash: submission.hash,
+            ..Default::default()
+        }))
+    }
+
+    async fn get_transaction(
+        &self,
+        request: Request<GetTransactionRequest>,
+    ) -> Result<Response<GetTransactionResponse>, Status> {
+        let hash = request.into_inner().hash;
+        let mut state = self.state();
+        if let Some(lookups) = state.unknown_lookups.get_mut(&hash).filter(|l| **l > 0) {
+            *lookups -= 1;
+            return Err(Status::not_found("txn not found"));
+        }
+        let transaction = state
+            .transactions
+            .get(&hash)
+            .cloned()
+            .ok_or_else(|| Status::not_found("txn not found"))?;
+        Ok(Response::new(GetTransactionResponse {
+            serialized_transaction: Some(
+                get_transaction_response::SerializedTransaction::Transaction(Transaction {
+                    last_ledger_sequence: Some(LastLedgerSequence {
+                        value: transaction.last_ledger_sequence,
+                    }),
+                    ..Default::default()
+                }),
+            ),
+            ledger_index: transaction.ledger_index,
+            hash,
+            validated: transaction.validated,
+            serialized_meta: Some(get_transaction_response::SerializedMeta::Meta(Meta {
+                transaction_result: Some(TransactionResult {
+                    result_type: 0,
+                    result: transaction.result,
+                }),
+                delivered_amount: transaction.delivered_amount.map(|amount| DeliveredAmount {
+                    value: Some(amount.to_proto()),
+                }),
+                ..Default::default()
+            })),
+            date: None,
+        }))
+    }
+
+    async fn get_account_transaction_history(
+        &self,
+        request: Request<GetAccountTransactionHistoryRequest>,
+    ) -> Result<Response<GetAccountTransactionHistoryResponse>, Status> {
+        Ok(Response::new(GetAccountTransactionHistoryResponse {
+            account: request.into_inner().account,
+            validated: true,
+            ..Default::default()
+        }))
+    }
+}
+
+/// An account of a `MockConnector`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct MockIlpAccount {
+    pub token: String,
+    pub asset_code: String,
+    pub asset_scale: i32,
+    pub net_balance: i64,
+    pub prepaid_amount: i64,
+    pub clearing_balance: i64,
+}
+
+impl MockIlpAccount {
+    /// An account with an empty balance that accepts the bearer token `token`.
+    pub fn new(token: &str, asset_code: &str, asset_scale: i32) -> MockIlpAccount {
+        MockIlpAccount {
+            token: token.to_owned(),
+            asset_code: asset_code.to_owned(),
+            asset_scale,
+            net_balance: 0,
+            prepaid_amount: 0,
+            clearing_balance: 0,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ConnectorState {
+    accounts: HashMap<String, MockIlpAccount>,
+    rates: HashMap<(String, String), f64>,
+    admin_token: Option<String>,
+    reject_payments: bool,
+    failure: Option<tonic::Code>,
+}
+
+/// In-memory ILP connector answering the `BalanceService`, `IlpOverHttpService` and
+/// `AccountService` gRPC APIs.
+///
+/// Every request must carry the bearer token of the account it is made for. Payment pointers
+/// are resolved by their last path segment, so `$connector.example/alice` pays the account
+/// `alice`. Clones share the same connector.
+///
+/// # Example
+///
+/// ```
+/// # use xpring::testing::{MockConnector, MockIlpAccount};
+/// # use xpring::Ilp;
+/// # fn main() -> Result<(), xpring::error::XpringError> {
+/// let connector = MockConnector::new();
+/// connector
+///     .account("alice", MockIlpAccount::new("alice_token", "XRP", 9))
+///     .account("bob", MockIlpAccount::new("bob_token", "XRP", 9));
+/// let server = connector.serve()?;
+/// let mut ilp = Ilp::new(server.url(), "alice", "alice_token")?;
+/// ilp.send_to("$connector.example/bob", 10, 10)?;
+/// assert_eq!(connector.balance("bob"), Some(10));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct MockConnector {
+    state: Arc<Mutex<ConnectorState>>,
+}
+
+impl MockConnector {
+    /// Creates a connector without accounts.
+    pub fn new() -> MockConnector {
+        MockConnector::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ConnectorState> {
+        self.state.lock().expect("Mock connector state poisoned")
+    }
+
+    /// Creates or replaces an account.
+    pub fn account(&self, account_id: &str, account: MockIlpAccount) -> &MockConnector {
+        self.state().accounts.insert(account_id.to_owned(), account);
+        self
+    }
+
+    /// Sets the rate used when paying from `from_asset_code` to `to_asset_code`. Payments
+    /// between assets without a rate fail.
+    pub fn rate(&self, from_asset_code: &str, to_asset_code: &str, rate: f64) -> &MockConnector {
+        self.state()
+            .rates
+            .insert((from_asset_code.to_owned(), to_asset_code.to_owned()), rate);
+        self
+    }
+
+    /// Sets the token allowed to create accounts. Created accounts accept the same token.
+    pub fn admin_token(&self, token: &str) -> &MockConnector {
+        self.state().admin_token = Some(token.to_owned());
+        self
+    }
+
+    /// When set, payments are answered as unsuccessful without moving any funds.
+    pub fn reject_payments(&self, reject: bool) -> &MockConnector {
+        self.state().reject_payments = reject;
+        self
+    }
+
+    /// When set, every request fails with the given gRPC status code.
+    pub fn fail_with(&self, code: Option<tonic::Code>) -> &MockConnector {
+        self.state().failure = code;
+        self
+    }
+
+    /// Net balance of an account.
+    pub fn balance(&self, account_id: &str) -> Option<i64> {
+        self.state().accounts.get(account_id).map(|a| a.net_balance)
+    }
+
+    /// Starts answering the gRPC APIs on a free localhost port.
+    #[throws(_)]
+    pub fn serve(&self) -> MockServer {
+        let connector = self.clone();
+        spawn(move |mut listener, signal| async move {
+            let _ = Server::builder()
+                .add_service(BalanceServiceServer::new(connector.clone()))
+                .add_service(IlpOverHttpServiceServer::new(connector.clone()))
+                .add_service(AccountServiceServer::new(connector))
+                .serve_with_incoming_shutdown(listener.incoming(), async {
+                    let _ = signal.await;
+                })
+                .await;
+        })?
+    }
+
+    /// Fails with the scripted failure, if any.
+    fn check_failure(&self) -> Result<(), Status> {
+        match self.state().failure {
+            Some(code) => Err(Status::new(code, "scripted failure")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks that `request` carries `token` as its bearer token.
+fn authorize<T>(request: &Request<T>, token: &str) -> Result<(), Status> {
+    let header = request
+        .metadata()
+        .get("authorization")
+        .ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+    if header.to_str().ok() != Some(&format!("Bearer {}", token)) {
+        return Err(Status::unauthenticated("invalid bearer token"));
+    }
+    Ok(())
+}
+
+fn account_response(account_id: &str, account: &MockIlpAccount) -> GetAccountResponse {
+    GetAccountResponse {
+        account_id: account_id.to_owned(),
+        account_relationship: "CHILD".to_owned(),
+        asset_code: account.asset_code.clone(),
+        asset_scale: account.asset_scale,
+        link_type: "ILP_OVER_HTTP".to_owned(),
+        is_child_account: true,
+        ..Default::default()
+    }
+}
+
+#[tonic::async_trait]
+impl BalanceService for MockConnector {
+    async fn get_balance(
+        &self,
+        request: Request<GetBalanceRequest>,
+    ) -> Result<Response<GetBalanceResponse>, Status> {
+        self.check_failure()?;
+        let state = self.state();
+        let account_id = &request.get_ref().account_id;
+        let account = state
+            .accounts
+            .get(account_id)
+            .ok_or_else(|| Status::not_found("account not found"))?;
+        authorize(&request, &account.token)?;
+        Ok(Response::new(GetBalanceResponse {
+            account_id: account_id.to_owned(),
+            asset_code: account.asset_code.clone(),
+            asset_scale: account.asset_scale,
+            net_balance: account.net_balance,
+            prepaid_amount: account.prepaid_amount,
+            clearing_balance: account.clearing_balance,
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl IlpOverHttpService for MockConnector {
+    async fn send_money(
+        &self,
+        request: Request<SendPaymentRequest>,
+    ) -> Result<Response<SendPaymentResponse>, Status> {
+        self.check_failure()?;
+        let mut state = self.state();
+        let payment = request.get_ref();
+        let source = state
+            .accounts
+            .get(&payment.account_id)
+            .ok_or_else(|| Status::not_found("account not found"))?;
+        authorize(&request, &source.token)?;
+        let destination_id = payment
+            .destination_payment_pointer
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_owned();
+        let destination = state
+            .accounts
+            .get(&destination_id)
+            .ok_or_else(|| Status::not_found("payment pointer not found"))?;
+        let rate = if source.asset_code == destination.asset_code {
+            Some(1.0)
+        } else {
+            state
+                .rates
+                .get(&(source.asset_code.clone(), destination.asset_code.clone()))
+                .cloned()
+        };
+        let mut response = SendPaymentResponse {
+            original_amount: payment.amount,
+            ..Default::default()
+        };
+        if let (Some(rate), false) = (rate, state.reject_payments) {
+            let delivered = (payment.amount as f64 * rate).floor() as u64;
+            let source_id = payment.account_id.clone();
+            if let Some(source) = state.accounts.get_mut(&source_id) {
+                source.net_balance -= payment.amount as i64;
+            }
+            if let Some(destination) = state.accounts.get_mut(&destination_id) {
+                destination.net_balance += delivered as i64;
+            }
+            response.amount_sent = payment.amount;
+            response.amount_delivered = delivered;
+            response.successful_payment = true;
+        }
+        Ok(Response::new(response))
+    }
+}
+
+#[tonic::async_trait]
+impl AccountService for MockConnector {
+    async fn get_account(
+        &self,
+        request: Request<GetAccountRequest>,
+    ) -> Result<Response<GetAccountResponse>, Status> {
+        self.check_failure()?;
+        let state = self.state();
+        let account_id = &request.get_ref().account_id;
+        let account = state
+            .accounts
+            .get(account_id)
+            .ok_or_else(|| Status::not_found("account not found"))?;
+        authorize(&request, &account.token)?;
+        Ok(Response::new(account_response(account_id, account)))
+    }
+
+    async fn create_account(
+        &self,
+        request: Request<CreateAccountRequest>,
+    ) -> Result<Response<CreateAccountResponse>, Status> {
+        self.check_failure()?;
+        let mut state = self.state();
+        let admin_token = state
+            .admin_token
+            .clone()
+            .ok_or_else(|| Status::permission_denied("account creation is disabled"))?;
+        authorize(&request, &admin_token)?;
+        let create = request.into_inner();
+        if state.accounts.contains_key(&create.account_id) {
+            return Err(Status::already_exists("account already exists"));
+        }
+        let account = MockIlpAccount::new(&admin_token, &create.asset_code, create.asset_scale);
+        state
+            .accounts
+            .insert(create.account_id.clone(), account.clone());
+        Ok(Response::new(CreateAccountResponse {
+            account_id: create.account_id,
+            account_relationship: "CHILD".to_owned(),
+            asset_code: account.asset_code,
+            asset_scale: account.asset_scale,
+            description: create.description,
+            link_type: "ILP_OVER_HTTP".to_owned(),
+            is_child_account: true,
+            ..Default::default()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::xrplclient::XrplClient;
+
+    #[throws(_)]
+    #[test]
+    fn test_mock_ledger_unknown_account() {
+        let ledger = MockLedger::new();
+        let server = ledger.serve()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = crate::javascript::JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        match client.get_balance(
+            &jscontext,
+            "TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ",
+        ) {
+            Err(Error::Rpc { code, .. }) => assert_eq!(code, tonic::Code::NotFound),
+            result => panic!("expected a not found error, got {:?}", result),
+        }
+        ledger.account("rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7", 2_500_000, 1);
+        assert_eq!(
+            client.get_balance(
+                &jscontext,
+                "TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ"
+            )?,
+            2.5
+        );
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_mock_ledger_record_with_config() {
+        let path = std::env::temp_dir().join(format!("xpring-config-{}", std::process::id()));
+        let mut config = crate::XrplConfig::new();
+        config.test(true).js_bundle(crate::JsBundle::TempDir);
+        let ledger = MockLedger::new();
+        ledger.account("rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7", 2_500_000, 1);
+        let server = ledger.serve()?;
+        let mut xrpl = crate::Xrpl::record_with_config(server.url(), &config, &path)?;
+        let balance = xrpl.get_balance("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
+        drop(xrpl);
+        drop(server);
+
+        let mut xrpl = crate::Xrpl::replay_with_config(&path, &config)?;
+        assert_eq!(
+            xrpl.get_balance("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?,
+            balance
+        );
+        // The wallets follow the network of the config.
+        assert!(xrpl.pool().generate_random_wallet(None)?.wallet.test);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_mock_connector_accounts() {
+        use crate::i::account_service_client::AccountServiceClient;
+        use tonic::metadata::MetadataValue;
+
+        let connector = MockConnector::new();
+        connector.admin_token("admin");
+        let server = connector.serve()?;
+        let mut rt = Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .unwrap();
+        let mut client = rt.block_on(AccountServiceClient::connect(server.url().to_owned()))?;
+        let request = |token: &str, account_id: &str| {
+            let mut request = Request::new(CreateAccountRequest {
+                account_id: account_id.to_owned(),
+                asset_code: "USD".to_owned(),
+                asset_scale: 2,
+                description: String::new(),
+            });
+            let bearer = MetadataValue::from_str(&format!("Bearer {}", token)).unwrap();
+            request.metadata_mut().insert("authorization", bearer);
+            request
+        };
+        let denied = rt
+            .block_on(client.create_account(request("password", "carol")))
+            .unwrap_err();
+        assert_eq!(denied.code(), tonic::Code::Unauthenticated);
+        let created = rt.block_on(client.create_account(request("admin", "carol")))?;
+        assert_eq!(created.get_ref().asset_code, "USD");
+        let existing = rt
+            .block_on(client.create_account(request("admin", "carol")))
+            .unwrap_err();
+        assert_eq!(existing.code(), tonic::Code::AlreadyExists);
+
+        let mut get = Request::new(GetAccountRequest {
+            account_id: "carol".to_owned(),
+        });
+        get.metadata_mut().insert(
+            "authorization",
+            MetadataValue::from_str("Bearer admin").unwrap(),
+        );
+        let account = rt.block_on(client.get_account(get))?;
+        assert_eq!(account.get_ref().asset_scale, 2);
+        assert_eq!(connector.balance("carol"), Some(0));
+    }
+}
@@ -0,0 +1,833 @@
+use super::{spawn, xrp, MockServer};
+use crate::bundle::JsBundle;
+use crate::error::{EngineResultCategory, XpringError as Error};
+use crate::javascript::{JavaScript, JsCall};
+use crate::x::prelude::*;
+use crate::x::transaction_result::ResultType;
+use crate::x::xrp_ledger_api_service_server::{XrpLedgerApiService, XrpLedgerApiServiceServer};
+use crate::x::{
+    affected_node, ledger_object, AffectedNode, Balance, CreatedNode, DeliveredAmount, Fee,
+    GetAccountInfoResponse, GetAccountTransactionHistoryResponse, LedgerEntryType, LedgerObject,
+    Meta, ModifiedNode, PreviousTransactionId, PreviousTransactionLedgerSequence,
+    SubmitTransactionResponse, TransactionSignature,
+};
+use fehler::throws;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tonic::{transport::Server, Request, Response, Status};
+
+/// An engine result: its code, its numeric value and its message, as reported by rippled.
+type EngineResult = (&'static str, i64, &'static str);
+
+const TES_SUCCESS: EngineResult = (
+    "tesSUCCESS",
+    0,
+    "The transaction was applied. Only final in a validated ledger.",
+);
+const TEC_UNFUNDED_PAYMENT: EngineResult = (
+    "tecUNFUNDED_PAYMENT",
+    104,
+    "Insufficient XRP balance to send.",
+);
+const TEC_NO_DST_INSUF_XRP: EngineResult = (
+    "tecNO_DST_INSUF_XRP",
+    125,
+    "Destination does not exist. Too little XRP sent to create it.",
+);
+const TEF_ALREADY: EngineResult = (
+    "tefALREADY",
+    -198,
+    "The exact transaction was already in this ledger.",
+);
+const TEF_BAD_AUTH: EngineResult = (
+    "tefBAD_AUTH",
+    -196,
+    "Transaction's public key is not authorized.",
+);
+const TEF_PAST_SEQ: EngineResult = (
+    "tefPAST_SEQ",
+    -190,
+    "This sequence number has already passed.",
+);
+const TEF_MAX_LEDGER: EngineResult = ("tefMAX_LEDGER", -187, "Ledger sequence too high.");
+const TEL_INSUF_FEE_P: EngineResult = ("telINSUF_FEE_P", -394, "Fee insufficient.");
+const TEM_BAD_AMOUNT: EngineResult = ("temBAD_AMOUNT", -298, "Can only send positive amounts.");
+const TEM_BAD_FEE: EngineResult = ("temBAD_FEE", -295, "Invalid fee, negative or not XRP.");
+const TEM_DST_NEEDED: EngineResult = ("temDST_NEEDED", -278, "Destination not specified.");
+const TEM_REDUNDANT: EngineResult = ("temREDUNDANT", -275, "Sends same currency to self.");
+const TER_INSUF_FEE_B: EngineResult = ("terINSUF_FEE_B", -97, "Account balance can't pay fee.");
+const TER_NO_ACCOUNT: EngineResult = ("terNO_ACCOUNT", -96, "The source account does not exist.");
+const TER_PRE_SEQ: EngineResult = ("terPRE_SEQ", -92, "Missing/inapplicable prior transaction.");
+
+/// Fields of a transaction decoded by xpring.js.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+struct SimTransaction {
+    transaction_type: String,
+    account: String,
+    destination: Option<String>,
+    amount: Option<serde_json::Value>,
+    fee: String,
+    sequence: u32,
+    last_ledger_sequence: Option<u32>,
+    flags: Option<u32>,
+    source_tag: Option<u32>,
+    destination_tag: Option<u32>,
+    #[serde(rename = "InvoiceID")]
+    invoice_id: Option<String>,
+    signing_pub_key: String,
+    txn_signature: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DecodedBlob {
+    transaction: SimTransaction,
+    hash: String,
+    signing_address: Option<String>,
+    signature_valid: bool,
+}
+
+#[derive(Debug, Clone)]
+struct SimAccount {
+    balance: u64,
+    sequence: u32,
+    previous_transaction: Option<(Vec<u8>, u32)>,
+}
+
+/// A transaction applied to the ledger with a `tes` or `tec` result.
+#[derive(Debug, Clone)]
+struct SimRecord {
+    hash: Vec<u8>,
+    transaction: SimTransaction,
+    amount: u64,
+    fee: u64,
+    result: EngineResult,
+    ledger_index: u32,
+    transaction_index: u64,
+    validated: bool,
+    affected_nodes: Vec<AffectedNode>,
+}
+
+struct SimState {
+    jscontext: JavaScript,
+    accounts: BTreeMap<String, SimAccount>,
+    ledger_index: u32,
+    base_fee: u64,
+    reserve: u64,
+    auto_close: bool,
+    records: Vec<SimRecord>,
+}
+
+/// Deterministic in-memory XRP Ledger that applies the transactions submitted to it.
+///
+/// Submitted blobs are decoded and their signature verified. XRP payments are checked the way
+/// rippled checks them (sequence, fee, reserve and `LastLedgerSequence`) and applied to the open
+/// ledger, which is validated when it is closed. Only XRP payments are supported. Accounts are
+/// keyed by classic address and clones share the same ledger.
+///
+/// # Example
+///
+/// ```
+/// # use xpring::testing::SimulatedLedger;
+/// # use xpring::Xrpl;
+/// # fn main() -> Result<(), xpring::error::XpringError> {
+/// let ledger = SimulatedLedger::new()?;
+/// ledger
+///     .fund("r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4", 100_000_000)
+///     .auto_close(true);
+/// let server = ledger.serve()?;
+/// let mut xrpl = Xrpl::new(server.url(), true)?;
+/// let wallet = xrpl.pool().wallet_from_seed("sn3UJSLzAEeAGcrK3nsQTDZW6KT92", None)?;
+/// xrpl.send(
+///     25.0,
+///     "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+///     "TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ",
+///     wallet,
+/// )?;
+/// assert_eq!(ledger.balance("rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7"), Some(25_000_000));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SimulatedLedger {
+    state: Arc<Mutex<SimState>>,
+}
+
+impl SimulatedLedger {
+    /// Creates an empty ledger with a base fee of 10 drops and an account reserve of 20 XRP.
+    /// Ledger 2 is open.
+    #[throws(_)]
+    pub fn new() -> SimulatedLedger {
+        let xpringjs_path = crate::bundle::install(&JsBundle::TempDir)?;
+        SimulatedLedger {
+            state: Arc::new(Mutex::new(SimState {
+                jscontext: JavaScript::new(xpringjs_path)?,
+                accounts: BTreeMap::new(),
+                ledger_index: 2,
+                base_fee: 10,
+                reserve: 20_000_000,
+                auto_close: false,
+                records: vec![],
+            })),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, SimState> {
+        self.state.lock().expect("Simulated ledger state poisoned")
+    }
+
+    /// Creates an account with sequence 1 or sets the balance of an existing one.
+    pub fn fund(&self, address: &str, balance_drops: u64) -> &SimulatedLedger {
+        self.state()
+            .accounts
+            .entry(address.to_owned())
+            .or_insert(SimAccount {
+                balance: 0,
+                sequence: 1,
+                previous_transaction: None,
+            })
+            .balance = balance_drops;
+        self
+    }
+
+    /// Sets the minimum fee in drops.
+    pub fn base_fee(&self, base_fee: u64) -> &SimulatedLedger {
+        self.state().base_fee = base_fee;
+        self
+    }
+
+    /// Sets the account reserve in drops.
+    pub fn reserve(&self, reserve: u64) -> &SimulatedLedger {
+        self.state().reserve = reserve;
+        self
+    }
+
+    /// When set, the open ledger is closed after every applied transaction.
+    pub fn auto_close(&self, auto_close: bool) -> &SimulatedLedger {
+        self.state().auto_close = auto_close;
+        self
+    }
+
+    /// Validates the transactions of the open ledger and opens the next one. Returns the index
+    /// of the closed ledger.
+    pub fn close_ledger(&self) -> u32 {
+        self.state().close_ledger()
+    }
+
+    /// Index of the open ledger.
+    pub fn ledger_index(&self) -> u32 {
+        self.state().ledger_index
+    }
+
+    /// Balance in drops of an account.
+    pub fn balance(&self, address: &str) -> Option<u64> {
+        self.state().accounts.get(address).map(|a| a.balance)
+    }
+
+    /// Next sequence number of an account.
+    pub fn sequence(&self, address: &str) -> Option<u32> {
+        self.state().accounts.get(address).map(|a| a.sequence)
+    }
+
+    /// Starts answering the gRPC API on a free localhost port.
+    #[throws(_)]
+    pub fn serve(&self) -> MockServer {
+        let ledger = self.clone();
+        spawn(move |mut listener, signal| async move {
+            let _ = Server::builder()
+                .add_service(XrpLedgerApiServiceServer::new(ledger))
+                .serve_with_incoming_shutdown(listener.incoming(), async {
+                    let _ = signal.await;
+                })
+                .await;
+        })?
+    }
+}
+
+fn status(error: Error) -> Status {
+    match error {
+        Error::Validation(message) => Status::invalid_argument(message),
+        error => Status::internal(error.to_string()),
+    }
+}
+
+fn address(address: &str) -> Option<AccountAddress> {
+    Some(AccountAddress {
+        address: address.to_owned(),
+    })
+}
+
+fn account_root(account: Option<&str>, balance: u64, sequence: u32) -> Option<LedgerObject> {
+    Some(LedgerObject {
+        object: Some(ledger_object::Object::AccountRoot(AccountRoot {
+            account: account.map(|a| Account { value: address(a) }),
+            balance: Some(Balance {
+                value: Some(xrp(balance)),
+            }),
+            sequence: Some(Sequence { value: sequence }),
+            ..Default::default()
+        })),
+    })
+}
+
+fn result_type(code: &str) -> ResultType {
+    match EngineResultCategory::from_code(code) {
+        Some(EngineResultCategory::Tec) => ResultType::Tec,
+        Some(EngineResultCategory::Tef) => ResultType::Tef,
+        Some(EngineResultCategory::Tel) => ResultType::Tel,
+        Some(EngineResultCategory::Tem) => ResultType::Tem,
+        Some(EngineResultCategory::Ter) => ResultType::Ter,
+        None if code.starts_with("tes") => ResultType::Tes,
+        None => ResultType::Unspecified,
+    }
+}
+
+fn transaction_result(result: EngineResult) -> Option<TransactionResult> {
+    Some(TransactionResult {
+        result_type: result_type(result.0) as i32,
+        result: result.0.to_owned(),
+    })
+}
+
+impl SimState {
+    fn close_ledger(&mut self) -> u32 {
+        let closed = self.ledger_index;
+        for record in self.records.iter_mut() {
+            if record.ledger_index == closed {
+                record.validated = true;
+            }
+        }
+        self.ledger_index += 1;
+        closed
+    }
+
+    #[throws(_)]
+    fn decode(&mut self, blob: &[u8]) -> DecodedBlob {
+        let jscontext = &self.jscontext;
+        let blob = hex::encode_upper(blob);
+        js!(jscontext
+            .signer
+            .decodeSignedTransaction::<DecodedBlob>(blob))?
+    }
+
+    #[throws(_)]
+    fn account_index(&mut self, address: &str) -> Vec<u8> {
+        let jscontext = &self.jscontext;
+        let index = js!(jscontext.utils.accountRootIndex::<String>(address))?;
+        hex::decode(index).map_err(|e| Error::Decode(e.to_string()))?
+    }
+
+    /// Node of an account whose balance and sequence changed.
+    fn modified_node(
+        &mut self,
+        address: &str,
+        before: &SimAccount,
+        after: &SimAccount,
+    ) -> Result<AffectedNode, Status> {
+        Ok(AffectedNode {
+            ledger_entry_type: LedgerEntryType::AccountRoot as i32,
+            ledger_index: self.account_index(address).map_err(status)?,
+            node: Some(affected_node::Node::ModifiedNode(ModifiedNode {
+                final_fields: account_root(Some(address), after.balance, after.sequence),
+                previous_fields: account_root(None, before.balance, before.sequence),
+                previous_transaction_id: before.previous_transaction.as_ref().map(|(hash, _)| {
+                    PreviousTransactionId {
+                        value: hash.clone(),
+                    }
+                }),
+                previous_transaction_ledger_sequence: before.previous_transaction.as_ref().map(
+                    |(_, ledger_index)| PreviousTransactionLedgerSequence {
+                        value: *ledger_index,
+                    },
+                ),
+            })),
+        })
+    }
+
+    fn submit(&mut self, blob: &[u8]) -> Result<SubmitTransactionResponse, Status> {
+        let decoded = self.decode(blob).map_err(status)?;
+        if !decoded.signature_valid {
+            return Err(Status::invalid_argument("Invalid signature."));
+        }
+        let hash = hex::decode(&decoded.hash).map_err(|e| Status::internal(e.to_string()))?;
+        let result = self.apply(decoded, &hash)?;
+        Ok(SubmitTransactionResponse {
+            engine_result: transaction_result(result),
+            engine_result_code: result.1,
+            engine_result_message: result.2.to_owned(),
+            hash,
+        })
+    }
+
+    /// Checks a transaction against the open ledger and applies it when its result is `tes` or
+    /// `tec`.
+    fn apply(&mut self, decoded: DecodedBlob, hash: &[u8]) -> Result<EngineResult, Status> {
+        let transaction = decoded.transaction;
+        if transaction.transaction_type != "Payment" {
+            return Err(Status::unimplemented("Only payments are simulated."));
+        }
+        let amount = match transaction.amount.as_ref() {
+            Some(serde_json::Value::String(drops)) => match drops.parse::<u64>() {
+                Ok(drops) => drops,
+                Err(_) => return Ok(TEM_BAD_AMOUNT),
+            },
+            _ => return Err(Status::unimplemented("Only XRP payments are simulated.")),
+        };
+        let fee = match transaction.fee.parse::<u64>() {
+            Ok(fee) => fee,
+            Err(_) => return Ok(TEM_BAD_FEE),
+        };
+        let destination = match transaction.destination.clone() {
+            Some(destination) => destination,
+            None => return Ok(TEM_DST_NEEDED),
+        };
+        if amount == 0 {
+            return Ok(TEM_BAD_AMOUNT);
+        }
+        if destination == transaction.account {
+            return Ok(TEM_REDUNDANT);
+        }
+        if self.records.iter().any(|r| r.hash == hash) {
+            return Ok(TEF_ALREADY);
+        }
+        if decoded.signing_address.as_ref() != Some(&transaction.account) {
+            return Ok(TEF_BAD_AUTH);
+        }
+        let source = match self.accounts.get(&transaction.account) {
+            Some(source) => source.clone(),
+            None => return Ok(TER_NO_ACCOUNT),
+        };
+        if fee < self.base_fee {
+            return Ok(TEL_INSUF_FEE_P);
+        }
+        if transaction.sequence < source.sequence {
+            return Ok(TEF_PAST_SEQ);
+        }
+        if transaction.sequence > source.sequence {
+            return Ok(TER_PRE_SEQ);
+        }
+        if transaction.last_ledger_sequence.unwrap_or(u32::MAX) < self.ledger_index {
+            return Ok(TEF_MAX_LEDGER);
+        }
+        if fee > source.balance {
+            return Ok(TER_INSUF_FEE_B);
+        }
+
+        // From here on the fee is claimed and the sequence consumed.
+        let destination_account = self.accounts.get(&destination).cloned();
+        let available = source.balance - fee;
+        let result = if available < amount.saturating_add(self.reserve) {
+            TEC_UNFUNDED_PAYMENT
+        } else if destination_account.is_none() && amount < self.reserve {
+            TEC_NO_DST_INSUF_XRP
+        } else {
+            TES_SUCCESS
+        };
+        let delivered = if result == TES_SUCCESS { amount } else { 0 };
+        let previous_transaction = Some((hash.to_vec(), self.ledger_index));
+
+        let source_after = SimAccount {
+            balance: available - delivered,
+            sequence: source.sequence + 1,
+            previous_transaction: previous_transaction.clone(),
+        };
+        let mut affected_nodes =
+            vec![self.modified_node(&transaction.account, &source, &source_after)?];
+        self.accounts
+            .insert(transaction.account.clone(), source_after);
+        if delivered > 0 {
+            let destination_after = SimAccount {
+                balance: destination_account.as_ref().map_or(0, |a| a.balance) + delivered,
+                sequence: destination_account.as_ref().map_or(1, |a| a.sequence),
+                previous_transaction,
+            };
+            affected_nodes.push(match destination_account {
+                Some(before) => self.modified_node(&destination, &before, &destination_after)?,
+                None => AffectedNode {
+                    ledger_entry_type: LedgerEntryType::AccountRoot as i32,
+                    ledger_index: self.account_index(&destination).map_err(status)?,
+                    node: Some(affected_node::Node::CreatedNode(CreatedNode {
+                        new_fields: account_root(
+                            Some(&destination),
+                            destination_after.balance,
+                            destination_after.sequence,
+                        ),
+                    })),
+                },
+            });
+            self.accounts.insert(destination, destination_after);
+        }
+
+        let ledger_index = self.ledger_index;
+        let transaction_index = self
+            .records
+            .iter()
+            .filter(|r| r.ledger_index == ledger_index)
+            .count() as u64;
+        self.records.push(SimRecord {
+            hash: hash.to_vec(),
+            transaction,
+            amount,
+            fee,
+            result,
+            ledger_index,
+            transaction_index,
+            validated: false,
+            affected_nodes,
+        });
+        if self.auto_close {
+            self.close_ledger();
+        }
+        Ok(result)
+    }
+}
+
+impl SimRecord {
+    fn involves(&self, account: &str) -> bool {
+        self.transaction.account == account
+            || self.transaction.destination.as_deref() == Some(account)
+    }
+
+    fn to_proto(&self) -> GetTransactionResponse {
+        let transaction = &self.transaction;
+        GetTransactionResponse {
+            serialized_transaction: Some(
+                get_transaction_response::SerializedTransaction::Transaction(Transaction {
+                    account: Some(Account {
+                        value: address(&transaction.account),
+                    }),
+                    fee: Some(XrpDropsAmount { drops: self.fee }),
+                    sequence: Some(Sequence {
+                        value: transaction.sequence,
+                    }),
+                    signing_public_key: Some(SigningPublicKey {
+                        value: hex::decode(&transaction.signing_pub_key).unwrap_or_default(),
+                    }),
+                    transaction_signature: transaction.txn_signature.as_ref().map(|signature| {
+                        TransactionSignature {
+                            value: hex::decode(signature).unwrap_or_default(),
+                        }
+                    }),
+                    flags: transaction.flags.map(|value| Flags { value }),
+                    last_ledger_sequence: transaction
+                        .last_ledger_sequence
+                        .map(|value| LastLedgerSequence { value }),
+                    source_tag: transaction.source_tag.map(|value| SourceTag { value }),
+                    transaction_data: Some(TransactionData::Payment(Payment {
+                        amount: Some(Amount {
+                            value: Some(xrp(self.amount)),
+                        }),
+                        destination: transaction
+                            .destination
+                            .as_ref()
+                            .map(|d| Destination { value: address(d) }),
+                        destination_tag: transaction
+                            .destination_tag
+                            .map(|value| DestinationTag { value }),
+                        invoice_id: transaction.invoice_id.as_ref().map(|id| InvoiceId {
+                            value: hex::decode(id).unwrap_or_default(),
+                        }),
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                }),
+            ),
+            ledger_index: self.ledger_index,
+            hash: self.hash.clone(),
+            validated: self.validated,
+            serialized_meta: Some(get_transaction_response::SerializedMeta::Meta(Meta {
+                transaction_index: self.transaction_index,
+                transaction_result: transaction_result(self.result),
+                affected_nodes: self.affected_nodes.clone(),
+                delivered_amount: Some(DeliveredAmount {
+                    value: Some(xrp(if self.result == TES_SUCCESS {
+                        self.amount
+                    } else {
+                        0
+                    })),
+                }),
+            })),
+            date: None,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl XrpLedgerApiService for SimulatedLedger {
+    async fn get_account_info(
+        &self,
+        request: Request<GetAccountInfoRequest>,
+    ) -> Result<Response<GetAccountInfoResponse>, Status> {
+        let account = request
+            .into_inner()
+            .account
+            .map(|a| a.address)
+            .unwrap_or_default();
+        let state = self.state();
+        let root = state
+            .accounts
+            .get(&account)
+            .ok_or_else(|| Status::not_found("account not found"))?;
+        Ok(Response::new(GetAccountInfoResponse {
+            account_data: Some(AccountRoot {
+                account: Some(Account {
+                    value: address(&account),
+                }),
+                balance: Some(Balance {
+                    value: Some(xrp(root.balance)),
+                }),
+                sequence: Some(Sequence {
+                    value: root.sequence,
+                }),
+                ..Default::default()
+            }),
+            ledger_index: state.ledger_index,
+            validated: false,
+            ..Default::default()
+        }))
+    }
+
+    async fn get_fee(
+        &self,
+        _request: Request<GetFeeRequest>,
+    ) -> Result<Response<GetFeeResponse>, Status> {
+        let state = self.state();
+        let drops = Some(XrpDropsAmount {
+            drops: state.base_fee,
+        });
+        Ok(Response::new(GetFeeResponse {
+            fee: Some(Fee {
+                base_fee: drops.clone(),
+                median_fee: drops.clone(),
+                minimum_fee: drops.clone(),
+                open_ledger_fee: drops,
+            }),
+            ledger_current_index: state.ledger_index,
+            ..Default::default()
+        }))
+    }
+
+    async fn submit_transaction(
+        &self,
+        request: Request<SubmitTransactionRequest>,
+    ) -> Result<Response<SubmitTransactionResponse>, Status> {
+        let blob = request.into_inner().signed_transaction;
+        Ok(Response::new(self.state().submit(&blob)?))
+    }
+
+    async fn get_transaction(
+        &self,
+        request: Request<GetTransactionRequest>,
+    ) -> Result<Response<GetTransactionResponse>, Status> {
+        let hash = request.into_inner().hash;
+        let state = self.state();
+        let record = state
+            .records
+            .iter()
+            .find(|r| r.hash == hash)
+            .ok_or_else(|| Status::not_found("txn not found"))?;
+        Ok(Response::new(record.to_proto()))
+    }
+
+    async fn get_account_transaction_history(
+        &self,
+        request: Request<GetAccountTransactionHistoryRequest>,
+    ) -> Result<Response<GetAccountTransactionHistoryResponse>, Status> {
+        let request = request.into_inner();
+        let account = request.account.map(|a| a.address).unwrap_or_default();
+        let state = self.state();
+        let (min, max) = match request.ledger {
+            Some(get_account_transaction_history_request::Ledger::LedgerRange(range)) => (
+                range.ledger_index_min,
+                match range.ledger_index_max {
+                    0 => state.ledger_index - 1,
+                    max => max,
+                },
+            ),
+            _ => (1, state.ledger_index - 1),
+        };
+        let mut transactions: Vec<GetTransactionResponse> = state
+            .records
+            .iter()
+            .filter(|r| r.validated && r.ledger_index >= min && r.ledger_index <= max)
+            .filter(|r| r.involves(&account))
+            .map(SimRecord::to_proto)
+            .collect();
+        if !request.forward {
+            transactions.reverse();
+        }
+        Ok(Response::new(GetAccountTransactionHistoryResponse {
+            account: address(&account),
+            ledger_index_min: min,
+            ledger_index_max: max,
+            transactions,
+            validated: true,
+            ..Default::default()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::transaction::{self, XAmount, XCurrencyAmount, XPayment, XTransactionStatus};
+    use crate::wallet::{self, XWallet};
+    use crate::xrplclient::XrplClient;
+    use crate::Xrpl;
+    use std::thread;
+    use std::time::Duration;
+
+    const SENDER: &str = "r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4";
+    const SENDER_X: &str = "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs";
+    const SENDER_SEED: &str = "sn3UJSLzAEeAGcrK3nsQTDZW6KT92";
+    const RECEIVER: &str = "rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7";
+    const RECEIVER_X: &str = "TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ";
+
+    /// Signs a payment of `drops` from the sender to the receiver and submits it directly.
+    #[throws(_)]
+    fn submit(ledger: &SimulatedLedger, drops: u64, fee: u64, sequence: u32) -> String {
+        let jscontext = JavaScript::new(crate::bundle::install(&JsBundle::TempDir)?)?;
+        let wallet: XWallet = wallet::from_seed(&jscontext, SENDER_SEED.to_owned(), None, true)?;
+        let amount = XCurrencyAmount::Xrp(XAmount { drops });
+        let mut payment = XPayment::with_amount(amount, SENDER_X, RECEIVER_X);
+        transaction::resolve_destination(&jscontext, &mut payment)?;
+        let transaction =
+            transaction::build_payment_transaction(payment, fee, sequence, 10, &wallet.public_key)?;
+        let signed = transaction::sign_transaction(&jscontext, &transaction, &wallet)?;
+        let response = ledger
+            .state()
+            .submit(&hex::decode(signed.result).unwrap())
+            .map_err(Error::from)?;
+        response.engine_result.unwrap().result
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_simulated_send() {
+        let ledger = SimulatedLedger::new()?;
+        ledger
+            .fund(SENDER, 100_000_000)
+            .base_fee(12)
+            .auto_close(true);
+        let server = ledger.serve()?;
+        let mut xrpl = Xrpl::new(server.url(), true)?;
+        let wallet = xrpl.pool().wallet_from_seed(SENDER_SEED, None)?;
+
+        // Too little to create the receiver: the fee is claimed anyway.
+        let response = xrpl.send(12.12, SENDER_X, RECEIVER_X, wallet)?;
+        assert_eq!(response.transaction_status, XTransactionStatus::FAILED);
+        assert_eq!(
+            response.transaction_info,
+            "Destination does not exist. Too little XRP sent to create it."
+        );
+        assert_eq!(ledger.balance(SENDER), Some(100_000_000 - 12));
+        assert_eq!(ledger.sequence(SENDER), Some(2));
+        assert_eq!(ledger.balance(RECEIVER), None);
+
+        let wallet = xrpl.pool().wallet_from_seed(SENDER_SEED, None)?;
+        let response = xrpl.send(25.0, SENDER_X, RECEIVER_X, wallet)?;
+        assert_eq!(response.transaction_status, XTransactionStatus::SUCCEEDED);
+        assert_eq!(ledger.balance(SENDER), Some(100_000_000 - 25_000_024));
+        assert_eq!(ledger.balance(RECEIVER), Some(25_000_000));
+        assert_eq!(ledger.ledger_index(), 4);
+
+        let payments = xrpl.get_incoming_payments(RECEIVER_X, 1, 0)?;
+        assert_eq!(payments.len(), 1);
+        assert_eq!(payments[0].transaction_hash, response.transaction_hash);
+        assert_eq!(payments[0].ledger_index, 3);
+        assert_eq!(payments[0].from_address, SENDER);
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_simulated_send_waits_for_close() {
+        let ledger = SimulatedLedger::new()?;
+        ledger.fund(SENDER, 100_000_000).fund(RECEIVER, 30_000_000);
+        let server = ledger.serve()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        let jscontext = JavaScript::new(crate::bundle::install(&JsBundle::TempDir)?)?;
+        let wallet = wallet::from_seed(&jscontext, SENDER_SEED.to_owned(), None, true)?;
+        let closer = ledger.clone();
+        let close = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(500));
+            closer.close_ledger()
+        });
+        let response = client.send(&jscontext, 1.0, SENDER_X, RECEIVER_X, wallet)?;
+        assert_eq!(close.join().unwrap(), 2);
+        assert_eq!(response.transaction_status, XTransactionStatus::SUCCEEDED);
+        assert_eq!(ledger.balance(RECEIVER), Some(31_000_000));
+        assert_eq!(
+            client.get_transaction_status(&response.transaction_hash)?,
+            XTransactionStatus::SUCCEEDED
+        );
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_simulated_checks() {
+        let ledger = SimulatedLedger::new()?;
+        ledger.fund(SENDER, 50_000_000).fund(RECEIVER, 0);
+        assert_eq!(submit(&ledger, 1_000, 12, 2)?, "terPRE_SEQ");
+        assert_eq!(submit(&ledger, 1_000, 5, 1)?, "telINSUF_FEE_P");
+        assert_eq!(submit(&ledger, 40_000_000, 12, 1)?, "tecUNFUNDED_PAYMENT");
+        assert_eq!(ledger.balance(SENDER), Some(50_000_000 - 12));
+        assert_eq!(submit(&ledger, 1_000, 12, 1)?, "tefPAST_SEQ");
+        assert_eq!(submit(&ledger, 1_000, 12, 2)?, "tesSUCCESS");
+        assert_eq!(submit(&ledger, 1_000, 12, 2)?, "tefALREADY");
+        assert_eq!(ledger.balance(RECEIVER), Some(1_000));
+        for _ in 0..9 {
+            ledger.close_ledger();
+        }
+        assert_eq!(submit(&ledger, 1_000, 12, 3)?, "tefMAX_LEDGER");
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_simulated_meta() {
+        let ledger = SimulatedLedger::new()?;
+        ledger.fund(SENDER, 50_000_000).fund(RECEIVER, 20_000_000);
+        submit(&ledger, 1_000, 12, 1)?;
+        let state = ledger.state();
+        let record = &state.records[0];
+        assert!(!record.validated);
+        let meta = match record.to_proto().serialized_meta {
+            Some(get_transaction_response::SerializedMeta::Meta(meta)) => meta,
+            _ => panic!("expected meta"),
+        };
+        assert_eq!(meta.transaction_index, 0);
+        assert_eq!(meta.affected_nodes.len(), 2);
+        assert_eq!(meta.delivered_amount.unwrap().value.unwrap(), xrp(1_000));
+        let sender = &meta.affected_nodes[0];
+        // Index of the AccountRoot of r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4.
+        assert_eq!(sender.ledger_index.len(), 32);
+        match sender.node.as_ref() {
+            Some(affected_node::Node::ModifiedNode(node)) => {
+                assert_eq!(
+                    node.final_fields,
+                    account_root(Some(SENDER), 50_000_000 - 1_012, 2)
+                );
+                assert_eq!(node.previous_fields, account_root(None, 50_000_000, 1));
+            }
+            node => panic!("expected a modified node, got {:?}", node),
+        }
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_simulated_invalid_signature() {
+        let ledger = SimulatedLedger::new()?;
+        let jscontext = JavaScript::new(crate::bundle::install(&JsBundle::TempDir)?)?;
+        let wallet = wallet::from_seed(&jscontext, SENDER_SEED.to_owned(), None, true)?;
+        let payment = XPayment::new(1.0, SENDER_X, RECEIVER_X);
+        let transaction =
+            transaction::build_payment_transaction(payment, 12, 1, 10, &wallet.public_key)?;
+        let signed = transaction::sign_transaction(&jscontext, &transaction, &wallet)?;
+        let mut blob = hex::decode(signed.result).unwrap();
+        let last = blob.len() - 1;
+        blob[last] ^= 1;
+        let status = ledger.state().submit(&blob).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -1,8 +1,9 @@
+use crate::address;
+use crate::error::XpringError as Error;
 use crate::javascript::{JavaScript, JsCall};
 use crate::wallet::XWallet;
 use crate::x::prelude::*;
-use anyhow::{bail, Error};
-use fehler::throws;
+use fehler::{throw, throws};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::str;
@@ -15,15 +16,224 @@ pub enum XTransactionStatus {
     UNKNOWN,
 }
 
-#[derive(PartialEq, Debug, Serialize, Deserialize)]
-pub(crate) struct XPayment {
-    pub amount: XAmount,
+/// Version of the JSON files written by `XUnsignedTransaction::to_json` and
+/// `XSignedTransaction::to_json`.
+pub const OFFLINE_FORMAT_VERSION: u32 = 1;
+
+/// Highest fee, in drops, that `XrplPool::sign_transaction` signs. Fees are a few drops unless the
+/// network is heavily loaded, so a higher fee is most likely a mistake in the prepared file.
+pub const MAX_OFFLINE_FEE: u64 = 2_000_000;
+
+/// Serializes optional bytes as an uppercase hex string.
+mod hex_bytes {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error> {
+        match bytes {
+            Some(bytes) => s.serialize_some(&hex::encode(bytes).to_uppercase()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|bytes| hex::decode(bytes).map_err(de::Error::custom))
+            .transpose()
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XMemo {
+    #[serde(with = "hex_bytes", default)]
+    pub memo_type: Option<Vec<u8>>,
+    #[serde(with = "hex_bytes", default)]
+    pub memo_format: Option<Vec<u8>>,
+    #[serde(with = "hex_bytes", default)]
+    pub memo_data: Option<Vec<u8>>,
+}
+
+impl XMemo {
+    pub fn new<D: Into<Vec<u8>>>(memo_data: D) -> XMemo {
+        XMemo {
+            memo_type: None,
+            memo_format: None,
+            memo_data: Some(memo_data.into()),
+        }
+    }
+}
+
+/// Allows a payment to deliver less than `amount` (tfPartialPayment).
+pub const PARTIAL_PAYMENT_FLAG: u32 = 0x0002_0000;
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct XIssuedCurrencyAmount {
+    pub currency: String,
+    pub issuer: String,
+    pub value: String,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub enum XCurrencyAmount {
+    Xrp(XAmount),
+    Issued(XIssuedCurrencyAmount),
+}
+
+impl XCurrencyAmount {
+    pub fn xrp(amount: f32) -> XCurrencyAmount {
+        XCurrencyAmount::Xrp(XAmount::new(amount))
+    }
+
+    pub fn issued<S: Into<String>>(currency: S, issuer: S, value: S) -> XCurrencyAmount {
+        XCurrencyAmount::Issued(XIssuedCurrencyAmount {
+            currency: currency.into(),
+            issuer: issuer.into(),
+            value: value.into(),
+        })
+    }
+
+    pub fn is_xrp(&self) -> bool {
+        matches!(self, XCurrencyAmount::Xrp(_))
+    }
+
+    pub(crate) fn to_proto(&self) -> CurrencyAmount {
+        let amount = match self {
+            XCurrencyAmount::Xrp(a) => {
+                currency_amount::Amount::XrpAmount(XrpDropsAmount { drops: a.drops })
+            }
+            XCurrencyAmount::Issued(a) => {
+                currency_amount::Amount::IssuedCurrencyAmount(IssuedCurrencyAmount {
+                    currency: Some(Currency {
+                        name: a.currency.to_owned(),
+                        code: zero_vector::<u8>(),
+                    }),
+                    value: a.value.to_owned(),
+                    issuer: Some(AccountAddress {
+                        address: a.issuer.to_owned(),
+                    }),
+                })
+            }
+        };
+        CurrencyAmount {
+            amount: Some(amount),
+        }
+    }
+
+    pub(crate) fn from_proto(amount: &CurrencyAmount) -> Option<XCurrencyAmount> {
+        match amount.amount.as_ref()? {
+            currency_amount::Amount::XrpAmount(d) => {
+                Some(XCurrencyAmount::Xrp(XAmount { drops: d.drops }))
+            }
+            currency_amount::Amount::IssuedCurrencyAmount(i) => {
+                let currency = i.currency.as_ref()?;
+                let currency = if currency.name.is_empty() {
+                    hex::encode(&currency.code).to_uppercase()
+                } else {
+                    currency.name.to_owned()
+                };
+                Some(XCurrencyAmount::Issued(XIssuedCurrencyAmount {
+                    currency,
+                    issuer: i.issuer.as_ref()?.address.to_owned(),
+                    value: i.value.to_owned(),
+                }))
+            }
+        }
+    }
+}
+
+/// A step of a payment path. Accounts and issuers are classic addresses.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XPathElement {
+    pub account: Option<String>,
+    pub currency: Option<String>,
+    pub issuer: Option<String>,
+}
+
+pub type XPath = Vec<XPathElement>;
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct XPayment {
+    pub amount: XCurrencyAmount,
     pub from_address: String,
     pub to_address: String,
+    pub destination_tag: Option<u32>,
+    pub source_tag: Option<u32>,
+    #[serde(with = "hex_bytes", default)]
+    pub invoice_id: Option<Vec<u8>>,
+    pub memos: Vec<XMemo>,
+    pub send_max: Option<XCurrencyAmount>,
+    pub deliver_min: Option<XCurrencyAmount>,
+    pub paths: Vec<XPath>,
+    pub partial_payment: bool,
+}
+
+impl<'a> XPayment {
+    pub fn new<S: Into<String>>(amount: f32, from_address: S, to_address: S) -> XPayment {
+        XPayment::with_amount(XCurrencyAmount::xrp(amount), from_address, to_address)
+    }
+
+    pub fn with_amount<S: Into<String>>(
+        amount: XCurrencyAmount,
+        from_address: S,
+        to_address: S,
+    ) -> XPayment {
+        XPayment {
+            amount,
+            from_address: from_address.into(),
+            to_address: to_address.into(),
+            destination_tag: None,
+            source_tag: None,
+            invoice_id: None,
+            memos: zero_vector::<XMemo>(),
+            send_max: None,
+            deliver_min: None,
+            paths: zero_vector::<XPath>(),
+            partial_payment: false,
+        }
+    }
+
+    pub fn send_max(&'a mut self, send_max: XCurrencyAmount) -> &'a mut XPayment {
+        self.send_max = Some(send_max);
+        self
+    }
+
+    pub fn deliver_min(&'a mut self, deliver_min: XCurrencyAmount) -> &'a mut XPayment {
+        self.deliver_min = Some(deliver_min);
+        self
+    }
+
+    pub fn path(&'a mut self, path: XPath) -> &'a mut XPayment {
+        self.paths.push(path);
+        self
+    }
+
+    pub fn partial_payment(&'a mut self, partial_payment: bool) -> &'a mut XPayment {
+        self.partial_payment = partial_payment;
+        self
+    }
+
+    pub fn destination_tag(&'a mut self, destination_tag: u32) -> &'a mut XPayment {
+        self.destination_tag = Some(destination_tag);
+        self
+    }
+
+    pub fn source_tag(&'a mut self, source_tag: u32) -> &'a mut XPayment {
+        self.source_tag = Some(source_tag);
+        self
+    }
+
+    pub fn invoice_id(&'a mut self, invoice_id: Vec<u8>) -> &'a mut XPayment {
+        self.invoice_id = Some(invoice_id);
+        self
+    }
+
+    pub fn memo(&'a mut self, memo: XMemo) -> &'a mut XPayment {
+        self.memos.push(memo);
+        self
+    }
 }
 
-#[derive(PartialEq, Debug, Serialize, Deserialize)]
-pub(crate) struct XAmount {
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct XAmount {
     pub drops: u64,
 }
 
@@ -32,10 +242,11 @@ pub(crate) struct XRawTransactionStatus {
     pub transaction_result: TransactionResult,
     pub last_ledger_sequence: u32,
     pub validated: bool,
+    pub delivered_amount: Option<XCurrencyAmount>,
 }
 
 impl XAmount {
-    pub(crate) fn new(amount: f32) -> XAmount {
+    pub fn new(amount: f32) -> XAmount {
         let drops = amount * 1_000_000.;
         XAmount {
             drops: drops as u64,
@@ -43,9 +254,121 @@ impl XAmount {
     }
 }
 
+/// A validated, successful payment received by an account.
+///
+/// `amount` is the amount the sender asked to deliver. When crediting an account use
+/// `delivered_amount`: partial payments can deliver much less than `amount`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct XIncomingPayment {
+    pub transaction_hash: String,
+    pub ledger_index: u32,
+    pub from_address: String,
+    pub amount: XCurrencyAmount,
+    pub delivered_amount: Option<XCurrencyAmount>,
+    pub destination_tag: Option<u32>,
+    pub source_tag: Option<u32>,
+    pub partial_payment: bool,
+}
+
+/// A payment with everything the ledger needs, ready to be signed without a network
+/// connection.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct XUnsignedTransaction {
+    /// The payment, with its destination resolved into a classic address and a destination tag.
+    pub payment: XPayment,
+    /// Fee in drops.
+    pub fee: u64,
+    pub sequence: u32,
+    pub last_ledger_sequence: u32,
+    /// Public key expected to sign the transaction, when it is known.
+    pub signing_public_key: Option<String>,
+    /// Whether the transaction is for the test network.
+    pub test: bool,
+}
+
+/// A signed transaction, ready to be submitted.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct XSignedTransaction {
+    /// Hex encoded transaction blob.
+    pub transaction_blob: String,
+    pub transaction_hash: String,
+    pub last_ledger_sequence: u32,
+    /// Whether the transaction is for the test network.
+    pub test: bool,
+}
+
+/// The JSON file of an offline transaction: the transaction fields next to the format version
+/// and the stage of the transaction.
+#[derive(Serialize, Deserialize)]
+struct OfflineFile<T> {
+    version: u32,
+    stage: String,
+    #[serde(flatten)]
+    transaction: T,
+}
+
+#[throws(_)]
+fn to_offline_json<T: Serialize>(stage: &str, transaction: T) -> String {
+    serde_json::to_string_pretty(&OfflineFile {
+        version: OFFLINE_FORMAT_VERSION,
+        stage: stage.to_owned(),
+        transaction,
+    })
+    .map_err(|e| Error::Decode(format!("Invalid {} transaction: {}", stage, e)))?
+}
+
+#[throws(_)]
+fn from_offline_json<T: serde::de::DeserializeOwned>(stage: &str, json: &str) -> T {
+    let invalid =
+        |e: serde_json::Error| Error::Decode(format!("Invalid {} transaction: {}", stage, e));
+    let file: OfflineFile<Value> = serde_json::from_str(json).map_err(invalid)?;
+    if file.version != OFFLINE_FORMAT_VERSION {
+        throw!(Error::Decode(format!(
+            "Unsupported offline transaction version {}",
+            file.version
+        )));
+    }
+    if file.stage != stage {
+        throw!(Error::Decode(format!(
+            "Expected the {} stage, found the {} stage",
+            stage, file.stage
+        )));
+    }
+    serde_json::from_value(file.transaction).map_err(invalid)?
+}
+
+impl XUnsignedTransaction {
+    /// Writes the transaction as versioned JSON, to be carried to the signing machine.
+    #[throws(_)]
+    pub fn to_json(&self) -> String {
+        to_offline_json("unsigned", self)?
+    }
+
+    /// Reads a transaction written by `to_json`.
+    #[throws(_)]
+    pub fn from_json(json: &str) -> XUnsignedTransaction {
+        from_offline_json("unsigned", json)?
+    }
+}
+
+impl XSignedTransaction {
+    /// Writes the transaction as versioned JSON, to be carried back for submission.
+    #[throws(_)]
+    pub fn to_json(&self) -> String {
+        to_offline_json("signed", self)?
+    }
+
+    /// Reads a transaction written by `to_json`.
+    #[throws(_)]
+    pub fn from_json(json: &str) -> XSignedTransaction {
+        from_offline_json("signed", json)?
+    }
+}
+
 #[derive(PartialEq, Deserialize, Debug)]
-pub(crate) struct XSignedTransaction {
+pub(crate) struct XSignerResult {
     pub result: String,
+    pub hash: String,
 }
 
 pub(crate) fn from_raw_status(raw_status: XRawTransactionStatus) -> XTransactionStatus {
@@ -59,10 +382,88 @@ pub(crate) fn from_raw_status(raw_status: XRawTransactionStatus) -> XTransaction
     }
 }
 
+/// Returns the payment contained in `response` if it is a validated, successful
+/// payment to `account` (a classic address).
+pub(crate) fn incoming_payment(
+    account: &str,
+    response: &GetTransactionResponse,
+) -> Option<XIncomingPayment> {
+    if !response.validated {
+        return None;
+    }
+    let transaction = match response.serialized_transaction.as_ref()? {
+        get_transaction_response::SerializedTransaction::Transaction(t) => t,
+        _ => return None,
+    };
+    let meta = match response.serialized_meta.as_ref()? {
+        get_transaction_response::SerializedMeta::Meta(m) => m,
+        _ => return None,
+    };
+    let payment = match transaction.transaction_data.as_ref()? {
+        TransactionData::Payment(p) => p,
+        _ => return None,
+    };
+    let destination = payment.destination.as_ref()?.value.as_ref()?;
+    if destination.address != account
+        || !meta.transaction_result.as_ref()?.result.starts_with("tes")
+    {
+        return None;
+    }
+    let amount = XCurrencyAmount::from_proto(payment.amount.as_ref()?.value.as_ref()?)?;
+    let flags = transaction.flags.as_ref().map_or(0, |f| f.value);
+    let partial_payment = flags & PARTIAL_PAYMENT_FLAG != 0;
+    let delivered_amount = match meta.delivered_amount.as_ref() {
+        Some(d) => d.value.as_ref().and_then(XCurrencyAmount::from_proto),
+        // Without a partial payment the full amount is always delivered.
+        None if !partial_payment => Some(amount.clone()),
+        None => None,
+    };
+    Some(XIncomingPayment {
+        transaction_hash: hex::encode(&response.hash).to_uppercase(),
+        ledger_index: response.ledger_index,
+        from_address: transaction
+            .account
+            .as_ref()?
+            .value
+            .as_ref()?
+            .address
+            .to_owned(),
+        amount,
+        delivered_amount,
+        destination_tag: payment.destination_tag.as_ref().map(|t| t.value),
+        source_tag: transaction.source_tag.as_ref().map(|t| t.value),
+        partial_payment,
+    })
+}
+
+#[throws(_)]
+fn serialize_amount(amount: Option<&CurrencyAmount>) -> Value {
+    match amount.and_then(|a| a.amount.as_ref()) {
+        Some(currency_amount::Amount::XrpAmount(d)) => json!({
+            "xrp_amount": {
+                "drops": d.drops
+            }
+        }),
+        Some(currency_amount::Amount::IssuedCurrencyAmount(i)) => json!({
+            "issued_currency_amount": {
+                "currency": i.currency.as_ref().map(|c| c.name.to_owned()),
+                "issuer": i.issuer.as_ref().map(|a| a.address.to_owned()),
+                "value": i.value
+            }
+        }),
+        None => throw!(Error::Decode("Error parsing transaction amount".to_owned())),
+    }
+}
+
 #[throws(_)]
 pub(crate) fn serialize_transaction(transaction: &Transaction) -> Value {
     let trx_destination;
-    let trx_xrp_amount;
+    let trx_amount;
+    let trx_destination_tag;
+    let trx_invoice_id;
+    let trx_send_max;
+    let trx_deliver_min;
+    let trx_paths: Vec<Value>;
     if let Some(TransactionData::Payment(c)) = &transaction.transaction_data {
         trx_destination = &c
             .destination
@@ -72,24 +473,50 @@ pub(crate) fn serialize_transaction(transaction: &Transaction) -> Value {
             .as_ref()
             .unwrap()
             .address;
-        if let currency_amount::Amount::XrpAmount(d) = &c
-            .amount
-            .as_ref()
-            .unwrap()
-            .value
-            .as_ref()
-            .unwrap()
-            .amount
+        trx_amount = serialize_amount(c.amount.as_ref().and_then(|a| a.value.as_ref()))?;
+        trx_send_max = match &c.send_max {
+            Some(send_max) => serialize_amount(send_max.value.as_ref())?,
+            None => Value::Null,
+        };
+        trx_deliver_min = match &c.deliver_min {
+            Some(deliver_min) => serialize_amount(deliver_min.value.as_ref())?,
+            None => Value::Null,
+        };
+        trx_paths = c
+            .paths
+            .iter()
+            .map(|p| {
+                p.elements
+                    .iter()
+                    .map(|e| {
+                        json!({
+                            "account": e.account.as_ref().map(|a| a.address.to_owned()),
+                            "currency": e.currency.as_ref().map(|c| c.name.to_owned()),
+                            "issuer": e.issuer.as_ref().map(|a| a.address.to_owned()),
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        trx_destination_tag = c.destination_tag.as_ref().map(|t| t.value);
+        trx_invoice_id = c
+            .invoice_id
             .as_ref()
-            .unwrap()
-        {
-            trx_xrp_amount = d.drops;
-        } else {
-            bail!("Error parsing transaction amount");
-        }
+            .map(|i| hex::encode(&i.value).to_uppercase());
     } else {
-        bail!("Error parsing transaction data");
+        throw!(Error::Decode("Error parsing transaction data".to_owned()));
     }
+    let trx_memos: Vec<Value> = transaction
+        .memos
+        .iter()
+        .map(|m| {
+            json!({
+                "memo_type": m.memo_type.as_ref().map(|v| hex::encode(&v.value).to_uppercase()),
+                "memo_format": m.memo_format.as_ref().map(|v| hex::encode(&v.value).to_uppercase()),
+                "memo_data": m.memo_data.as_ref().map(|v| hex::encode(&v.value).to_uppercase()),
+            })
+        })
+        .collect();
     let signing_public_key_hex =
         str::from_utf8(&transaction.signing_public_key.as_ref().unwrap().value);
     let trx = json!({
@@ -100,13 +527,19 @@ pub(crate) fn serialize_transaction(transaction: &Transaction) -> Value {
         },
         "sequence": transaction.sequence.as_ref().unwrap().value,
         "payment": {
-          "xrp_amount": {
-            "drops": trx_xrp_amount
-          },
-          "destination": trx_destination
+          "amount": trx_amount,
+          "send_max": trx_send_max,
+          "deliver_min": trx_deliver_min,
+          "paths": trx_paths,
+          "destination": trx_destination,
+          "destination_tag": trx_destination_tag,
+          "invoice_id": trx_invoice_id
         },
         "signing_public_key_hex": signing_public_key_hex.unwrap(),
-        "last_ledger_sequence": transaction.last_ledger_sequence.as_ref().unwrap().value
+        "last_ledger_sequence": transaction.last_ledger_sequence.as_ref().unwrap().value,
+        "source_tag": transaction.source_tag.as_ref().map(|t| t.value),
+        "flags": transaction.flags.as_ref().map(|f| f.value),
+        "memos": trx_memos
       }
     });
     trx
@@ -119,27 +552,194 @@ pub(crate) fn zero_vector<T>() -> Vec<T> {
 
 #[throws(_)]
 pub(crate) fn sign_transaction(
-    jscontext: &mut JavaScript,
+    jscontext: &JavaScript,
     transaction: &Transaction,
     wallet: &XWallet,
-) -> XSignedTransaction {
+) -> XSignerResult {
     let trx = serialize_transaction(transaction)?;
+    let wallet = wallet.signing();
     let result = js!(jscontext
         .signer
-        .signTransaction::<XSignedTransaction>(trx, wallet))?;
+        .signTransaction::<XSignerResult>(trx, wallet))?;
     result
 }
 
+/// Signs a prepared payment with `wallet`, without a network connection.
+#[throws(_)]
+pub(crate) fn sign_unsigned_transaction(
+    jscontext: &JavaScript,
+    unsigned: &XUnsignedTransaction,
+    wallet: &XWallet,
+) -> XSignedTransaction {
+    if wallet.test != unsigned.test {
+        throw!(Error::Validation(
+            "The wallet and the transaction are for different networks".to_owned()
+        ));
+    }
+    if let Some(signing_public_key) = &unsigned.signing_public_key {
+        if !signing_public_key.eq_ignore_ascii_case(&wallet.public_key) {
+            throw!(Error::Validation(
+                "The wallet does not have the public key the transaction was prepared for"
+                    .to_owned()
+            ));
+        }
+    }
+    if unsigned.fee > MAX_OFFLINE_FEE {
+        throw!(Error::Validation(format!(
+            "The fee of {} drops is above the maximum of {} drops",
+            unsigned.fee, MAX_OFFLINE_FEE
+        )));
+    }
+    let transaction = build_payment_transaction(
+        unsigned.payment.clone(),
+        unsigned.fee,
+        unsigned.sequence,
+        unsigned.last_ledger_sequence,
+        &wallet.public_key,
+    )?;
+    let signed = sign_transaction(jscontext, &transaction, wallet)?;
+    XSignedTransaction {
+        transaction_blob: signed.result,
+        transaction_hash: signed.hash,
+        last_ledger_sequence: unsigned.last_ledger_sequence,
+        test: unsigned.test,
+    }
+}
+
+/// Fields of a signed transaction blob, as decoded by xpring.js.
+#[derive(Deserialize)]
+struct XDecodedBlob {
+    transaction: XDecodedFields,
+    hash: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct XDecodedFields {
+    last_ledger_sequence: Option<u32>,
+}
+
+/// Checks the hash and the last ledger sequence recorded next to a signed transaction against
+/// its blob, and returns the last ledger sequence of the blob.
+#[throws(_)]
+pub(crate) fn verify_signed_transaction(
+    jscontext: &JavaScript,
+    signed: &XSignedTransaction,
+) -> u32 {
+    let blob = signed.transaction_blob.clone();
+    let decoded = js!(jscontext
+        .signer
+        .decodeSignedTransaction::<XDecodedBlob>(blob))?;
+    if !decoded.hash.eq_ignore_ascii_case(&signed.transaction_hash) {
+        throw!(Error::Validation(format!(
+            "The transaction hash {} does not match the hash of the blob {}",
+            signed.transaction_hash, decoded.hash
+        )));
+    }
+    match decoded.transaction.last_ledger_sequence {
+        Some(sequence) if sequence == signed.last_ledger_sequence => sequence,
+        Some(sequence) => throw!(Error::Validation(format!(
+            "The last ledger sequence {} does not match the last ledger sequence of the blob {}",
+            signed.last_ledger_sequence, sequence
+        ))),
+        None => throw!(Error::Validation(
+            "The transaction has no last ledger sequence, so its status cannot be reliably determined"
+                .to_owned()
+        )),
+    }
+}
+
+/// Resolves the payment destination into a classic address, moving the tag
+/// embedded in an X-Address (if any) into `destination_tag`.
+#[throws(_)]
+pub(crate) fn resolve_destination(jscontext: &JavaScript, payment: &mut XPayment) {
+    if !address::is_valid_x_address(jscontext, &payment.to_address)? {
+        return;
+    }
+    let decoded_address = address::decode_x_address(jscontext, &payment.to_address)?;
+    if let Some(tag) = decoded_address.tag {
+        match payment.destination_tag {
+            Some(destination_tag) if destination_tag != tag => {
+                throw!(Error::Validation(format!(
+                    "Destination tag {} conflicts with the X-Address tag {}",
+                    destination_tag, tag
+                )));
+            }
+            _ => {
+                payment.destination_tag = Some(tag);
+            }
+        }
+    }
+    payment.to_address = decoded_address.address;
+}
+
 #[throws(_)]
 pub(crate) fn build_payment_transaction(
     payment: XPayment,
     fee: u64,
     trx_sequence: u32,
     last_ledger_sequence: u32,
-    source_wallet: &XWallet,
+    signing_public_key: &str,
 ) -> Transaction {
     let from = payment.from_address;
     let to = payment.to_address;
+    if let Some(invoice_id) = &payment.invoice_id {
+        if invoice_id.len() != 32 {
+            throw!(Error::Validation(
+                "Invoice ID must be 32 bytes long".to_owned()
+            ));
+        }
+    }
+    if payment.deliver_min.is_some() && !payment.partial_payment {
+        throw!(Error::Validation(
+            "deliver_min can only be used in partial payments".to_owned()
+        ));
+    }
+    if payment.amount.is_xrp() && payment.send_max.iter().all(|m| m.is_xrp()) {
+        if payment.send_max.is_some() {
+            throw!(Error::Validation(
+                "send_max cannot be used in XRP to XRP payments".to_owned()
+            ));
+        }
+        if !payment.paths.is_empty() {
+            throw!(Error::Validation(
+                "paths cannot be used in XRP to XRP payments".to_owned()
+            ));
+        }
+        if payment.partial_payment {
+            throw!(Error::Validation(
+                "XRP to XRP payments cannot be partial payments".to_owned()
+            ));
+        }
+    }
+    let paths = payment
+        .paths
+        .iter()
+        .map(|p| xPath {
+            elements: p
+                .iter()
+                .map(|e| xPathElement {
+                    account: e.account.as_ref().map(|a| AccountAddress {
+                        address: a.to_owned(),
+                    }),
+                    currency: e.currency.as_ref().map(|c| Currency {
+                        name: c.to_owned(),
+                        code: zero_vector::<u8>(),
+                    }),
+                    issuer: e.issuer.as_ref().map(|a| AccountAddress {
+                        address: a.to_owned(),
+                    }),
+                })
+                .collect(),
+        })
+        .collect();
+    let flags = if payment.partial_payment {
+        Some(Flags {
+            value: PARTIAL_PAYMENT_FLAG,
+        })
+    } else {
+        None
+    };
     let transaction_data = TransactionData::Payment(Payment {
         destination: Some(Destination {
             value: Some(AccountAddress {
@@ -147,19 +747,31 @@ pub(crate) fn build_payment_transaction(
             }),
         }),
         amount: Some(Amount {
-            value: Some(CurrencyAmount {
-                amount: Some(currency_amount::Amount::XrpAmount(XrpDropsAmount {
-                    drops: payment.amount.drops,
-                })),
-            }),
+            value: Some(payment.amount.to_proto()),
+        }),
+        invoice_id: payment.invoice_id.map(|value| InvoiceId { value }),
+        send_max: payment.send_max.map(|a| SendMax {
+            value: Some(a.to_proto()),
         }),
-        invoice_id: None,
-        send_max: None,
-        deliver_min: None,
-        destination_tag: None, //TODO
-        paths: zero_vector::<xPath>(),
+        deliver_min: payment.deliver_min.map(|a| DeliverMin {
+            value: Some(a.to_proto()),
+        }),
+        destination_tag: payment
+            .destination_tag
+            .map(|value| DestinationTag { value }),
+        paths,
     });
 
+    let memos = payment
+        .memos
+        .into_iter()
+        .map(|m| Memo {
+            memo_type: m.memo_type.map(|value| MemoType { value }),
+            memo_format: m.memo_format.map(|value| MemoFormat { value }),
+            memo_data: m.memo_data.map(|value| MemoData { value }),
+        })
+        .collect();
+
     let transaction = Transaction {
         account: Some(Account {
             value: Some(AccountAddress {
@@ -171,17 +783,17 @@ pub(crate) fn build_payment_transaction(
             value: trx_sequence,
         }),
         signing_public_key: Some(SigningPublicKey {
-            value: source_wallet.public_key.clone().as_bytes().to_vec(),
+            value: signing_public_key.as_bytes().to_vec(),
         }),
         last_ledger_sequence: Some(LastLedgerSequence {
             value: last_ledger_sequence,
         }),
         transaction_data: Some(transaction_data),
         account_transaction_id: None,
-        flags: None,
-        memos: zero_vector::<Memo>(),
+        flags,
+        memos,
         signers: zero_vector::<Signer>(),
-        source_tag: None,
+        source_tag: payment.source_tag.map(|value| SourceTag { value }),
         transaction_signature: None,
     };
 
@@ -192,6 +804,7 @@ pub(crate) fn build_payment_transaction(
 mod tests {
 
     use super::*;
+    use crate::x::{DeliveredAmount, Meta};
     use fehler::throws;
 
     #[throws(_)]
@@ -204,6 +817,7 @@ mod tests {
             },
             last_ledger_sequence: 11239,
             validated: true,
+            delivered_amount: None,
         };
         assert_eq!(from_raw_status(raw_status), XTransactionStatus::SUCCEEDED);
     }
@@ -218,6 +832,7 @@ mod tests {
             },
             last_ledger_sequence: 11239,
             validated: true,
+            delivered_amount: None,
         };
         assert_eq!(from_raw_status(raw_status), XTransactionStatus::FAILED);
     }
@@ -232,6 +847,7 @@ mod tests {
             },
             last_ledger_sequence: 11239,
             validated: false,
+            delivered_amount: None,
         };
         assert_eq!(from_raw_status(raw_status), XTransactionStatus::PENDING);
     }
@@ -244,12 +860,12 @@ mod tests {
             "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4".to_owned(),
             false,
         );
-        let p = XPayment {
-            amount: XAmount::new(12.12),
-            from_address: "XVwDxLQ4SN9pEBQagTNHwqpFkPgGppXqrMoTmUcSKdCtcK5".to_owned(),
-            to_address: "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUFyQVMzRrMGUZpokKH".to_owned(),
-        };
-        let r = build_payment_transaction(p, 12, 10, 139019301, &w).unwrap();
+        let p = XPayment::new(
+            12.12,
+            "XVwDxLQ4SN9pEBQagTNHwqpFkPgGppXqrMoTmUcSKdCtcK5",
+            "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUFyQVMzRrMGUZpokKH",
+        );
+        let r = build_payment_transaction(p, 12, 10, 139019301, &w.public_key).unwrap();
         assert_eq!(
             r.account.unwrap().value.unwrap().address,
             "XVwDxLQ4SN9pEBQagTNHwqpFkPgGppXqrMoTmUcSKdCtcK5".to_owned()
@@ -271,15 +887,15 @@ mod tests {
             "009F56FC7B02354C428673EA14854616FED71888270C44911CBD87B84A5A59650F".to_owned(),
             false,
         );
-        let p = XPayment {
-            amount: XAmount::new(0.000010),
-            from_address: "T7jkn8zYC2NhPdcbVxkiEXZGy56YiEE4P7uXRgpy5j4Q6S1".to_owned(),
-            to_address: "T7QqSicoC1nB4YRyzWzctWW7KjwiYUo9ZAXPrwRoKJ7FudP".to_owned(),
-        };
+        let p = XPayment::new(
+            0.000010,
+            "T7jkn8zYC2NhPdcbVxkiEXZGy56YiEE4P7uXRgpy5j4Q6S1",
+            "T7QqSicoC1nB4YRyzWzctWW7KjwiYUo9ZAXPrwRoKJ7FudP",
+        );
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let t = build_payment_transaction(p, 10, 11, 5141976, &w).unwrap();
-        let signed_transaction = sign_transaction(&mut jscontext, &t, &w)?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let t = build_payment_transaction(p, 10, 11, 5141976, &w.public_key).unwrap();
+        let signed_transaction = sign_transaction(&jscontext, &t, &w)?;
         assert_eq!(signed_transaction.result, "120000240000000B201B004E75D861400000000000000A68400000000000000A73210314ACE51F9B116BCF3C1E38A9BD92706AF4334165870139144E947B27BB0103E87446304402201347C052098361A5F32155A42A2BB43ADF6A29B93B9512705E6B1960FF3016900220702A450E1F8A674BBEC32CF8926ED5E35F88A31AD9B5B7FA8940C667EC963AE881144594AF4CCC84B8E0AE58E6465F0BE056F0F70392831405EEB009A9DAE7DFBBB13523EA5CAB0B9B4B2E99".to_owned());
     }
 
@@ -291,15 +907,342 @@ mod tests {
             "009F56FC7B02354C428673EA14854616FED71888270C44911CBD87B84A5A59650F".to_owned(),
             false,
         );
-        let p = XPayment {
-            amount: XAmount::new(0.000010),
-            from_address: "T7jkn8zYC2NhPdcbVxkiEXZGy56YiEE4P7uXRgpy5j4Q6S1".to_owned(),
-            to_address: "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU".to_owned(),
-        };
+        let p = XPayment::new(
+            0.000010,
+            "T7jkn8zYC2NhPdcbVxkiEXZGy56YiEE4P7uXRgpy5j4Q6S1",
+            "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
+        );
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let t = build_payment_transaction(p, 10, 11, 5141976, &w).unwrap();
-        let signed_transaction = sign_transaction(&mut jscontext, &t, &w)?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let t = build_payment_transaction(p, 10, 11, 5141976, &w.public_key).unwrap();
+        let signed_transaction = sign_transaction(&jscontext, &t, &w)?;
         assert_eq!(signed_transaction.result, "120000240000000B2E0000000D201B004E75D861400000000000000A68400000000000000A73210314ACE51F9B116BCF3C1E38A9BD92706AF4334165870139144E947B27BB0103E87447304502210093C4587DA120D86CFA76E8D87DFA35CE587013AAC7680D334C0CEC11741D7E9A022068CB5EED93624D3114ACF7DAB6C47EFEA1474E9BD0FE49E0B0D4FCE5A233586F81144594AF4CCC84B8E0AE58E6465F0BE056F0F70392831405EEB009A9DAE7DFBBB13523EA5CAB0B9B4B2E99".to_owned());
     }
+
+    #[throws(_)]
+    #[test]
+    fn test_sign_transaction_explicit_destination_tag() {
+        let w = XWallet::new(
+            "0314ACE51F9B116BCF3C1E38A9BD92706AF4334165870139144E947B27BB0103E8".to_owned(),
+            "009F56FC7B02354C428673EA14854616FED71888270C44911CBD87B84A5A59650F".to_owned(),
+            false,
+        );
+        let mut p = XPayment::new(
+            0.000010,
+            "T7jkn8zYC2NhPdcbVxkiEXZGy56YiEE4P7uXRgpy5j4Q6S1",
+            "T7QqSicoC1nB4YRyzWzctWW7KjwiYUo9ZAXPrwRoKJ7FudP",
+        );
+        p.destination_tag(13);
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        resolve_destination(&jscontext, &mut p)?;
+        assert_eq!(p.to_address, "rY4KGXL6ZL1P68rGoHN4y6iWnjM7Xj2kc");
+        let t = build_payment_transaction(p, 10, 11, 5141976, &w.public_key).unwrap();
+        let signed_transaction = sign_transaction(&jscontext, &t, &w)?;
+        assert_eq!(signed_transaction.result, "120000240000000B2E0000000D201B004E75D861400000000000000A68400000000000000A73210314ACE51F9B116BCF3C1E38A9BD92706AF4334165870139144E947B27BB0103E87447304502210093C4587DA120D86CFA76E8D87DFA35CE587013AAC7680D334C0CEC11741D7E9A022068CB5EED93624D3114ACF7DAB6C47EFEA1474E9BD0FE49E0B0D4FCE5A233586F81144594AF4CCC84B8E0AE58E6465F0BE056F0F70392831405EEB009A9DAE7DFBBB13523EA5CAB0B9B4B2E99".to_owned());
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_resolve_destination_tag_conflict() {
+        let mut p = XPayment::new(
+            0.000010,
+            "T7jkn8zYC2NhPdcbVxkiEXZGy56YiEE4P7uXRgpy5j4Q6S1",
+            "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
+        );
+        p.destination_tag(14);
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let error = resolve_destination(&jscontext, &mut p).unwrap_err();
+        assert_eq!(
+            "Destination tag 14 conflicts with the X-Address tag 13",
+            error.to_string()
+        );
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_sign_transaction_source_tag_invoice_id_and_memos() {
+        let w = XWallet::new(
+            "0314ACE51F9B116BCF3C1E38A9BD92706AF4334165870139144E947B27BB0103E8".to_owned(),
+            "009F56FC7B02354C428673EA14854616FED71888270C44911CBD87B84A5A59650F".to_owned(),
+            false,
+        );
+        let mut p = XPayment::new(
+            0.000010,
+            "T7jkn8zYC2NhPdcbVxkiEXZGy56YiEE4P7uXRgpy5j4Q6S1",
+            "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
+        );
+        p.source_tag(42).invoice_id(vec![0xAB; 32]).memo(XMemo {
+            memo_type: Some(b"text/plain".to_vec()),
+            memo_format: None,
+            memo_data: Some(b"hello".to_vec()),
+        });
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let t = build_payment_transaction(p, 10, 11, 5141976, &w.public_key).unwrap();
+        assert_eq!(t.source_tag.as_ref().unwrap().value, 42);
+        assert_eq!(t.memos.len(), 1);
+        let signed_transaction = sign_transaction(&jscontext, &t, &w)?;
+        // SourceTag
+        assert!(signed_transaction.result.contains("230000002A"));
+        // DestinationTag taken from the X-Address
+        assert!(signed_transaction.result.contains("2E0000000D"));
+        // InvoiceID
+        assert!(signed_transaction
+            .result
+            .contains(&format!("5011{}", "AB".repeat(32))));
+        // Memos > Memo > MemoType, MemoData
+        assert!(signed_transaction
+            .result
+            .contains("F9EA7C0A746578742F706C61696E7D0568656C6C6FE1F1"));
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_build_payment_transaction_invalid_invoice_id() {
+        let w = XWallet::new(
+            "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE".to_owned(),
+            "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4".to_owned(),
+            false,
+        );
+        let mut p = XPayment::new(
+            12.12,
+            "XVwDxLQ4SN9pEBQagTNHwqpFkPgGppXqrMoTmUcSKdCtcK5",
+            "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUFyQVMzRrMGUZpokKH",
+        );
+        p.invoice_id(vec![0x01; 4]);
+        let error = build_payment_transaction(p, 12, 10, 139019301, &w.public_key).unwrap_err();
+        assert_eq!("Invoice ID must be 32 bytes long", error.to_string());
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_sign_cross_currency_partial_payment() {
+        let issuer = "rvYAfWj5gh67oV6fW32ZzP3Aw4Eubs59B";
+        let w = XWallet::new(
+            "0314ACE51F9B116BCF3C1E38A9BD92706AF4334165870139144E947B27BB0103E8".to_owned(),
+            "009F56FC7B02354C428673EA14854616FED71888270C44911CBD87B84A5A59650F".to_owned(),
+            false,
+        );
+        let mut p = XPayment::with_amount(
+            XCurrencyAmount::issued("EUR", issuer, "1.5"),
+            "T7jkn8zYC2NhPdcbVxkiEXZGy56YiEE4P7uXRgpy5j4Q6S1",
+            "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
+        );
+        p.send_max(XCurrencyAmount::xrp(2.0))
+            .deliver_min(XCurrencyAmount::issued("EUR", issuer, "1"))
+            .path(vec![XPathElement {
+                account: None,
+                currency: Some("EUR".to_owned()),
+                issuer: Some(issuer.to_owned()),
+            }])
+            .partial_payment(true);
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let t = build_payment_transaction(p, 10, 11, 5141976, &w.public_key).unwrap();
+        assert_eq!(t.flags.as_ref().unwrap().value, PARTIAL_PAYMENT_FLAG);
+        let signed_transaction = sign_transaction(&jscontext, &t, &w)?;
+        assert_eq!(signed_transaction.result, "1200002200020000240000000B2E0000000D201B004E75D861D485543DF729C00000000000000000000000000045555200000000000A20B3C85F482532A9578DBB3950B85CA06594D168400000000000000A6940000000001E84806AD4838D7EA4C6800000000000000000000000000045555200000000000A20B3C85F482532A9578DBB3950B85CA06594D173210314ACE51F9B116BCF3C1E38A9BD92706AF4334165870139144E947B27BB0103E874473045022100C7CDFC984796406E27634094357241487402785DD5A098B01E6F3B08F1162EFB022063BCFE29B6C2426EB984FD8E4D32CBF68A763B29B552D491FBF19A0CBA4E28E981144594AF4CCC84B8E0AE58E6465F0BE056F0F70392831405EEB009A9DAE7DFBBB13523EA5CAB0B9B4B2E9901123000000000000000000000000045555200000000000A20B3C85F482532A9578DBB3950B85CA06594D100".to_owned());
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_build_payment_transaction_deliver_min_without_partial_payment() {
+        let w = XWallet::new(
+            "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE".to_owned(),
+            "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4".to_owned(),
+            false,
+        );
+        let mut p = XPayment::with_amount(
+            XCurrencyAmount::issued("EUR", "rvYAfWj5gh67oV6fW32ZzP3Aw4Eubs59B", "1.5"),
+            "XVwDxLQ4SN9pEBQagTNHwqpFkPgGppXqrMoTmUcSKdCtcK5",
+            "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUFyQVMzRrMGUZpokKH",
+        );
+        p.deliver_min(XCurrencyAmount::issued(
+            "EUR",
+            "rvYAfWj5gh67oV6fW32ZzP3Aw4Eubs59B",
+            "1",
+        ));
+        let error = build_payment_transaction(p, 12, 10, 139019301, &w.public_key).unwrap_err();
+        assert_eq!(
+            "deliver_min can only be used in partial payments",
+            error.to_string()
+        );
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_build_payment_transaction_xrp_to_xrp_send_max() {
+        let w = XWallet::new(
+            "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE".to_owned(),
+            "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4".to_owned(),
+            false,
+        );
+        let mut p = XPayment::new(
+            12.12,
+            "XVwDxLQ4SN9pEBQagTNHwqpFkPgGppXqrMoTmUcSKdCtcK5",
+            "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUFyQVMzRrMGUZpokKH",
+        );
+        p.send_max(XCurrencyAmount::xrp(13.0));
+        let error = build_payment_transaction(p, 12, 10, 139019301, &w.public_key).unwrap_err();
+        assert_eq!(
+            "send_max cannot be used in XRP to XRP payments",
+            error.to_string()
+        );
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_currency_amount_proto_round_trip() {
+        let issued = XCurrencyAmount::issued("EUR", "rvYAfWj5gh67oV6fW32ZzP3Aw4Eubs59B", "1.5");
+        assert_eq!(
+            XCurrencyAmount::from_proto(&issued.to_proto()),
+            Some(issued)
+        );
+        let xrp = XCurrencyAmount::xrp(12.5);
+        assert_eq!(XCurrencyAmount::from_proto(&xrp.to_proto()), Some(xrp));
+        assert_eq!(
+            XCurrencyAmount::from_proto(&CurrencyAmount { amount: None }),
+            None
+        );
+    }
+
+    const RECEIVER: &str = "rPEPPER7kfTD9w2To4CQk6UCfuHM9c6GDY";
+    const SENDER: &str = "rvYAfWj5gh67oV6fW32ZzP3Aw4Eubs59B";
+
+    fn payment_response(
+        flags: u32,
+        delivered_amount: Option<XCurrencyAmount>,
+        result: &str,
+    ) -> GetTransactionResponse {
+        let address = |a: &str| {
+            Some(AccountAddress {
+                address: a.to_owned(),
+            })
+        };
+        GetTransactionResponse {
+            serialized_transaction: Some(
+                get_transaction_response::SerializedTransaction::Transaction(Transaction {
+                    account: Some(Account {
+                        value: address(SENDER),
+                    }),
+                    flags: Some(Flags { value: flags }),
+                    transaction_data: Some(TransactionData::Payment(Payment {
+                        amount: Some(Amount {
+                            value: Some(XCurrencyAmount::xrp(100.0).to_proto()),
+                        }),
+                        destination: Some(Destination {
+                            value: address(RECEIVER),
+                        }),
+                        destination_tag: Some(DestinationTag { value: 12345 }),
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                }),
+            ),
+            ledger_index: 42,
+            hash: vec![0xAB; 32],
+            validated: true,
+            serialized_meta: Some(get_transaction_response::SerializedMeta::Meta(Meta {
+                transaction_result: Some(TransactionResult {
+                    result_type: 6,
+                    result: result.to_owned(),
+                }),
+                delivered_amount: delivered_amount.map(|d| DeliveredAmount {
+                    value: Some(d.to_proto()),
+                }),
+                ..Default::default()
+            })),
+            date: None,
+        }
+    }
+
+    #[test]
+    fn test_incoming_payment_partial_payment_uses_delivered_amount() {
+        let response = payment_response(
+            PARTIAL_PAYMENT_FLAG,
+            Some(XCurrencyAmount::xrp(0.000001)),
+            "tesSUCCESS",
+        );
+        let payment = incoming_payment(RECEIVER, &response).unwrap();
+        assert!(payment.partial_payment);
+        assert_eq!(payment.amount, XCurrencyAmount::xrp(100.0));
+        assert_eq!(
+            payment.delivered_amount,
+            Some(XCurrencyAmount::xrp(0.000001))
+        );
+        assert_eq!(payment.from_address, SENDER);
+        assert_eq!(payment.destination_tag, Some(12345));
+        assert_eq!(payment.ledger_index, 42);
+        assert_eq!(payment.transaction_hash, "AB".repeat(32));
+    }
+
+    #[test]
+    fn test_incoming_payment_partial_payment_without_delivered_amount() {
+        let response = payment_response(PARTIAL_PAYMENT_FLAG, None, "tesSUCCESS");
+        let payment = incoming_payment(RECEIVER, &response).unwrap();
+        assert_eq!(payment.delivered_amount, None);
+    }
+
+    #[test]
+    fn test_incoming_payment_full_payment_defaults_to_amount() {
+        let response = payment_response(0, None, "tesSUCCESS");
+        let payment = incoming_payment(RECEIVER, &response).unwrap();
+        assert!(!payment.partial_payment);
+        assert_eq!(payment.delivered_amount, Some(XCurrencyAmount::xrp(100.0)));
+    }
+
+    #[test]
+    fn test_incoming_payment_ignores_failed_outgoing_and_unvalidated() {
+        let failed = payment_response(0, None, "tecPATH_PARTIAL");
+        assert_eq!(incoming_payment(RECEIVER, &failed), None);
+        let outgoing = payment_response(0, None, "tesSUCCESS");
+        assert_eq!(incoming_payment(SENDER, &outgoing), None);
+        let mut unvalidated = payment_response(0, None, "tesSUCCESS");
+        unvalidated.validated = false;
+        assert_eq!(incoming_payment(RECEIVER, &unvalidated), None);
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_offline_json() {
+        let mut payment = XPayment::new(
+            1.0,
+            "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+            "rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7",
+        );
+        payment.memo(XMemo::new("hi")).invoice_id(vec![0xAB; 32]);
+        let unsigned = XUnsignedTransaction {
+            payment,
+            fee: 12,
+            sequence: 7,
+            last_ledger_sequence: 1000,
+            signing_public_key: None,
+            test: true,
+        };
+        let json = unsigned.to_json()?;
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], OFFLINE_FORMAT_VERSION);
+        assert_eq!(value["stage"], "unsigned");
+        assert_eq!(value["payment"]["memos"][0]["memo_data"], "6869");
+        assert_eq!(value["payment"]["invoice_id"], "AB".repeat(32));
+        assert_eq!(XUnsignedTransaction::from_json(&json)?, unsigned);
+
+        let signed = XSignedTransaction {
+            transaction_blob: "1200".to_owned(),
+            transaction_hash: "AB".repeat(32),
+            last_ledger_sequence: 1000,
+            test: true,
+        };
+        assert_eq!(XSignedTransaction::from_json(&signed.to_json()?)?, signed);
+
+        assert!(matches!(
+            XSignedTransaction::from_json(&json),
+            Err(Error::Decode(ref m)) if m == "Expected the signed stage, found the unsigned stage"
+        ));
+        let future = json.replace("\"version\": 1", "\"version\": 2");
+        assert!(XUnsignedTransaction::from_json(&future).is_err());
+        assert!(XUnsignedTransaction::from_json("{}").is_err());
+    }
 }
@@ -1,39 +1,39 @@
 use crate::address;
 use crate::address::XClassicAddress;
+use crate::error::XpringError as Error;
 use crate::javascript::JavaScript;
-use anyhow::Error;
 use fehler::throws;
 use std::str;
 
 // Address
 
 #[throws(_)]
-pub fn is_valid_address(jscontext: &mut JavaScript, address: &str) -> bool {
+pub fn is_valid_address(jscontext: &JavaScript, address: &str) -> bool {
     address::is_valid_address(jscontext, address)?
 }
 
 #[throws(_)]
 pub fn encode_classic_address(
-    jscontext: &mut JavaScript,
+    jscontext: &JavaScript,
     classic_address: &str,
-    tag: Option<u16>,
+    tag: Option<u32>,
     test: Option<bool>,
 ) -> String {
     address::encode_classic_address(jscontext, classic_address, tag, test)?
 }
 
 #[throws(_)]
-pub fn decode_x_address(jscontext: &mut JavaScript, x_address: &str) -> XClassicAddress {
+pub fn decode_x_address(jscontext: &JavaScript, x_address: &str) -> XClassicAddress {
     address::decode_x_address(jscontext, x_address)?
 }
 
 #[throws(_)]
-pub fn is_valid_x_address(jscontext: &mut JavaScript, x_address: &str) -> bool {
+pub fn is_valid_x_address(jscontext: &JavaScript, x_address: &str) -> bool {
     address::is_valid_x_address(jscontext, x_address)?
 }
 
 #[throws(_)]
-pub fn is_valid_classic_address(jscontext: &mut JavaScript, address: &str) -> bool {
+pub fn is_valid_classic_address(jscontext: &JavaScript, address: &str) -> bool {
     address::is_valid_classic_address(jscontext, address)?
 }
 
@@ -47,9 +47,9 @@ mod tests {
     #[test]
     fn test_valid_classic_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_address(&mut jscontext, "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1").unwrap(),
+            is_valid_address(&jscontext, "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1").unwrap(),
             true
         );
     }
@@ -58,10 +58,10 @@ mod tests {
     #[test]
     fn test_valid_x_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             is_valid_address(
-                &mut jscontext,
+                &jscontext,
                 "XVLhHMPHU98es4dbozjVtdWzVrDjtV18pX8yuPT7y4xaEHi"
             )
             .unwrap(),
@@ -73,9 +73,9 @@ mod tests {
     #[test]
     fn test_address_wrong_alphabet() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_address(&mut jscontext, "1EAG1MwmzkG6gRZcYqcRMfC17eMt8TDTit").unwrap(),
+            is_valid_address(&jscontext, "1EAG1MwmzkG6gRZcYqcRMfC17eMt8TDTit").unwrap(),
             false
         );
     }
@@ -84,9 +84,9 @@ mod tests {
     #[test]
     fn test_classic_address_wrong_checksum() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_address(&mut jscontext, "rU6K7V3Po4sBBBBBaU29sesqs2qTQJWDw1").unwrap(),
+            is_valid_address(&jscontext, "rU6K7V3Po4sBBBBBaU29sesqs2qTQJWDw1").unwrap(),
             false
         );
     }
@@ -95,10 +95,10 @@ mod tests {
     #[test]
     fn test_x_address_wrong_checksum() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             is_valid_address(
-                &mut jscontext,
+                &jscontext,
                 "XVLhHMPHU98es4dbozjVtdWzVrDjtV18pX8yuPT7y4xaEHI"
             )
             .unwrap(),
@@ -110,9 +110,9 @@ mod tests {
     #[test]
     fn test_address_invalid_character() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_address(&mut jscontext, "rU6K7V3Po4sBBBBBaU@#$%qs2qTQJWDw1").unwrap(),
+            is_valid_address(&jscontext, "rU6K7V3Po4sBBBBBaU@#$%qs2qTQJWDw1").unwrap(),
             false
         );
     }
@@ -121,10 +121,10 @@ mod tests {
     #[test]
     fn test_address_invalid_too_long() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             is_valid_address(
-                &mut jscontext,
+                &jscontext,
                 "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1"
             )
             .unwrap(),
@@ -136,9 +136,9 @@ mod tests {
     #[test]
     fn test_address_invalid_too_short() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_address(&mut jscontext, "rU6K7V3Po4s2qTQJWDw1").unwrap(),
+            is_valid_address(&jscontext, "rU6K7V3Po4s2qTQJWDw1").unwrap(),
             false
         );
     }
@@ -147,10 +147,10 @@ mod tests {
     #[test]
     fn test_encode_x_address_mainnet_and_tag() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             encode_classic_address(
-                &mut jscontext,
+                &jscontext,
                 "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1",
                 Some(12345),
                 None
@@ -164,10 +164,10 @@ mod tests {
     #[test]
     fn test_encode_x_address_testnet_and_tag() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             encode_classic_address(
-                &mut jscontext,
+                &jscontext,
                 "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1",
                 Some(12345),
                 None
@@ -181,10 +181,10 @@ mod tests {
     #[test]
     fn test_encode_x_address_only() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             encode_classic_address(
-                &mut jscontext,
+                &jscontext,
                 "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1",
                 None,
                 Some(false)
@@ -198,20 +198,16 @@ mod tests {
     #[test]
     fn test_encode_x_address_invalid_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let x_address =
-            encode_classic_address(&mut jscontext, "xrp", None, Some(false)).unwrap_err();
-        assert_eq!(
-            "Invalid Parameters",
-            x_address.downcast_ref::<String>().unwrap()
-        );
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let x_address = encode_classic_address(&jscontext, "xrp", None, Some(false)).unwrap_err();
+        assert!(matches!(x_address, Error::Validation(ref m) if m == "Invalid Parameters"));
     }
 
     #[throws(_)]
     #[test]
     fn test_decode_x_address_valid_mainnet_with_tag() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let expected = XClassicAddress {
             address: "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1".to_owned(),
             tag: Some(12345),
@@ -219,7 +215,7 @@ mod tests {
         };
         assert_eq!(
             decode_x_address(
-                &mut jscontext,
+                &jscontext,
                 "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUvtU3HnooQDgBnUpQT"
             )
             .unwrap(),
@@ -231,7 +227,7 @@ mod tests {
     #[test]
     fn test_decode_x_address_valid_testnet_with_tag() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let expected = XClassicAddress {
             address: "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1".to_owned(),
             tag: Some(12345),
@@ -239,7 +235,7 @@ mod tests {
         };
         assert_eq!(
             decode_x_address(
-                &mut jscontext,
+                &jscontext,
                 "TVsBZmcewpEHgajPi1jApLeYnHPJw82v9JNYf7dkGmWphmh"
             )
             .unwrap(),
@@ -251,7 +247,7 @@ mod tests {
     #[test]
     fn test_decode_x_address_valid_testnet_without_tag() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let expected = XClassicAddress {
             address: "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1".to_owned(),
             tag: None,
@@ -259,7 +255,7 @@ mod tests {
         };
         assert_eq!(
             decode_x_address(
-                &mut jscontext,
+                &jscontext,
                 "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUFyQVMzRrMGUZpokKH"
             )
             .unwrap(),
@@ -271,21 +267,18 @@ mod tests {
     #[test]
     fn test_decode_x_address_invalid_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let x_address = decode_x_address(&mut jscontext, "xrp").unwrap_err();
-        assert_eq!(
-            "Invalid Address",
-            x_address.downcast_ref::<String>().unwrap()
-        );
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let x_address = decode_x_address(&jscontext, "xrp").unwrap_err();
+        assert!(matches!(x_address, Error::Validation(ref m) if m == "Invalid Address"));
     }
 
     #[throws(_)]
     #[test]
     fn test_is_valid_x_address_with_classic_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_x_address(&mut jscontext, "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1").unwrap(),
+            is_valid_x_address(&jscontext, "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1").unwrap(),
             false
         );
     }
@@ -294,18 +287,18 @@ mod tests {
     #[test]
     fn test_is_valid_x_address_with_invalid_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        assert_eq!(is_valid_x_address(&mut jscontext, "xrp").unwrap(), false);
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        assert_eq!(is_valid_x_address(&jscontext, "xrp").unwrap(), false);
     }
 
     #[throws(_)]
     #[test]
     fn test_is_valid_x_address_with_valid_x_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
             is_valid_x_address(
-                &mut jscontext,
+                &jscontext,
                 "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUvtU3HnooQDgBnUpQT"
             )
             .unwrap(),
@@ -317,9 +310,9 @@ mod tests {
     #[test]
     fn test_is_valid_classic_address_with_valid_classic_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         assert_eq!(
-            is_valid_classic_address(&mut jscontext, "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1").unwrap(),
+            is_valid_classic_address(&jscontext, "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1").unwrap(),
             true
         );
     }
@@ -328,10 +321,7 @@ mod tests {
     #[test]
     fn test_is_valid_classic_address_with_invalid_classic_address() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        assert_eq!(
-            is_valid_classic_address(&mut jscontext, "xrp").unwrap(),
-            false
-        );
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        assert_eq!(is_valid_classic_address(&jscontext, "xrp").unwrap(), false);
     }
 }
@@ -0,0 +1,363 @@
+//! Vanity addresses: random wallets generated until their address has a chosen prefix or matches
+//! a regular expression.
+//!
+//! The search runs in batches on every worker of the xpring.js bridge at once. `Xrpl` gives it a
+//! bridge with a worker per CPU, `XrplPool` its own workers. Only the seed of the matching wallet
+//! leaves the bridge.
+use crate::error::XpringError as Error;
+use crate::javascript::{JavaScript, JsCall};
+use crate::secret::Seed;
+use crate::wallet::{self, KeyAlgorithm, XWallet};
+use fehler::{throw, throws};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// The base58 alphabet of XRP Ledger addresses, in digit order.
+pub const ADDRESS_ALPHABET: &str = "rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz";
+
+/// Seeds tried by a worker in one call.
+const BATCH_ATTEMPTS: u64 = 200;
+
+/// Characters of a prefix whose distribution is computed exactly, the following ones are
+/// taken as uniform.
+const EXACT_PREFIX_CHARACTERS: usize = 6;
+
+/// What the address of a vanity wallet looks like.
+#[derive(PartialEq, Debug, Clone)]
+pub enum XVanityPattern {
+    /// The classic address starts with this prefix, `r` included.
+    ClassicPrefix(String),
+    /// The X-Address (without a tag) starts with this prefix, `X` or `T` included.
+    XAddressPrefix(String),
+    /// The classic address matches this JavaScript regular expression.
+    ClassicRegex(String),
+    /// The X-Address (without a tag) matches this JavaScript regular expression.
+    XAddressRegex(String),
+}
+
+impl XVanityPattern {
+    /// Average number of wallets to generate before one matches, for a prefix on the network
+    /// selected by `test`. It is infinite for prefixes no address has, and unknown (`None`)
+    /// for regular expressions.
+    ///
+    /// Addresses are base58 numbers from a fixed range, so their first characters are far from
+    /// uniform: after the `r` of a classic address, `p` comes up once in 23 addresses but `X`
+    /// once in 1330. The estimate accounts for this on the first characters.
+    pub fn expected_attempts(&self, test: bool) -> Option<f64> {
+        let (prefix, low, width, zero_bytes) = match self {
+            XVanityPattern::ClassicPrefix(prefix) => {
+                // An `r` for the zero type byte and one for each leading zero byte of the
+                // account ID, then the rest of the 24 bytes of account ID and checksum.
+                let prefix = match prefix.strip_prefix('r') {
+                    Some(prefix) => prefix,
+                    None => return Some(f64::INFINITY),
+                };
+                let digits = prefix.trim_start_matches('r');
+                let zero_bytes = (prefix.len() - digits.len()) as i32;
+                (digits, 0., 2f64.powi(192 - 8 * zero_bytes), zero_bytes)
+            }
+            XVanityPattern::XAddressPrefix(prefix) => {
+                // Two type bytes, then 33 bytes of account ID, tag and checksum.
+                let type_bytes: f64 = if test { 1171. } else { 1348. };
+                (
+                    prefix.as_str(),
+                    type_bytes * 2f64.powi(264),
+                    2f64.powi(264),
+                    0,
+                )
+            }
+            _ => return None,
+        };
+        let digits = match prefix
+            .chars()
+            .map(|c| ADDRESS_ALPHABET.find(c))
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(digits) => digits,
+            None => return Some(f64::INFINITY),
+        };
+        let exact = digits.len().min(EXACT_PREFIX_CHARACTERS);
+        let share = share_with_prefix(&digits[..exact], low, width)
+            / 58f64.powi((digits.len() - exact) as i32)
+            / 256f64.powi(zero_bytes);
+        Some(1. / share)
+    }
+}
+
+/// Share of the numbers in `low..low + width` whose base58 digits start with `digits`.
+fn share_with_prefix(digits: &[usize], low: f64, width: f64) -> f64 {
+    let value = digits.iter().fold(0., |value, d| value * 58. + *d as f64);
+    let mut share = 0.;
+    for length in digits.len().max(1)..=64 {
+        let unit = 58f64.powi((length - digits.len()) as i32);
+        let smallest = if length == 1 {
+            0.
+        } else {
+            58f64.powi(length as i32 - 1)
+        };
+        let start = (value * unit).max(smallest).max(low);
+        let end = ((value + 1.) * unit)
+            .min(58f64.powi(length as i32))
+            .min(low + width);
+        if end > start {
+            share += (end - start) / width;
+        }
+    }
+    share
+}
+
+/// A wallet found by `search`.
+#[derive(PartialEq, Debug)]
+pub struct XVanityWallet {
+    pub wallet: XWallet,
+    pub seed: Seed,
+    pub classic_address: String,
+    /// Number of wallets generated by the search.
+    pub attempts: u64,
+}
+
+#[derive(Serialize)]
+struct XVanityOptions<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prefix: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    regex: Option<&'a str>,
+    x_address: bool,
+    algorithm: KeyAlgorithm,
+    test: bool,
+    attempts: u64,
+}
+
+#[derive(Deserialize)]
+struct XVanityBatch {
+    seed: Option<Seed>,
+    attempts: u64,
+}
+
+#[throws(_)]
+fn search_batch(jscontext: &JavaScript, options: XVanityOptions) -> XVanityBatch {
+    let result = js!(jscontext.wallet.vanitySearch::<XVanityBatch>(options))?;
+    result
+}
+
+/// Generates wallets with random seeds on every worker of `jscontext` until one matches
+/// `pattern`, or until `max_attempts` wallets were generated. Regular expressions need
+/// `max_attempts`: unlike a prefix, nothing tells whether any address matches them.
+#[throws(_)]
+pub(crate) fn search(
+    jscontext: &JavaScript,
+    pattern: &XVanityPattern,
+    algorithm: KeyAlgorithm,
+    max_attempts: Option<u64>,
+    test: bool,
+) -> XVanityWallet {
+    if pattern.expected_attempts(test) == Some(f64::INFINITY) {
+        throw!(Error::Validation(format!(
+            "No {} address matches {:?}",
+            if test { "test" } else { "main" },
+            pattern
+        )));
+    }
+    if max_attempts.is_none() && pattern.expected_attempts(test).is_none() {
+        throw!(Error::Validation(format!(
+            "Searching for {:?} needs a maximum number of attempts",
+            pattern
+        )));
+    }
+    let (prefix, regex, x_address) = match pattern {
+        XVanityPattern::ClassicPrefix(prefix) => (Some(prefix.as_str()), None, false),
+        XVanityPattern::XAddressPrefix(prefix) => (Some(prefix.as_str()), None, true),
+        XVanityPattern::ClassicRegex(regex) => (None, Some(regex.as_str()), false),
+        XVanityPattern::XAddressRegex(regex) => (None, Some(regex.as_str()), true),
+    };
+    let max_attempts = max_attempts.unwrap_or(u64::MAX);
+    let reserved = AtomicU64::new(0);
+    let attempts = AtomicU64::new(0);
+    let done = AtomicBool::new(false);
+    let outcome: Mutex<Option<Result<Seed, Error>>> = Mutex::new(None);
+
+    thread::scope(|scope| {
+        for _ in 0..jscontext.workers() {
+            scope.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    let start = reserved.fetch_add(BATCH_ATTEMPTS, Ordering::Relaxed);
+                    if start >= max_attempts {
+                        break;
+                    }
+                    let options = XVanityOptions {
+                        prefix,
+                        regex,
+                        x_address,
+                        algorithm,
+                        test,
+                        attempts: BATCH_ATTEMPTS.min(max_attempts - start),
+                    };
+                    let found = match search_batch(jscontext, options) {
+                        Ok(batch) => {
+                            attempts.fetch_add(batch.attempts, Ordering::Relaxed);
+                            match batch.seed {
+                                Some(seed) => Ok(seed),
+                                None => continue,
+                            }
+                        }
+                        Err(error) => Err(error),
+                    };
+                    let mut outcome = outcome.lock().expect("vanity search poisoned");
+                    if outcome.is_none() {
+                        *outcome = Some(found);
+                    }
+                    done.store(true, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+
+    let attempts = attempts.into_inner();
+    let seed = match outcome.into_inner().expect("vanity search poisoned") {
+        Some(found) => found?,
+        None => throw!(Error::Validation(format!(
+            "No address matched {:?} in {} attempts",
+            pattern, attempts
+        ))),
+    };
+    let wallet = wallet::from_seed(jscontext, seed.expose_secret().to_owned(), None, test)?;
+    let classic_address = match &wallet.address {
+        Some(address) => crate::address::decode_x_address(jscontext, address)?.address,
+        None => throw!(Error::Decode("The wallet has no address".to_owned())),
+    };
+    XVanityWallet {
+        wallet,
+        seed,
+        classic_address,
+        attempts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_expected_attempts() {
+        let expected = |pattern: XVanityPattern, test| pattern.expected_attempts(test).unwrap();
+        assert_eq!(
+            XVanityPattern::ClassicRegex("^rXRP".to_owned()).expected_attempts(false),
+            None
+        );
+        assert_eq!(
+            expected(XVanityPattern::ClassicPrefix("r".to_owned()), false),
+            1.
+        );
+        // `p` is digit 1: the first digit of 33 digit account IDs (up to 2^192 / 58^32 ≈ 23.4),
+        // or of shorter ones. Counted exactly, one address in 22.9356 starts with `rp`.
+        let rp = expected(XVanityPattern::ClassicPrefix("rp".to_owned()), false);
+        assert!((rp - 22.9356).abs() < 1e-4, "{}", rp);
+        let rpx = expected(XVanityPattern::ClassicPrefix("rpx".to_owned()), false);
+        assert!((rpx / rp - 58.).abs() < 0.01);
+        let long = expected(
+            XVanityPattern::ClassicPrefix("rpshnaf39wB".to_owned()),
+            false,
+        );
+        assert!((long / rpx / 58f64.powi(8) - 1.).abs() < 0.01);
+        // Only addresses shorter than most, from account IDs below 58^32, can have an `X`
+        // (digit 30) there.
+        let rx = expected(XVanityPattern::ClassicPrefix("rX".to_owned()), false);
+        assert!((rx - 1330.27).abs() < 0.01, "{}", rx);
+        let rr = expected(XVanityPattern::ClassicPrefix("rr".to_owned()), false);
+        assert!((rr - 256.).abs() < 1e-9, "{}", rr);
+        assert!(expected(XVanityPattern::ClassicPrefix("x".to_owned()), false).is_infinite());
+        assert!(expected(XVanityPattern::ClassicPrefix("r0".to_owned()), false).is_infinite());
+
+        assert_eq!(
+            expected(XVanityPattern::XAddressPrefix("X".to_owned()), false),
+            1.
+        );
+        assert_eq!(
+            expected(XVanityPattern::XAddressPrefix("T".to_owned()), true),
+            1.
+        );
+        assert!(expected(XVanityPattern::XAddressPrefix("T".to_owned()), false).is_infinite());
+        let x7 = expected(XVanityPattern::XAddressPrefix("X7".to_owned()), false);
+        let xv = expected(XVanityPattern::XAddressPrefix("XV".to_owned()), false);
+        assert!((1. / x7 + 1. / xv - 1.).abs() < 1e-9, "{} {}", x7, xv);
+    }
+
+    #[fehler::throws(_)]
+    #[test]
+    fn test_search() {
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let pattern = XVanityPattern::ClassicPrefix("rp".to_owned());
+        let found = search(&jscontext, &pattern, KeyAlgorithm::Secp256k1, None, true)?;
+        assert!(found.classic_address.starts_with("rp"));
+        assert!(found.attempts >= 1);
+        let wallet = wallet::from_seed(
+            &jscontext,
+            found.seed.expose_secret().to_owned(),
+            None,
+            true,
+        )?;
+        assert_eq!(wallet, found.wallet);
+
+        let pattern = XVanityPattern::XAddressRegex("[a-z]$".to_owned());
+        let error = search(&jscontext, &pattern, KeyAlgorithm::Ed25519, None, true).unwrap_err();
+        assert!(
+            matches!(error, Error::Validation(ref m) if m.ends_with("maximum number of attempts"))
+        );
+        let found = search(
+            &jscontext,
+            &pattern,
+            KeyAlgorithm::Ed25519,
+            Some(10_000),
+            true,
+        )?;
+        let address = found.wallet.address.unwrap();
+        assert!(address.starts_with('T'));
+        assert!(address.ends_with(|c: char| c.is_ascii_lowercase()));
+        assert!(found.wallet.public_key.starts_with("ED"));
+
+        let pattern = XVanityPattern::ClassicPrefix("rpshnaf39wBUDNEGHJ".to_owned());
+        let error = search(
+            &jscontext,
+            &pattern,
+            KeyAlgorithm::Secp256k1,
+            Some(10),
+            true,
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::Validation(ref m) if m.ends_with("in 10 attempts")));
+        let pattern = XVanityPattern::ClassicPrefix("rl".to_owned());
+        assert!(search(&jscontext, &pattern, KeyAlgorithm::Secp256k1, None, true).is_err());
+        let pattern = XVanityPattern::ClassicRegex("(".to_owned());
+        let error = search(
+            &jscontext,
+            &pattern,
+            KeyAlgorithm::Secp256k1,
+            Some(10),
+            true,
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::Validation(ref m) if m == "Invalid Regular Expression"));
+    }
+
+    #[cfg(feature = "embedded-js")]
+    #[fehler::throws(_)]
+    #[test]
+    fn test_embedded_search() {
+        use crate::javascript::JsBridgeOptions;
+        let jscontext = JavaScript::embedded(2, JsBridgeOptions::default())?;
+        let pattern = XVanityPattern::ClassicPrefix("rp".to_owned());
+        let found = search(&jscontext, &pattern, KeyAlgorithm::Secp256k1, None, false)?;
+        assert!(found.classic_address.starts_with("rp"));
+        let wallet = wallet::from_seed(
+            &jscontext,
+            found.seed.expose_secret().to_owned(),
+            None,
+            false,
+        )?;
+        assert_eq!(wallet, found.wallet);
+    }
+}
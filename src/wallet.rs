@@ -1,17 +1,27 @@
+use crate::address;
+use crate::error::XpringError as Error;
 use crate::javascript::{JavaScript, JsCall};
-use anyhow::Error;
-use fehler::throws;
+use crate::secret::{self, Mnemonic, PrivateKey, Seed};
+use fehler::{throw, throws};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use zeroize::Zeroizing;
 
 #[derive(Debug, Serialize)]
 struct XGenerateWalletOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     entropy: Option<String>,
     test: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    seed: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    mnemonic: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "secret::expose_option"
+    )]
+    seed: Option<Seed>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "secret::expose_option"
+    )]
+    mnemonic: Option<Mnemonic>,
     #[serde(skip_serializing_if = "Option::is_none")]
     derivation_path: Option<String>,
 }
@@ -27,7 +37,7 @@ impl<'a> XGenerateWalletOptions {
         }
     }
 
-    fn seed(&'a mut self, seed: String) -> &'a mut XGenerateWalletOptions {
+    fn seed(&'a mut self, seed: Seed) -> &'a mut XGenerateWalletOptions {
         self.seed = Some(seed);
         self
     }
@@ -37,7 +47,7 @@ impl<'a> XGenerateWalletOptions {
         self
     }
 
-    fn mnemonic(&'a mut self, mnemonic: String) -> &'a mut XGenerateWalletOptions {
+    fn mnemonic(&'a mut self, mnemonic: Mnemonic) -> &'a mut XGenerateWalletOptions {
         self.mnemonic = Some(mnemonic);
         self
     }
@@ -48,32 +58,69 @@ impl<'a> XGenerateWalletOptions {
     }
 }
 
+/// A wallet. Its private key is redacted in `Debug` output and left out when it is serialized.
 #[derive(PartialEq, Debug, Serialize, Deserialize)]
 pub struct XWallet {
     #[serde(rename(deserialize = "publicKey"))]
     pub public_key: String,
-    #[serde(rename(deserialize = "privateKey"))]
-    pub private_key: String,
+    #[serde(rename(deserialize = "privateKey"), skip_serializing)]
+    pub private_key: PrivateKey,
     pub test: bool,
     pub address: Option<String>,
 }
 
+/// The public part of a wallet. It follows an account and prepares its transactions, which
+/// are signed elsewhere with the full `XWallet`.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct XWatchOnlyWallet {
+    /// X-Address of the account, without a tag.
+    pub address: String,
+    pub classic_address: String,
+    /// Public key of the wallet, when it was built from one.
+    pub public_key: Option<String>,
+    pub test: bool,
+}
+
+#[derive(Serialize)]
+struct XPublicKeyOptions {
+    public_key: String,
+    test: bool,
+}
+
+/// The wallet as xpring.js expects it for signing, with the private key.
+#[derive(Serialize)]
+pub(crate) struct XSigningWallet<'a> {
+    public_key: &'a str,
+    #[serde(serialize_with = "secret::expose")]
+    private_key: &'a PrivateKey,
+    test: bool,
+}
+
 impl XWallet {
     #[allow(dead_code)]
     pub(crate) fn new(public_key: String, private_key: String, test: bool) -> XWallet {
         XWallet {
             public_key,
-            private_key,
+            private_key: PrivateKey::new(private_key),
             test,
             address: None,
         }
     }
+
+    pub(crate) fn signing(&self) -> XSigningWallet<'_> {
+        XSigningWallet {
+            public_key: &self.public_key,
+            private_key: &self.private_key,
+            test: self.test,
+        }
+    }
 }
 
-#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Serialize)]
 struct XSignOptions {
     message: String,
-    private_key: String,
+    #[serde(serialize_with = "secret::expose")]
+    private_key: PrivateKey,
 }
 
 #[derive(PartialEq, Debug, Serialize, Deserialize)]
@@ -93,17 +140,91 @@ impl XVerifyOptions {
     }
 }
 
+/// Signing algorithm of a key pair, encoded in its family seed.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyAlgorithm {
+    #[default]
+    Secp256k1,
+    Ed25519,
+}
+
+#[derive(Serialize)]
+struct XSeedOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entropy: Option<Zeroizing<String>>,
+    algorithm: KeyAlgorithm,
+}
+
+#[derive(Serialize)]
+struct XDecodeSeedOptions<'a> {
+    #[serde(serialize_with = "secret::expose")]
+    seed: &'a Seed,
+}
+
+#[derive(Deserialize)]
+struct XDecodedSeed {
+    entropy: Zeroizing<String>,
+    algorithm: KeyAlgorithm,
+}
+
+/// The 16 bytes of entropy a family seed encodes, and the algorithm of its key pair.
+#[derive(PartialEq, Debug)]
+pub struct XSeedEntropy {
+    pub entropy: Zeroizing<[u8; 16]>,
+    pub algorithm: KeyAlgorithm,
+}
+
+#[derive(Serialize)]
+struct XPrivateKeyOptions<'a> {
+    #[serde(serialize_with = "secret::expose")]
+    private_key: &'a PrivateKey,
+    test: bool,
+}
+
+/// A random wallet derived from a family seed.
+#[derive(PartialEq, Debug)]
+pub struct XSeedWalletGenerationResult {
+    pub wallet: XWallet,
+    pub seed: Seed,
+}
+
+/// Highest account or address index of a BIP44 path, as paths only use the non-hardened range.
+pub const MAX_DERIVATION_INDEX: u32 = 0x7FFF_FFFF;
+
+#[derive(Serialize)]
+struct XDeriveOptions<'a> {
+    #[serde(serialize_with = "secret::expose")]
+    mnemonic: &'a Mnemonic,
+    account: u32,
+    start: u32,
+    count: u32,
+    test: bool,
+}
+
+/// A wallet derived from a mnemonic at `m/44'/144'/account'/0/index`.
+#[derive(PartialEq, Debug, Deserialize)]
+pub struct XDerivedWallet {
+    pub wallet: XWallet,
+    pub classic_address: String,
+    pub account: u32,
+    pub index: u32,
+    pub derivation_path: String,
+}
+
+type XDerivedWallets = Vec<XDerivedWallet>;
+
 #[derive(PartialEq, Debug, Deserialize)]
 pub struct XWalletGenerationResult {
     pub wallet: XWallet,
-    pub mnemonic: String,
+    pub mnemonic: Mnemonic,
     #[serde(rename(deserialize = "derivationPath"))]
     pub derivation_path: String,
 }
 
 #[throws(_)]
 pub(crate) fn generate_random(
-    jscontext: &mut JavaScript,
+    jscontext: &JavaScript,
     entropy: Option<String>,
     test: bool,
 ) -> XWalletGenerationResult {
@@ -119,13 +240,13 @@ pub(crate) fn generate_random(
 
 #[throws(_)]
 pub(crate) fn from_mnemonic(
-    jscontext: &mut JavaScript,
+    jscontext: &JavaScript,
     mnemonic: String,
     derivation_path: Option<String>,
     test: bool,
 ) -> XWallet {
     let mut options = XGenerateWalletOptions::new(test);
-    options.mnemonic(mnemonic);
+    options.mnemonic(Mnemonic::new(mnemonic));
     if let Some(d) = derivation_path {
         options.derivation_path(d);
     }
@@ -137,13 +258,13 @@ pub(crate) fn from_mnemonic(
 
 #[throws(_)]
 pub(crate) fn from_seed(
-    jscontext: &mut JavaScript,
+    jscontext: &JavaScript,
     seed: String,
     derivation_path: Option<String>,
     test: bool,
 ) -> XWallet {
     let mut options = XGenerateWalletOptions::new(test);
-    options.seed(seed);
+    options.seed(Seed::new(seed));
     if let Some(d) = derivation_path {
         options.derivation_path(d);
     }
@@ -151,11 +272,153 @@ pub(crate) fn from_seed(
     result
 }
 
+/// Derives the wallets of `account` at the addresses `indexes`, in order.
+#[throws(_)]
+pub(crate) fn derive_range(
+    jscontext: &JavaScript,
+    mnemonic: &Mnemonic,
+    account: u32,
+    indexes: Range<u32>,
+    test: bool,
+) -> Vec<XDerivedWallet> {
+    if account > MAX_DERIVATION_INDEX || indexes.end > MAX_DERIVATION_INDEX + 1 {
+        throw!(Error::Validation(format!(
+            "Derivation indexes cannot be greater than {}",
+            MAX_DERIVATION_INDEX
+        )));
+    }
+    if indexes.start >= indexes.end {
+        return vec![];
+    }
+    let options = XDeriveOptions {
+        mnemonic,
+        account,
+        start: indexes.start,
+        count: indexes.end - indexes.start,
+        test,
+    };
+    let result = js!(jscontext.wallet.deriveWallets::<XDerivedWallets>(options))?;
+    result
+}
+
+#[throws(_)]
+pub(crate) fn watch_only_from_public_key(
+    jscontext: &JavaScript,
+    public_key: String,
+    test: bool,
+) -> XWatchOnlyWallet {
+    let options = XPublicKeyOptions { public_key, test };
+    let result = js!(jscontext
+        .wallet
+        .watchOnlyFromPublicKey::<XWatchOnlyWallet>(options))?;
+    result
+}
+
+/// Builds a watch-only wallet from an X-Address without a tag or a classic address.
+#[throws(_)]
+pub(crate) fn watch_only_from_address(
+    jscontext: &JavaScript,
+    address: &str,
+    test: bool,
+) -> XWatchOnlyWallet {
+    let classic_address = if address::is_valid_x_address(jscontext, address)? {
+        let decoded = address::decode_x_address(jscontext, address)?;
+        if decoded.tag.is_some() {
+            throw!(Error::Validation(
+                "A watch-only wallet is for an account: use an X-Address without a tag".to_owned()
+            ));
+        }
+        if decoded.test != test {
+            throw!(Error::Validation(format!(
+                "The X-Address is not for the {} network",
+                if test { "test" } else { "main" }
+            )));
+        }
+        decoded.address
+    } else if address::is_valid_classic_address(jscontext, address)? {
+        address.to_owned()
+    } else {
+        throw!(Error::Validation(format!("Invalid address {}", address)));
+    };
+    XWatchOnlyWallet {
+        address: address::encode_classic_address(jscontext, &classic_address, None, Some(test))?,
+        classic_address,
+        public_key: None,
+        test,
+    }
+}
+
+/// Encodes `entropy` as a family seed, or a random one when it is `None`.
+#[throws(_)]
+pub(crate) fn seed_from_entropy(
+    jscontext: &JavaScript,
+    entropy: Option<&[u8; 16]>,
+    algorithm: KeyAlgorithm,
+) -> Seed {
+    let options = XSeedOptions {
+        entropy: entropy.map(|e| Zeroizing::new(hex::encode_upper(e))),
+        algorithm,
+    };
+    let result = js!(jscontext.wallet.seedFromEntropy::<Seed>(options))?;
+    result
+}
+
+#[throws(_)]
+pub(crate) fn entropy_from_seed(jscontext: &JavaScript, seed: &Seed) -> XSeedEntropy {
+    let options = XDecodeSeedOptions { seed };
+    let decoded = js!(jscontext.wallet.entropyFromSeed::<XDecodedSeed>(options))?;
+    let mut entropy = Zeroizing::new([0u8; 16]);
+    hex::decode_to_slice(decoded.entropy.as_bytes(), &mut *entropy)
+        .map_err(|e| Error::Decode(format!("Invalid seed entropy: {}", e)))?;
+    XSeedEntropy {
+        entropy,
+        algorithm: decoded.algorithm,
+    }
+}
+
 #[throws(_)]
-pub(crate) fn sign(jscontext: &mut JavaScript, message: String, private_key: String) -> String {
+pub(crate) fn from_entropy(
+    jscontext: &JavaScript,
+    entropy: &[u8; 16],
+    algorithm: KeyAlgorithm,
+    test: bool,
+) -> XWallet {
+    let seed = seed_from_entropy(jscontext, Some(entropy), algorithm)?;
+    from_seed(jscontext, seed.expose_secret().to_owned(), None, test)?
+}
+
+#[throws(_)]
+pub(crate) fn generate_random_from_seed(
+    jscontext: &JavaScript,
+    algorithm: KeyAlgorithm,
+    test: bool,
+) -> XSeedWalletGenerationResult {
+    let seed = seed_from_entropy(jscontext, None, algorithm)?;
+    let wallet = from_seed(jscontext, seed.expose_secret().to_owned(), None, test)?;
+    XSeedWalletGenerationResult { wallet, seed }
+}
+
+/// Derives the wallet of a private key, either 33 bytes with the `00` (secp256k1) or `ED`
+/// (ed25519) prefix of derived wallets, or a bare 32 byte secp256k1 key. The wallet holds the
+/// key in the prefixed form.
+#[throws(_)]
+pub(crate) fn from_private_key(jscontext: &JavaScript, private_key: String, test: bool) -> XWallet {
+    let private_key = PrivateKey::new(private_key);
+    let options = XPrivateKeyOptions {
+        private_key: &private_key,
+        test,
+    };
+    let result = js!(jscontext
+        .wallet
+        .generateWalletFromPrivateKey::<XWallet>(options))?;
+    result
+}
+
+#[throws(_)]
+pub(crate) fn sign(jscontext: &JavaScript, message: String, private_key: String) -> String {
     let sign_options = XSignOptions {
         message: hex::encode(message),
-        private_key,
+        private_key: PrivateKey::new(private_key),
     };
     let result = js!(jscontext.wallet.sign::<String>(sign_options))?;
     result
@@ -163,7 +426,7 @@ pub(crate) fn sign(jscontext: &mut JavaScript, message: String, private_key: Str
 
 #[throws(_)]
 pub(crate) fn verify(
-    jscontext: &mut JavaScript,
+    jscontext: &JavaScript,
     message: String,
     signature: String,
     public_key: String,
@@ -183,31 +446,31 @@ mod tests {
     #[test]
     fn test_random_wallet_no_entropy_testnet() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let wallet = generate_random(&mut jscontext, None, true)?;
-        assert_eq!(wallet.mnemonic.is_empty(), false);
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = generate_random(&jscontext, None, true)?;
+        assert_eq!(wallet.mnemonic.expose_secret().is_empty(), false);
     }
 
     #[throws(_)]
     #[test]
     fn test_random_wallet_with_entropy_testnet() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let wallet = generate_random(
-            &mut jscontext,
+            &jscontext,
             Some("00000000000000000000000000000000".to_owned()),
             true,
         )?;
-        assert_eq!(wallet.mnemonic, "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about");
+        assert_eq!(wallet.mnemonic.expose_secret(), "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about");
     }
 
     #[throws(_)]
     #[test]
     fn test_random_wallet_with_invalid_entropy_testnet() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let wallet = generate_random(&mut jscontext, Some("wrong".to_owned()), true).unwrap_err();
-        assert_eq!("Invalid Entropy", wallet.downcast_ref::<String>().unwrap());
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = generate_random(&jscontext, Some("wrong".to_owned()), true).unwrap_err();
+        assert!(matches!(wallet, Error::Validation(ref m) if m == "Invalid Entropy"));
     }
 
     #[throws(_)]
@@ -216,17 +479,17 @@ mod tests {
         let expected = XWalletGenerationResult {
             wallet: XWallet {
                 public_key: "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE".to_owned(),
-                private_key: "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4".to_owned(),
+                private_key: "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4".into(),
                 test: true,
                 address: Some("TVHLFWLKvbMv1LFzd6FA2Bf9MPpcy4mRto4VFAAxLuNpvdW".to_owned())
             },
-            mnemonic: "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(),
+            mnemonic: "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".into(),
             derivation_path: "m/44\'/144\'/0\'/0/0".to_owned()
         };
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let wallet = generate_random(
-            &mut jscontext,
+            &jscontext,
             Some("00000000000000000000000000000000".to_owned()),
             true,
         )
@@ -240,17 +503,17 @@ mod tests {
         let expected = XWalletGenerationResult {
             wallet: XWallet {
                 public_key: "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE".to_owned(),
-                private_key: "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4".to_owned(),
+                private_key: "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4".into(),
                 test: false,
                 address: Some("XVMFQQBMhdouRqhPMuawgBMN1AVFTofPAdRsXG5RkPtUPNQ".to_owned())
             },
-            mnemonic: "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(),
+            mnemonic: "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".into(),
             derivation_path: "m/44\'/144\'/0\'/0/0".to_owned()
         };
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let wallet = generate_random(
-            &mut jscontext,
+            &jscontext,
             Some("00000000000000000000000000000000".to_owned()),
             false,
         )?;
@@ -263,17 +526,17 @@ mod tests {
         let expected = XWalletGenerationResult {
             wallet: XWallet {
                 public_key: "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE".to_owned(),
-                private_key: "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4".to_owned(),
+                private_key: "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4".into(),
                 test: true,
                 address: Some("TVHLFWLKvbMv1LFzd6FA2Bf9MPpcy4mRto4VFAAxLuNpvdW".to_owned())
             },
-            mnemonic: "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(),
+            mnemonic: "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".into(),
             derivation_path: "m/44\'/144\'/0\'/0/0".to_owned()
         };
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let wallet = generate_random(
-            &mut jscontext,
+            &jscontext,
             Some("00000000000000000000000000000000".to_owned()),
             true,
         )
@@ -285,9 +548,9 @@ mod tests {
     #[test]
     fn test_generate_random_wallet_testnet_no_entropy() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let wallet = generate_random(&mut jscontext, None, true).unwrap();
-        assert!(!wallet.mnemonic.is_empty());
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = generate_random(&jscontext, None, true).unwrap();
+        assert!(!wallet.mnemonic.expose_secret().is_empty());
         assert!(!wallet.wallet.address.unwrap().is_empty());
     }
 
@@ -295,9 +558,9 @@ mod tests {
     #[test]
     fn test_generate_random_wallet_mainnet_no_entropy() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let wallet = generate_random(&mut jscontext, None, false).unwrap();
-        assert!(!wallet.mnemonic.is_empty());
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = generate_random(&jscontext, None, false).unwrap();
+        assert!(!wallet.mnemonic.expose_secret().is_empty());
         assert!(!wallet.wallet.address.unwrap().is_empty());
     }
 
@@ -305,9 +568,9 @@ mod tests {
     #[test]
     fn test_generate_random_wallet_invalid_entropy() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let wallet = generate_random(&mut jscontext, Some("wrong".to_owned()), false).unwrap_err();
-        assert_eq!("Invalid Entropy", wallet.downcast_ref::<String>().unwrap());
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = generate_random(&jscontext, Some("wrong".to_owned()), false).unwrap_err();
+        assert!(matches!(wallet, Error::Validation(ref m) if m == "Invalid Entropy"));
     }
 
     #[throws(_)]
@@ -317,13 +580,13 @@ mod tests {
             public_key: "038BF420B5271ADA2D7479358FF98A29954CF18DC25155184AEAD05796DA737E89"
                 .to_owned(),
             private_key: "000974B4CFE004A2E6C4364CBF3510A36A352796728D0861F6B555ED7E54A70389"
-                .to_owned(),
+                .into(),
             test: true,
             address: Some("T7FxQEtaiNkq6ELhqGk3Pz2ov5aEoaGo6V642R74aaywJNT".to_owned()),
         };
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let wallet = from_mnemonic(&mut jscontext, "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(), Some("m/44'/144'/0'/0/1".to_owned()), true)?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = from_mnemonic(&jscontext, "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(), Some("m/44'/144'/0'/0/1".to_owned()), true)?;
         assert_eq!(wallet, expected);
     }
 
@@ -334,13 +597,13 @@ mod tests {
             public_key: "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE"
                 .to_owned(),
             private_key: "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4"
-                .to_owned(),
+                .into(),
             test: true,
             address: Some("TVHLFWLKvbMv1LFzd6FA2Bf9MPpcy4mRto4VFAAxLuNpvdW".to_owned()),
         };
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let wallet = from_mnemonic(&mut jscontext, "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(), None, true)?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = from_mnemonic(&jscontext, "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(), None, true)?;
         assert_eq!(wallet, expected);
     }
 
@@ -351,13 +614,13 @@ mod tests {
             public_key: "038BF420B5271ADA2D7479358FF98A29954CF18DC25155184AEAD05796DA737E89"
                 .to_owned(),
             private_key: "000974B4CFE004A2E6C4364CBF3510A36A352796728D0861F6B555ED7E54A70389"
-                .to_owned(),
+                .into(),
             test: false,
             address: Some("X7uRz9jfzHUFEjZTZ7rMVzFuTGZTHWcmkKjvGkNqVbfMhca".to_owned()),
         };
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let wallet = from_mnemonic(&mut jscontext, "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(), Some("m/44'/144'/0'/0/1".to_owned()), false).unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = from_mnemonic(&jscontext, "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(), Some("m/44'/144'/0'/0/1".to_owned()), false).unwrap();
         assert_eq!(wallet, expected);
     }
 
@@ -368,13 +631,13 @@ mod tests {
             public_key: "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE"
                 .to_owned(),
             private_key: "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4"
-                .to_owned(),
+                .into(),
             test: false,
             address: Some("XVMFQQBMhdouRqhPMuawgBMN1AVFTofPAdRsXG5RkPtUPNQ".to_owned()),
         };
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let wallet = from_mnemonic(&mut jscontext, "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(), None, false).unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = from_mnemonic(&jscontext, "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(), None, false).unwrap();
         assert_eq!(wallet, expected);
     }
 
@@ -385,13 +648,13 @@ mod tests {
             public_key: "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE"
                 .to_owned(),
             private_key: "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4"
-                .to_owned(),
+                .into(),
             test: true,
             address: Some("TVHLFWLKvbMv1LFzd6FA2Bf9MPpcy4mRto4VFAAxLuNpvdW".to_owned()),
         };
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let wallet = from_mnemonic(&mut jscontext, "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(), None, true).unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = from_mnemonic(&jscontext, "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_owned(), None, true).unwrap();
         assert_eq!(wallet, expected);
     }
 
@@ -399,9 +662,9 @@ mod tests {
     #[test]
     fn test_from_seed_without_derivation_path_mainnet() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let wallet = from_seed(
-            &mut jscontext,
+            &jscontext,
             "snYP7oArxKepd3GPDcrjMsJYiJeJB".to_owned(),
             None,
             false,
@@ -416,9 +679,9 @@ mod tests {
     #[test]
     fn test_from_seed_with_derivation_path_maintnet() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let wallet = from_seed(
-            &mut jscontext,
+            &jscontext,
             "snYP7oArxKepd3GPDcrjMsJYiJeJB".to_owned(),
             Some("m/44'/144'/0'/0/1".to_owned()),
             false,
@@ -433,18 +696,18 @@ mod tests {
     #[test]
     fn test_from_seed_with_invalid_seed() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let wallet = from_seed(&mut jscontext, "xxx".to_owned(), None, false).unwrap_err();
-        assert_eq!("Invalid Seed", wallet.downcast_ref::<String>().unwrap());
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = from_seed(&jscontext, "xxx".to_owned(), None, false).unwrap_err();
+        assert!(matches!(wallet, Error::Validation(ref m) if m == "Invalid Seed"));
     }
 
     #[throws(_)]
     #[test]
     fn test_generate_wallet_from_seed_mainnet() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let wallet = from_seed(
-            &mut jscontext,
+            &jscontext,
             "snYP7oArxKepd3GPDcrjMsJYiJeJB".to_owned(),
             None,
             false,
@@ -460,9 +723,9 @@ mod tests {
     #[test]
     fn test_generate_wallet_from_seed_testnet() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let wallet = from_seed(
-            &mut jscontext,
+            &jscontext,
             "snYP7oArxKepd3GPDcrjMsJYiJeJB".to_owned(),
             None,
             true,
@@ -478,9 +741,9 @@ mod tests {
     #[test]
     fn test_generate_wallet_from_invalid_seed() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
-        let wallet = from_seed(&mut jscontext, "xrp".to_owned(), None, false).unwrap_err();
-        assert_eq!("Invalid Seed", wallet.downcast_ref::<String>().unwrap());
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = from_seed(&jscontext, "xrp".to_owned(), None, false).unwrap_err();
+        assert!(matches!(wallet, Error::Validation(ref m) if m == "Invalid Seed"));
     }
 
     #[throws(_)]
@@ -489,9 +752,9 @@ mod tests {
         let message = "test message".to_owned();
         let expected_signature = "304402204146402099809E1F021421569F72BA34DCAFCC832741AB6310F887F60734D9F002203E813AD6A59D67D8EE06C8EA05BCC1BA8F690B631E6F243E8BE60633D27BE05D";
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let signed_message = sign(
-            &mut jscontext,
+            &jscontext,
             message,
             "000974B4CFE004A2E6C4364CBF3510A36A352796728D0861F6B555ED7E54A70389".to_owned(),
         )
@@ -505,9 +768,9 @@ mod tests {
         let message = "test message".to_owned();
         let signature = "304402204146402099809E1F021421569F72BA34DCAFCC832741AB6310F887F60734D9F002203E813AD6A59D67D8EE06C8EA05BCC1BA8F690B631E6F243E8BE60633D27BE05D".to_owned();
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let verified_message = verify(
-            &mut jscontext,
+            &jscontext,
             message,
             signature,
             "038BF420B5271ADA2D7479358FF98A29954CF18DC25155184AEAD05796DA737E89".to_owned(),
@@ -521,9 +784,9 @@ mod tests {
         let message = "test message".to_owned();
         let signature = "DEADBEEF".to_owned();
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let verified_message = verify(
-            &mut jscontext,
+            &jscontext,
             message,
             signature,
             "038BF420B5271ADA2D7479358FF98A29954CF18DC25155184AEAD05796DA737E89".to_owned(),
@@ -537,9 +800,9 @@ mod tests {
         let message = "test message".to_owned();
         let signature = "xrp".to_owned();
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let verified_message = verify(
-            &mut jscontext,
+            &jscontext,
             message,
             signature,
             "038BF420B5271ADA2D7479358FF98A29954CF18DC25155184AEAD05796DA737E89".to_owned(),
@@ -551,15 +814,15 @@ mod tests {
     #[test]
     fn test_signs_and_verifies_empty_message() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let signed_message = sign(
-            &mut jscontext,
+            &jscontext,
             "".to_owned(),
             "000974B4CFE004A2E6C4364CBF3510A36A352796728D0861F6B555ED7E54A70389".to_owned(),
         )
         .unwrap();
         let verified_message = verify(
-            &mut jscontext,
+            &jscontext,
             "".to_owned(),
             signed_message,
             "038BF420B5271ADA2D7479358FF98A29954CF18DC25155184AEAD05796DA737E89".to_owned(),
@@ -571,13 +834,176 @@ mod tests {
     #[test]
     fn test_fails_to_verify_a_bad_signature_on_an_empty_string() {
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let verified_message = verify(
-            &mut jscontext,
+            &jscontext,
             "".to_owned(),
             "DEADBEEF".to_owned(),
             "038BF420B5271ADA2D7479358FF98A29954CF18DC25155184AEAD05796DA737E89".to_owned(),
         )?;
         assert!(!verified_message);
     }
+
+    #[throws(_)]
+    #[test]
+    fn test_seed_entropy_and_private_key_round_trips() {
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        for (algorithm, expected_seed) in &[
+            (KeyAlgorithm::Secp256k1, "sp6JS7f14BuwFY8Mw6bTtLKWauoUs"),
+            (KeyAlgorithm::Ed25519, "sEdSJHS4oiAdz7w2X2ni1gFiqtbJHqE"),
+        ] {
+            let seed = seed_from_entropy(&jscontext, Some(&[0; 16]), *algorithm)?;
+            assert_eq!(seed.expose_secret(), *expected_seed);
+            let decoded = entropy_from_seed(&jscontext, &seed)?;
+            assert_eq!(*decoded.entropy, [0; 16]);
+            assert_eq!(decoded.algorithm, *algorithm);
+
+            let wallet = from_entropy(&jscontext, &[0; 16], *algorithm, true)?;
+            let from_seed = from_seed(&jscontext, seed.expose_secret().to_owned(), None, true)?;
+            assert_eq!(wallet, from_seed);
+            let from_key = from_private_key(
+                &jscontext,
+                wallet.private_key.expose_secret().to_owned(),
+                true,
+            )?;
+            assert_eq!(from_key, wallet);
+        }
+
+        // Bare secp256k1 keys come back prefixed.
+        let wallet = from_private_key(
+            &jscontext,
+            "90802a50aa84efb6cdb225f17c27616ea94048c179142fecf03f4712a07ea7a4".to_owned(),
+            true,
+        )?;
+        assert_eq!(
+            wallet.private_key.expose_secret(),
+            "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4"
+        );
+        assert_eq!(
+            wallet.address.as_deref(),
+            Some("TVHLFWLKvbMv1LFzd6FA2Bf9MPpcy4mRto4VFAAxLuNpvdW")
+        );
+
+        let generated = generate_random_from_seed(&jscontext, KeyAlgorithm::Ed25519, true)?;
+        assert!(generated.seed.expose_secret().starts_with("sEd"));
+        let wallet = from_seed(
+            &jscontext,
+            generated.seed.expose_secret().to_owned(),
+            None,
+            true,
+        )?;
+        assert_eq!(wallet, generated.wallet);
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_invalid_seeds_and_private_keys() {
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let error =
+            entropy_from_seed(&jscontext, &"snYP7oArxKepd3GPDcrjMsJYiJeJx".into()).unwrap_err();
+        assert!(matches!(error, Error::Validation(ref m) if m == "Invalid Seed"));
+        for key in &[
+            "00",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+            "0190802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4",
+        ] {
+            let error = from_private_key(&jscontext, key.to_string(), true).unwrap_err();
+            assert!(matches!(error, Error::Validation(ref m) if m == "Invalid Private Key"));
+        }
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_derive_range() {
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let mnemonic = Mnemonic::new("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about");
+        let derived = derive_range(&jscontext, &mnemonic, 0, 0..2, true)?;
+        assert_eq!(derived.len(), 2);
+        assert_eq!(
+            derived[0].classic_address,
+            "rHsMGQEkVNJmpGWs8XUBoTBiAAbwxZN5v3"
+        );
+        assert_eq!(
+            derived[0].wallet.address.as_deref(),
+            Some("TVHLFWLKvbMv1LFzd6FA2Bf9MPpcy4mRto4VFAAxLuNpvdW")
+        );
+        assert_eq!((derived[1].account, derived[1].index), (0, 1));
+        assert_eq!(derived[1].derivation_path, "m/44'/144'/0'/0/1");
+        let wallet = from_mnemonic(
+            &jscontext,
+            mnemonic.expose_secret().to_owned(),
+            Some("m/44'/144'/0'/0/1".to_owned()),
+            true,
+        )?;
+        assert_eq!(derived[1].wallet, wallet);
+
+        let derived = derive_range(&jscontext, &mnemonic, 5, 7..9, true)?;
+        let wallet = from_mnemonic(
+            &jscontext,
+            mnemonic.expose_secret().to_owned(),
+            Some("m/44'/144'/5'/0/8".to_owned()),
+            true,
+        )?;
+        assert_eq!(derived[1].wallet, wallet);
+
+        assert!(derive_range(&jscontext, &mnemonic, 0, 3..3, true)?.is_empty());
+        let error = derive_range(
+            &jscontext,
+            &mnemonic,
+            0,
+            MAX_DERIVATION_INDEX..MAX_DERIVATION_INDEX + 2,
+            true,
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::Validation(_)));
+        let error = derive_range(&jscontext, &"abandon about".into(), 0, 0..1, true).unwrap_err();
+        assert!(matches!(error, Error::Validation(ref m) if m == "Invalid mnemonic"));
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_watch_only() {
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let watch_only = watch_only_from_public_key(
+            &jscontext,
+            "031d68bc1a142e6766b2bdfb006ccfe135ef2e0e2e94abb5cf5c9ab6104776fbae".to_owned(),
+            true,
+        )?;
+        assert_eq!(
+            watch_only,
+            XWatchOnlyWallet {
+                address: "TVHLFWLKvbMv1LFzd6FA2Bf9MPpcy4mRto4VFAAxLuNpvdW".to_owned(),
+                classic_address: "rHsMGQEkVNJmpGWs8XUBoTBiAAbwxZN5v3".to_owned(),
+                public_key: Some(
+                    "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE".to_owned()
+                ),
+                test: true,
+            }
+        );
+        let error =
+            watch_only_from_public_key(&jscontext, "031D68BC".to_owned(), true).unwrap_err();
+        assert!(matches!(error, Error::Validation(ref m) if m == "Invalid Public Key"));
+
+        let from_classic =
+            watch_only_from_address(&jscontext, "rHsMGQEkVNJmpGWs8XUBoTBiAAbwxZN5v3", true)?;
+        let from_x = watch_only_from_address(&jscontext, &from_classic.address, true)?;
+        assert_eq!(from_classic, from_x);
+        assert_eq!(from_x.address, watch_only.address);
+        assert_eq!(from_x.public_key, None);
+
+        let tagged = address::encode_classic_address(
+            &jscontext,
+            "rHsMGQEkVNJmpGWs8XUBoTBiAAbwxZN5v3",
+            Some(1),
+            Some(true),
+        )?;
+        assert!(watch_only_from_address(&jscontext, &tagged, true).is_err());
+        assert!(watch_only_from_address(&jscontext, &from_x.address, false).is_err());
+        assert!(watch_only_from_address(&jscontext, "rHsMGQEk", true).is_err());
+    }
 }
@@ -1,202 +1,1469 @@
 use crate::address::XClassicAddress;
+use crate::bundle;
+use crate::challenge::{self, XOwnershipChallenge, XOwnershipProof, XOwnershipVerification};
+use crate::config::XrplConfig;
+use crate::error::XpringError as Error;
 use crate::ilpclient::{IlpBalanceResponse, IlpClient, IlpSendResponse};
-use crate::javascript::JavaScript;
-use crate::transaction::XTransactionStatus;
+use crate::javascript::{JavaScript, JsBridgeOptions, JsEngine};
+use crate::ledger_object::{XAccountObjects, XAccountRoot, XSignerList};
+use crate::message::{self, XMessageSignature, XSignatureVerification};
+use crate::secret::{Mnemonic, PrivateKey, Seed, Share};
+use crate::shamir;
+use crate::transaction::{
+    self, XIncomingPayment, XPayment, XSignedTransaction, XTransactionStatus, XUnsignedTransaction,
+};
 use crate::util;
-use crate::wallet::{self, XWallet, XWalletGenerationResult};
+use crate::vanity::{self, XVanityPattern, XVanityWallet};
+use crate::wallet::{
+    self, KeyAlgorithm, XDerivedWallet, XSeedEntropy, XSeedWalletGenerationResult, XWallet,
+    XWalletGenerationResult, XWatchOnlyWallet,
+};
 use crate::xrplclient::{XrplClient, XrplReliableSendResponse};
-use anyhow::Error;
 use fehler::throws;
-use std::{env, fs};
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
 
+/// Starts the xpring.js bridge described by `config`.
 #[throws(_)]
-fn copy_js_to_exec_path() -> String {
-    let mut current_executable = std::env::current_exe()?;
-    current_executable.pop();
-    let xpringjs_content = include_str!("../js/dist/xpring.js");
-    let xpringjs_path = format!("{}/xpring.js", current_executable.display());
-    fs::write(xpringjs_path.clone(), xpringjs_content).expect("Unable to write file");
-    xpringjs_path
-}
-
-#[throws(_)]
-fn set_vars() {
-    env::set_var("NODE_NO_WARNINGS", "1");
+fn start_javascript(config: &XrplConfig, workers: usize) -> JavaScript {
+    let options = config.js_bridge_options.clone();
+    match config.js_engine {
+        JsEngine::Node => JavaScript::pool(bundle::install(&config.js_bundle)?, workers, options)?,
+        #[cfg(feature = "embedded-js")]
+        JsEngine::Embedded => JavaScript::embedded(workers, options)?,
+    }
 }
 
-/// The Xrpl struct will allow you to access all the Xrpl methods
+/// The Xrpl struct will allow you to access all the Xrpl methods. Its offline methods (wallets,
+/// signing and address utilities) are those of its `XrplPool`, returned by `Xrpl::pool`, which
+/// has a single worker.
 pub struct Xrpl {
-    pub(crate) jscontext: JavaScript,
+    pub(crate) pool: XrplPool,
     pub(crate) xrplclient: XrplClient,
-    pub(crate) test: bool,
 }
 
-impl Xrpl {
-    /// Creates a Xpring struct.
+impl Xrpl {
+    /// Creates a Xpring struct.
+    ///
+    /// # Arguments
+    ///
+    /// * `xrplclient_url` -  `&str` Url for the XRP Ledger node.
+    /// * `test` -  `bool` true for TestNet, false for MainNet.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Xpring struct wrapped in a Result (Result<Xpring, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// let mut xpring =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn new<S: Into<String>>(xrplclient_url: S, test: bool) -> Xrpl {
+        Xrpl::with_config(xrplclient_url, XrplConfig::new().test(test))?
+    }
+
+    /// Creates a Xrpl struct with the settings of `config`.
+    ///
+    /// # Arguments
+    ///
+    /// * `xrplclient_url` -  `&str` Url for the XRP Ledger node.
+    /// * `config` -  `&XrplConfig` Network, JavaScript engine, xpring.js bundle location and
+    ///   bridge options.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Xrpl struct wrapped in a Result (Result<Xrpl, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::{JsBundle, Xrpl, XrplConfig};
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// let mut config = XrplConfig::new();
+    /// config.test(true).js_bundle(JsBundle::TempDir);
+    /// let mut xrpl = Xrpl::with_config("http://test.xrp.xpring.io:50051", &config)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn with_config<S: Into<String>>(xrplclient_url: S, config: &XrplConfig) -> Xrpl {
+        Xrpl {
+            pool: XrplPool::with_config(1, config)?,
+            xrplclient: XrplClient::connect(xrplclient_url.into())?,
+        }
+    }
+
+    /// Creates a Xrpl struct that records every request made to the XRP Ledger node, and its
+    /// response, to a file. The file can be replayed later with `Xrpl::replay`.
+    ///
+    /// # Arguments
+    ///
+    /// * `xrplclient_url` -  `&str` Url for the XRP Ledger node.
+    /// * `test` -  `bool` true for TestNet, false for MainNet.
+    /// * `path` -  `AsRef<Path>` File to record to. An existing file is replaced.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Xrpl struct wrapped in a Result (Result<Xrpl, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use xpring::Xrpl;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// let mut xrpl = Xrpl::record("http://test.xrp.xpring.io:50051", true, "session.jsonl")?;
+    /// let balance = xrpl.get_balance("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn record<S: Into<String>, P: AsRef<Path>>(xrplclient_url: S, test: bool, path: P) -> Xrpl {
+        Xrpl::record_with_config(xrplclient_url, XrplConfig::new().test(test), path)?
+    }
+
+    /// Same as `Xrpl::record`, with the settings of `config`.
+    ///
+    /// # Arguments
+    ///
+    /// * `xrplclient_url` -  `&str` Url for the XRP Ledger node.
+    /// * `config` -  `&XrplConfig` Network, JavaScript engine, xpring.js bundle location and
+    ///   bridge options.
+    /// * `path` -  `AsRef<Path>` File to record to. An existing file is replaced.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Xrpl struct wrapped in a Result (Result<Xrpl, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use xpring::{JsBundle, Xrpl, XrplConfig};
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// let mut config = XrplConfig::new();
+    /// config.test(true).js_bundle(JsBundle::TempDir);
+    /// let mut xrpl =
+    ///     Xrpl::record_with_config("http://test.xrp.xpring.io:50051", &config, "session.jsonl")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn record_with_config<S: Into<String>, P: AsRef<Path>>(
+        xrplclient_url: S,
+        config: &XrplConfig,
+        path: P,
+    ) -> Xrpl {
+        Xrpl {
+            pool: XrplPool::with_config(1, config)?,
+            xrplclient: XrplClient::record(xrplclient_url.into(), path)?,
+        }
+    }
+
+    /// Creates a Xrpl struct that answers every request to the XRP Ledger from a file written
+    /// by `Xrpl::record`, without any network access.
+    ///
+    /// Requests must be made in the order they were recorded and with the same arguments,
+    /// otherwise they fail with a `XpringError::Transport` error. `send` does not wait between
+    /// status polls when replaying.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` -  `AsRef<Path>` Recorded session.
+    /// * `test` -  `bool` true for TestNet, false for MainNet.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Xrpl struct wrapped in a Result (Result<Xrpl, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use xpring::Xrpl;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// let mut xrpl = Xrpl::replay("session.jsonl", true)?;
+    /// let balance = xrpl.get_balance("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn replay<P: AsRef<Path>>(path: P, test: bool) -> Xrpl {
+        Xrpl::replay_with_config(path, XrplConfig::new().test(test))?
+    }
+
+    /// Same as `Xrpl::replay`, with the settings of `config`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` -  `AsRef<Path>` Recorded session.
+    /// * `config` -  `&XrplConfig` Network, JavaScript engine, xpring.js bundle location and
+    ///   bridge options.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Xrpl struct wrapped in a Result (Result<Xrpl, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use xpring::{JsBundle, Xrpl, XrplConfig};
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// let mut config = XrplConfig::new();
+    /// config.test(true).js_bundle(JsBundle::TempDir);
+    /// let mut xrpl = Xrpl::replay_with_config("session.jsonl", &config)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn replay_with_config<P: AsRef<Path>>(path: P, config: &XrplConfig) -> Xrpl {
+        Xrpl {
+            pool: XrplPool::with_config(1, config)?,
+            xrplclient: XrplClient::replay(path)?,
+        }
+    }
+
+    /// Returns the pool that runs the offline methods: wallets, signing and address utilities.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", true)?;
+    /// let wallet = xrpl.pool().wallet_from_seed("sn3UJSLzAEeAGcrK3nsQTDZW6KT92", None)?;
+    /// assert!(xrpl.pool().validate_address("rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pool(&self) -> &XrplPool {
+        &self.pool
+    }
+
+    // Wallet
+
+    /// Generates random seed wallets until the address of one matches `pattern`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - `&XVanityPattern` Prefix or JavaScript regular expression for the classic address or the X-Address.
+    /// * `algorithm` - `KeyAlgorithm` Algorithm of the wallets.
+    /// * `max_attempts` - `Option<u64>` Number of wallets after which the search gives up, or None to search until one matches. Required for regular expressions.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XVanityWallet with the wallet, its seed and the number of wallets generated
+    /// wrapped in a Result (Result<XVanityWallet, XpringError>).
+    /// `XVanityPattern::expected_attempts` estimates how long a prefix takes: every character
+    /// after the first two multiplies it by 58. A prefix no address can have is an error, and
+    /// so is a regular expression without `max_attempts`, since it may match no address.
+    /// The search runs on a separate JavaScript bridge with one worker per available CPU, which
+    /// is stopped once the search is over.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # use xpring::vanity::XVanityPattern;
+    /// # use xpring::wallet::KeyAlgorithm;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", true)?;
+    /// let pattern = XVanityPattern::ClassicPrefix("rp".to_owned());
+    /// println!("about {:.0} wallets", pattern.expected_attempts(true).unwrap());
+    /// let found = xrpl.generate_vanity_wallet(&pattern, KeyAlgorithm::Secp256k1, None)?;
+    /// assert!(found.classic_address.starts_with("rp"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn generate_vanity_wallet(
+        &self,
+        pattern: &XVanityPattern,
+        algorithm: KeyAlgorithm,
+        max_attempts: Option<u64>,
+    ) -> XVanityWallet {
+        let workers = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let jscontext = self.pool.jscontext.with_workers(workers)?;
+        vanity::search(&jscontext, pattern, algorithm, max_attempts, self.pool.test)?
+    }
+
+    // XrplClient
+
+    /// Returns an account balance.
+    ///
+    /// # Arguments
+    ///
+    /// * `x_address` -  `&str` Account in x format.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a f32 with the balance in decimal format wrapped in a Result (Result<f32, XpringError> ).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
+    /// let balance = xrpl.get_balance("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
+    /// # Ok(())
+    /// # }
+    ///
+    /// // 1000
+    /// ```
+    #[throws(_)]
+    pub fn get_balance(&mut self, x_address: &str) -> f32 {
+        self.xrplclient
+            .get_balance(&self.pool.jscontext, x_address)?
+    }
+
+    /// Sends a payment from one account to another.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` -  `f32` Payment amount in decimal format (Ex. 10.32).
+    /// * `from_address` -  `&str` Origin account in x format.
+    /// * `to_address` -  `&str` Destination account in x format.
+    /// * `source_wallet` -  `XWallet` Wallet that will fund the payment and sign the transaction.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XrplReliableSendResponse wrapped in a Result (Result<XrplReliableSendResponse, XpringError>).
+    /// It waits until the transaction is validated or its last ledger sequence has passed, and
+    /// `transaction_info` holds the message of the node. Fails with `XpringError::EngineResult`
+    /// when the node rejects the transaction with a `tem`, `tef` or `tel` result.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # use xpring::xrplclient::{XrplReliableSendResponse};
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
+    /// let sending_wallet =
+    ///     xrpl.pool().wallet_from_seed(
+    ///         "sn3UJSLzAEeAGcrK3nsQTDZW6KT92",
+    ///         None
+    ///     )?;
+    /// let payment = xrpl.send(
+    ///     12.12,
+    ///     "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+    ///     "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
+    ///     sending_wallet,
+    /// )?;
+    ///
+    /// # Ok(())
+    /// # }
+    ///
+    /// // {
+    /// //  transaction_status: FAILED,
+    /// //  transaction_hash: "2E01FED358DDB9B843116D858695D8EF3285BA6C7A478D054E05AD20BD50857C",
+    /// //  transaction_info: "Insufficient XRP balance to send."
+    /// // }
+    /// ```
+    #[throws(_)]
+    pub fn send(
+        &mut self,
+        amount: f32,
+        from_x_address: &str,
+        to_x_address: &str,
+        source_wallet: XWallet,
+    ) -> XrplReliableSendResponse {
+        self.xrplclient.send(
+            &self.pool.jscontext,
+            amount,
+            from_x_address,
+            to_x_address,
+            source_wallet,
+        )?
+    }
+
+    /// Sends a payment built with `XPayment`, which allows setting a destination tag,
+    /// a source tag, an invoice ID and memos.
+    ///
+    /// # Arguments
+    ///
+    /// * `payment` -  `XPayment` Payment details.
+    /// * `source_wallet` -  `XWallet` Wallet that will fund the payment and sign the transaction.
+    ///
+    /// # Remarks
+    ///
+    /// If the destination X-Address carries a tag it will be used as destination tag, setting
+    /// a different destination tag explicitly results in an error.
+    ///
+    /// Returns a XrplReliableSendResponse wrapped in a Result (Result<XrplReliableSendResponse, XpringError>).
+    /// It waits until the transaction is validated or its last ledger sequence has passed, and
+    /// `transaction_info` holds the message of the node. Fails with `XpringError::EngineResult`
+    /// when the node rejects the transaction with a `tem`, `tef` or `tel` result.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # use xpring::transaction::{XMemo, XPayment};
+    /// # use xpring::xrplclient::{XrplReliableSendResponse};
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
+    /// let sending_wallet =
+    ///     xrpl.pool().wallet_from_seed(
+    ///         "sn3UJSLzAEeAGcrK3nsQTDZW6KT92",
+    ///         None
+    ///     )?;
+    /// let mut payment = XPayment::new(
+    ///     12.12,
+    ///     "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+    ///     "T7QqSicoC1nB4YRyzWzctWW7KjwiYUo9ZAXPrwRoKJ7FudP",
+    /// );
+    /// payment
+    ///     .destination_tag(12345)
+    ///     .source_tag(42)
+    ///     .memo(XMemo::new("invoice 12345"));
+    /// let result = xrpl.send_payment(payment, sending_wallet)?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn send_payment(
+        &mut self,
+        payment: XPayment,
+        source_wallet: XWallet,
+    ) -> XrplReliableSendResponse {
+        self.xrplclient
+            .send_payment(&self.pool.jscontext, payment, source_wallet)?
+    }
+
+    /// Prepares a payment of a watch-only wallet, so that it can be signed offline.
+    ///
+    /// # Arguments
+    ///
+    /// * `wallet` - `&XWatchOnlyWallet` Wallet of the account that sends the payment.
+    /// * `payment` - `XPayment` Payment details, from the account of `wallet`.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XUnsignedTransaction wrapped in a Result (Result<XUnsignedTransaction, XpringError>).
+    /// The account sequence and the last ledger sequence are read from the ledger, so the
+    /// transaction must be signed and submitted before the ledger passes the last ledger
+    /// sequence. The fee is the open ledger fee of the node, capped at
+    /// `transaction::MAX_OFFLINE_FEE`. `XUnsignedTransaction::to_json` writes it as a file for
+    /// the signing machine.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # use xpring::transaction::XPayment;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
+    /// let watch_only = xrpl
+    ///     .pool()
+    ///     .watch_only_wallet_from_address("r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4")?;
+    /// let payment = XPayment::new(
+    ///     12.12,
+    ///     "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+    ///     "T7QqSicoC1nB4YRyzWzctWW7KjwiYUo9ZAXPrwRoKJ7FudP",
+    /// );
+    /// let unsigned = xrpl.prepare_payment(&watch_only, payment);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn prepare_payment(
+        &mut self,
+        wallet: &XWatchOnlyWallet,
+        payment: XPayment,
+    ) -> XUnsignedTransaction {
+        self.xrplclient
+            .prepare_payment(&self.pool.jscontext, payment, wallet)?
+    }
+
+    /// Submits a transaction signed with `XrplPool::sign_transaction` and tracks it.
+    ///
+    /// # Arguments
+    ///
+    /// * `signed` - `&XSignedTransaction` Signed transaction.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XrplReliableSendResponse wrapped in a Result (Result<XrplReliableSendResponse, XpringError>).
+    /// Like `Xrpl::send_payment`, it waits until the transaction is validated or its last ledger
+    /// sequence has passed. The transaction must be for the network of this client, and its
+    /// `transaction_hash` and `last_ledger_sequence` must match the blob.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # use xpring::transaction::XSignedTransaction;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
+    /// # let json = "";
+    /// let signed = XSignedTransaction::from_json(json);
+    /// if let Ok(signed) = signed {
+    ///     let result = xrpl.submit_transaction(&signed)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn submit_transaction(&mut self, signed: &XSignedTransaction) -> XrplReliableSendResponse {
+        self.xrplclient
+            .submit_transaction(&self.pool.jscontext, signed, self.pool.test)?
+    }
+
+    /// Verifies the answer to a challenge: it must answer `issued` before it expires, be signed
+    /// by its public key, and the key must be the master key of the address (unless the account
+    /// disabled it) or the regular key of its account.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - `&XOwnershipProof` Answer of the client.
+    /// * `issued` - `&XOwnershipChallenge` Challenge the server issued.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XOwnershipVerification wrapped in a Result (Result<XOwnershipVerification, XpringError>).
+    /// Malformed proofs and failures to reach the node are errors.
+    #[throws(_)]
+    pub fn verify_ownership(
+        &mut self,
+        proof: &XOwnershipProof,
+        issued: &XOwnershipChallenge,
+    ) -> XOwnershipVerification {
+        self.xrplclient
+            .verify_ownership(&self.pool.jscontext, proof, issued, challenge::now())?
+    }
+
+    /// Returns a certain transaction status.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_hash` -  `&str` Transaction hash.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XTransactionStatus with the transaction status wrapped in a Result (Result<XTransactionStatus, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # use xpring::transaction::XTransactionStatus;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
+    /// let transaction_status = xrpl.get_transaction_status(
+    ///      "B171331C9B3D24ACF7ED35F9B6A63090D10048B56C8B187DE6EA71D0AB483086",
+    /// )?;
+    /// # Ok(())
+    /// # }
+    ///
+    /// // FAILED
+    /// ```
+    #[throws(_)]
+    pub fn get_transaction_status(&mut self, transaction_hash: &str) -> XTransactionStatus {
+        self.xrplclient.get_transaction_status(transaction_hash)?
+    }
+
+    /// Returns the validated payments received by an address within a ledger range.
+    ///
+    /// # Arguments
+    ///
+    /// * `x_address` -  `&str` Address in X-Address format. If it carries a tag only payments with that destination tag are returned.
+    /// * `ledger_index_min` - `u32` First ledger to look at.
+    /// * `ledger_index_max` - `u32` Last ledger to look at (0 for the latest validated ledger).
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Vec<XIncomingPayment> wrapped in a Result (Result<Vec<XIncomingPayment>, XpringError>).
+    /// Only successful payments are returned. Always credit `delivered_amount`, never `amount`:
+    /// a payment with the partial payment flag set may deliver much less than its `amount`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
+    /// let payments = xrpl.get_incoming_payments(
+    ///      "T7QqSicoC1nB4YRyzWzctWW7KjfiWnhDBNmUGstBV5wvRfB",
+    ///      1,
+    ///      0,
+    /// )?;
+    /// for payment in payments {
+    ///     println!("{} {:?}", payment.transaction_hash, payment.delivered_amount);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn get_incoming_payments(
+        &mut self,
+        x_address: &str,
+        ledger_index_min: u32,
+        ledger_index_max: u32,
+    ) -> Vec<XIncomingPayment> {
+        self.xrplclient.get_incoming_payments(
+            &self.pool.jscontext,
+            x_address,
+            ledger_index_min,
+            ledger_index_max,
+        )?
+    }
+
+    /// Returns the account root object of an account: its balance, sequence, flags and settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `x_address` -  `&str` Account in x format.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XAccountRoot wrapped in a Result (Result<XAccountRoot, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
+    /// let account = xrpl.get_account_root("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
+    /// println!("{:?} {:?}", account.sequence, account.regular_key);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn get_account_root(&mut self, x_address: &str) -> XAccountRoot {
+        self.xrplclient
+            .get_account_root(&self.pool.jscontext, x_address)?
+    }
+
+    /// Returns the signer list of an account, if it set one for multi-signing.
+    ///
+    /// # Arguments
+    ///
+    /// * `x_address` -  `&str` Account in x format.
+    ///
+    /// # Remarks
+    ///
+    /// Returns an Option<XSignerList> wrapped in a Result (Result<Option<XSignerList>, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
+    /// if let Some(list) = xrpl.get_signer_list("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")? {
+    ///     println!("{:?} of {} signers", list.signer_quorum, list.signer_entries.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn get_signer_list(&mut self, x_address: &str) -> Option<XSignerList> {
+        self.xrplclient
+            .get_signer_list(&self.pool.jscontext, x_address)?
+    }
+
+    /// Returns the objects an account owns or is the destination of: offers, escrows, checks,
+    /// payment channels, trust lines, its signer list and deposit preauthorizations.
+    ///
+    /// # Arguments
+    ///
+    /// * `x_address` -  `&str` Account in x format.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XAccountObjects wrapped in a Result (Result<XAccountObjects, XpringError>).
+    /// Every object comes with its index, the key transactions such as `CheckCash` name it by.
+    /// The node has no query for the owner directory of an account, so the objects are
+    /// reconstructed from the metadata of its validated transactions, back to the creation of
+    /// the account. This reads the whole history of the account, and fails with
+    /// `XpringError::IncompleteHistory` on nodes that do not hold it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
+    /// let objects = xrpl.get_account_objects("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
+    /// for check in objects.checks {
+    ///     println!("{} {:?}", check.index, check.object.send_max);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn get_account_objects(&mut self, x_address: &str) -> XAccountObjects {
+        self.xrplclient
+            .get_account_objects(&self.pool.jscontext, x_address)?
+    }
+
+    /// Finds the funded wallets of a mnemonic, following the account discovery of BIP44 on
+    /// `m/44'/144'/account'/0/index`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mnemonic` - `Mnemonic` Mnemonic.
+    /// * `gap_limit` - `u32` Number of consecutive unfunded addresses after which an account is not scanned further (BIP44 uses 20).
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Vec<XDerivedWallet> with the funded wallets, by account and index, wrapped in a Result (Result<Vec<XDerivedWallet>, XpringError>).
+    /// Accounts are scanned from 0 and the scan stops at the first account without funded
+    /// addresses. An address counts as funded when `get_account_info` finds it in the ledger,
+    /// so an account that was deleted counts as unfunded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::Xrpl;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", true)?;
+    /// let funded = xrpl.discover_wallets(
+    ///     "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+    ///     20,
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn discover_wallets<S: Into<Mnemonic>>(
+        &mut self,
+        mnemonic: S,
+        gap_limit: u32,
+    ) -> Vec<XDerivedWallet> {
+        self.xrplclient.discover_wallets(
+            &self.pool.jscontext,
+            &mnemonic.into(),
+            gap_limit,
+            self.pool.test,
+        )?
+    }
+}
+
+/// The XrplPool struct runs the offline methods (wallets, signing and address utilities) on a
+/// pool of xpring.js workers. Each `Xrpl` has one, see `Xrpl::pool`. It can be cloned and
+/// shared between threads: every call is served by an idle worker, so calls from different
+/// threads run in parallel.
+///
+/// # Example
+///
+/// ```
+/// # use xpring::XrplPool;
+/// # fn main() -> Result<(), xpring::error::XpringError> {
+/// let pool = XrplPool::new(4, true)?;
+/// let threads: Vec<_> = (0..8)
+///     .map(|_| {
+///         let pool = pool.clone();
+///         std::thread::spawn(move || pool.validate_address("rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1"))
+///     })
+///     .collect();
+/// for thread in threads {
+///     assert!(thread.join().unwrap()?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct XrplPool {
+    pub(crate) jscontext: JavaScript,
+    pub(crate) test: bool,
+}
+
+impl XrplPool {
+    /// Creates a pool of `workers` xpring.js processes. Processes are started when calls need
+    /// them.
+    ///
+    /// # Arguments
+    ///
+    /// * `workers` -  `usize` Maximum number of node.js processes.
+    /// * `test` -  `bool` true for TestNet, false for MainNet.
+    #[throws(_)]
+    pub fn new(workers: usize, test: bool) -> XrplPool {
+        XrplPool::with_config(workers, XrplConfig::new().test(test))?
+    }
+
+    /// Same as `XrplPool::new`, with the settings of `config`.
+    #[throws(_)]
+    pub fn with_config(workers: usize, config: &XrplConfig) -> XrplPool {
+        XrplPool {
+            jscontext: start_javascript(config, workers)?,
+            test: config.test,
+        }
+    }
+
+    /// Changes how the node.js processes behind xpring.js are supervised: how long calls may
+    /// take and how often a process is restarted when it dies. It applies to every worker.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` -  `JsBridgeOptions` Start and call timeouts, and restart limit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::{JsBridgeOptions, XrplPool};
+    /// # use std::time::Duration;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, false)?;
+    /// pool.set_js_bridge_options(JsBridgeOptions {
+    ///     call_timeout: Duration::from_secs(5),
+    ///     ..Default::default()
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_js_bridge_options(&self, options: JsBridgeOptions) {
+        self.jscontext.set_options(options);
+    }
+
+    /// Generates a random wallet. An Entropy can be passed for generation but it is optional.
+    ///
+    /// # Arguments
+    ///
+    /// * `entropy` -  `Option<String>` (Optional) Entropy.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XWalletGenerationResult with the generated wallet wrapped in a Result (Result<XWalletGenerationResult, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::XrplPool;
+    /// # use xpring::wallet::{XWalletGenerationResult};
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, false)?;
+    /// let random_wallet = pool.generate_random_wallet(None)?;
+    /// # Ok(())
+    /// # }
+    ///
+    /// // {
+    /// //   wallet:
+    /// //     XWallet {
+    /// //        public_key: "029D92AA16B71AB5EBADFD7A911C7CF8253C86BABFD7C6CB6A5587FCE20D26C5F0",
+    /// //        private_key: PrivateKey(<redacted>),
+    /// //        test: false,
+    /// //        address: Some("XVesH3RwNwJ3bpAcVh54A2TxaVyyyomhErvVhfjHvrA3z2h")
+    /// //     },
+    /// //     mnemonic: Mnemonic(<redacted>),
+    /// //     derivation_path: "m/44\'/144\'/0\'/0/0"
+    /// // }
+    /// ```
+    #[throws(_)]
+    pub fn generate_random_wallet<S: Into<Option<String>>>(
+        &self,
+        entropy: S,
+    ) -> XWalletGenerationResult {
+        wallet::generate_random(&self.jscontext, entropy.into(), self.test)?
+    }
+
+    /// Generates a wallet from a mnemonic (and derivation path).
+    ///
+    /// # Arguments
+    ///
+    /// * `mnemonic` -  `Option<String>` Mnemonic.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XWaller with the generated wallet wrapped in a Result (Result<XWallet, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::XrplPool;
+    /// # use xpring::wallet::{XWallet};
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, true)?;
+    /// let wallet_from_mnemonic = pool.wallet_from_mnemonic(
+    ///     "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+    ///     Some("m/44'/144'/0'/0/1")
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// // XWallet {
+    /// //  public_key: "038BF420B5271ADA2D7479358FF98A29954CF18DC25155184AEAD05796DA737E89",
+    /// //  private_key: PrivateKey(<redacted>),
+    /// //  test: true,
+    /// //  address: Some("T7FxQEtaiNkq6ELhqGk3Pz2ov5aEoaGo6V642R74aaywJNT")
+    /// // }
+    /// ```
+    #[throws(_)]
+    pub fn wallet_from_mnemonic<S: Into<String>>(
+        &self,
+        mnemonic: S,
+        derivation_path: Option<&str>,
+    ) -> XWallet {
+        wallet::from_mnemonic(
+            &self.jscontext,
+            mnemonic.into(),
+            derivation_path.map(str::to_owned),
+            self.test,
+        )?
+    }
+
+    /// Generates a wallet from a seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` -  `String` Seed
+    /// * `derivation_path` - `Option<String>` (Optional) Derivation path.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XWallet with the generated wallet wrapped in a Result (Result<XWallet, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::XrplPool;
+    /// # use xpring::wallet::{XWallet};
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, true)?;
+    /// let wallet_from_seed =
+    ///     pool.wallet_from_seed("snYP7oArxKepd3GPDcrjMsJYiJeJB", None)?;
+    /// # Ok(())
+    /// # }
+    ///
+    /// // XWallet {
+    /// //  public_key: "038BF420B5271ADA2D7479358FF98A29954CF18DC25155184AEAD05796DA737E89",
+    /// //  private_key: PrivateKey(<redacted>),
+    /// //  test: true,
+    /// //  address: Some("T7FxQEtaiNkq6ELhqGk3Pz2ov5aEoaGo6V642R74aaywJNT")
+    /// // }
+    /// ```
+    #[throws(_)]
+    pub fn wallet_from_seed<S: Into<String>>(
+        &self,
+        seed: S,
+        derivation_path: Option<&str>,
+    ) -> XWallet {
+        wallet::from_seed(
+            &self.jscontext,
+            seed.into(),
+            derivation_path.map(str::to_owned),
+            self.test,
+        )?
+    }
+
+    /// Generates a random wallet from a new family seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - `KeyAlgorithm` Algorithm of the key pair.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XSeedWalletGenerationResult with the wallet and its seed wrapped in a Result (Result<XSeedWalletGenerationResult, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::XrplPool;
+    /// # use xpring::wallet::KeyAlgorithm;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, true)?;
+    /// let generated = pool.generate_random_seed_wallet(KeyAlgorithm::Secp256k1)?;
+    /// let again = pool.wallet_from_seed(generated.seed.expose_secret(), None)?;
+    /// assert_eq!(again, generated.wallet);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn generate_random_seed_wallet(
+        &self,
+        algorithm: KeyAlgorithm,
+    ) -> XSeedWalletGenerationResult {
+        wallet::generate_random_from_seed(&self.jscontext, algorithm, self.test)?
+    }
+
+    /// Generates a wallet from the 16 bytes of entropy of a family seed. It is the wallet of
+    /// `XrplPool::seed_from_entropy` with the same arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `entropy` - `&[u8; 16]` Entropy.
+    /// * `algorithm` - `KeyAlgorithm` Algorithm of the key pair.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XWallet with the generated wallet wrapped in a Result (Result<XWallet, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::XrplPool;
+    /// # use xpring::wallet::KeyAlgorithm;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, true)?;
+    /// let wallet = pool.wallet_from_entropy(&[0; 16], KeyAlgorithm::Secp256k1)?;
+    /// # Ok(())
+    /// # }
+    ///
+    /// // XWallet {
+    /// //  public_key: "0390A196799EE412284A5D80BF78C3E84CBB80E1437A0AECD9ADF94D7FEAAFA284",
+    /// //  private_key: PrivateKey(<redacted>),
+    /// //  test: true,
+    /// //  address: Some("TVG3TcCD58BD6MZqsNuTihdrhZwR8SzvYS8U87zvHsAcNw4")
+    /// // }
+    /// ```
+    #[throws(_)]
+    pub fn wallet_from_entropy(&self, entropy: &[u8; 16], algorithm: KeyAlgorithm) -> XWallet {
+        wallet::from_entropy(&self.jscontext, entropy, algorithm, self.test)?
+    }
+
+    /// Generates a wallet from its private key.
+    ///
+    /// # Arguments
+    ///
+    /// * `private_key` - `String` Hex encoded private key: 33 bytes prefixed with `00` (secp256k1) or `ED` (ed25519), or a bare 32 byte secp256k1 key.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XWallet with the generated wallet wrapped in a Result (Result<XWallet, XpringError>).
+    /// Its private key is always in the prefixed form, so a wallet generated from the private key
+    /// of another wallet is equal to it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::XrplPool;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, true)?;
+    /// let wallet = pool.wallet_from_private_key(
+    ///     "0090802A50AA84EFB6CDB225F17C27616EA94048C179142FECF03F4712A07EA7A4",
+    /// )?;
+    /// # Ok(())
+    /// # }
+    ///
+    /// // XWallet {
+    /// //  public_key: "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE",
+    /// //  private_key: PrivateKey(<redacted>),
+    /// //  test: true,
+    /// //  address: Some("TVHLFWLKvbMv1LFzd6FA2Bf9MPpcy4mRto4VFAAxLuNpvdW")
+    /// // }
+    /// ```
+    #[throws(_)]
+    pub fn wallet_from_private_key<S: Into<String>>(&self, private_key: S) -> XWallet {
+        wallet::from_private_key(&self.jscontext, private_key.into(), self.test)?
+    }
+
+    /// Encodes 16 bytes of entropy as a family seed (`s...`).
+    ///
+    /// # Arguments
+    ///
+    /// * `entropy` - `&[u8; 16]` Entropy.
+    /// * `algorithm` - `KeyAlgorithm` Algorithm of the key pair derived from the seed.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Seed wrapped in a Result (Result<Seed, XpringError>). `XrplPool::entropy_from_seed`
+    /// gives back the entropy and algorithm.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::XrplPool;
+    /// # use xpring::wallet::KeyAlgorithm;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, true)?;
+    /// let seed = pool.seed_from_entropy(&[0; 16], KeyAlgorithm::Secp256k1)?;
+    /// assert_eq!(seed.expose_secret(), "sp6JS7f14BuwFY8Mw6bTtLKWauoUs");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn seed_from_entropy(&self, entropy: &[u8; 16], algorithm: KeyAlgorithm) -> Seed {
+        wallet::seed_from_entropy(&self.jscontext, Some(entropy), algorithm)?
+    }
+
+    /// Decodes a family seed (`s...`) into its entropy and key algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - `Seed` Family seed.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XSeedEntropy wrapped in a Result (Result<XSeedEntropy, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::XrplPool;
+    /// # use xpring::wallet::KeyAlgorithm;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, true)?;
+    /// let decoded = pool.entropy_from_seed("sEdSJHS4oiAdz7w2X2ni1gFiqtbJHqE")?;
+    /// assert_eq!(*decoded.entropy, [0; 16]);
+    /// assert_eq!(decoded.algorithm, KeyAlgorithm::Ed25519);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn entropy_from_seed<S: Into<Seed>>(&self, seed: S) -> XSeedEntropy {
+        wallet::entropy_from_seed(&self.jscontext, &seed.into())?
+    }
+
+    /// Splits the entropy of a family seed into SLIP-39 mnemonic shares, any `threshold` of
+    /// which recover the seed with `XrplPool::recover_seed`.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - `Seed` Family seed.
+    /// * `threshold` - `u8` Shares needed to recover the seed, 1 only for a single share.
+    /// * `share_count` - `u8` Shares to create, at most 16.
+    /// * `passphrase` - `&str` Printable ASCII passphrase the entropy is encrypted with, or "".
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Vec<Share> wrapped in a Result (Result<Vec<Share>, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::XrplPool;
+    /// # use xpring::wallet::KeyAlgorithm;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, true)?;
+    /// let shares = pool.split_seed("sp6JS7f14BuwFY8Mw6bTtLKWauoUs", 2, 3, "")?;
+    /// let seed = pool.recover_seed(&shares[1..], "", KeyAlgorithm::Secp256k1)?;
+    /// assert_eq!(seed.expose_secret(), "sp6JS7f14BuwFY8Mw6bTtLKWauoUs");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn split_seed<S: Into<Seed>>(
+        &self,
+        seed: S,
+        threshold: u8,
+        share_count: u8,
+        passphrase: &str,
+    ) -> Vec<Share> {
+        shamir::split_seed(
+            &self.jscontext,
+            &seed.into(),
+            threshold,
+            share_count,
+            passphrase,
+        )?
+    }
+
+    /// Recovers a family seed from SLIP-39 mnemonic shares of its entropy.
+    ///
+    /// # Arguments
+    ///
+    /// * `shares` - `&[Share]` Shares, at least as many as the threshold.
+    /// * `passphrase` - `&str` Passphrase of the shares. Another passphrase gives another seed.
+    /// * `algorithm` - `KeyAlgorithm` Key algorithm of the seed, which shares do not record.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Seed wrapped in a Result (Result<Seed, XpringError>).
+    #[throws(_)]
+    pub fn recover_seed(
+        &self,
+        shares: &[Share],
+        passphrase: &str,
+        algorithm: KeyAlgorithm,
+    ) -> Seed {
+        shamir::recover_seed(&self.jscontext, shares, passphrase, algorithm)?
+    }
+
+    /// Splits the entropy of a BIP39 mnemonic, such as the one of a `XWalletGenerationResult`,
+    /// into SLIP-39 mnemonic shares, any `threshold` of which recover the mnemonic with
+    /// `XrplPool::recover_mnemonic`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mnemonic` - `Mnemonic` BIP39 mnemonic of 12 or 24 words.
+    /// * `threshold` - `u8` Shares needed to recover the mnemonic, 1 only for a single share.
+    /// * `share_count` - `u8` Shares to create, at most 16.
+    /// * `passphrase` - `&str` Printable ASCII passphrase the entropy is encrypted with, or "".
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Vec<Share> wrapped in a Result (Result<Vec<Share>, XpringError>).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::XrplPool;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, true)?;
+    /// let generated = pool.generate_random_wallet(None)?;
+    /// let shares = pool.split_mnemonic(generated.mnemonic.clone(), 3, 5, "")?;
+    /// let mnemonic = pool.recover_mnemonic(&[shares[4].clone(), shares[0].clone(), shares[2].clone()], "")?;
+    /// assert_eq!(mnemonic, generated.mnemonic);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn split_mnemonic<S: Into<Mnemonic>>(
+        &self,
+        mnemonic: S,
+        threshold: u8,
+        share_count: u8,
+        passphrase: &str,
+    ) -> Vec<Share> {
+        shamir::split_mnemonic(
+            &self.jscontext,
+            &mnemonic.into(),
+            threshold,
+            share_count,
+            passphrase,
+        )?
+    }
+
+    /// Recovers a BIP39 mnemonic from SLIP-39 mnemonic shares of its entropy. Its wallets are
+    /// derived as usual, e.g. with `XrplPool::wallet_from_mnemonic`.
+    ///
+    /// # Arguments
+    ///
+    /// * `shares` - `&[Share]` Shares, at least as many as the threshold.
+    /// * `passphrase` - `&str` Passphrase of the shares. Another passphrase gives another mnemonic.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Mnemonic wrapped in a Result (Result<Mnemonic, XpringError>).
+    #[throws(_)]
+    pub fn recover_mnemonic(&self, shares: &[Share], passphrase: &str) -> Mnemonic {
+        shamir::recover_mnemonic(&self.jscontext, shares, passphrase)?
+    }
+
+    /// Derives the wallets of a mnemonic at `m/44'/144'/account'/0/index` for a range of
+    /// address indexes.
+    ///
+    /// # Arguments
+    ///
+    /// * `mnemonic` - `Mnemonic` Mnemonic.
+    /// * `account` - `u32` Account index.
+    /// * `indexes` - `Range<u32>` Address indexes.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a Vec<XDerivedWallet> with a wallet per index, in order, wrapped in a Result (Result<Vec<XDerivedWallet>, XpringError>).
+    /// The mnemonic is only turned into a seed once, so this is much faster than a call to
+    /// `XrplPool::wallet_from_mnemonic` per index.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use xpring::XrplPool;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, true)?;
+    /// let wallets = pool.derive_wallets(
+    ///     "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+    ///     0,
+    ///     0..100,
+    /// )?;
+    /// assert_eq!(wallets[1].derivation_path, "m/44'/144'/0'/0/1");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[throws(_)]
+    pub fn derive_wallets<S: Into<Mnemonic>>(
+        &self,
+        mnemonic: S,
+        account: u32,
+        indexes: Range<u32>,
+    ) -> Vec<XDerivedWallet> {
+        wallet::derive_range(
+            &self.jscontext,
+            &mnemonic.into(),
+            account,
+            indexes,
+            self.test,
+        )?
+    }
+
+    /// Creates a watch-only wallet from a public key.
     ///
     /// # Arguments
     ///
-    /// * `xrplclient_url` -  `&str` Url for the XRP Ledger node.
-    /// * `test` -  `bool` true for TestNet, false for MainNet.
+    /// * `public_key` - `&str` Hex encoded secp256k1 or ed25519 (`ED...`) public key.
     ///
     /// # Remarks
     ///
-    /// Returns a Xpring struct wrapped in a Result (Result<Xpring, anyhow::Error>).
+    /// Returns a XWatchOnlyWallet wrapped in a Result (Result<XWatchOnlyWallet, XpringError>).
+    /// A watch-only wallet has no private key: its address is used for balances and history, and
+    /// `Xrpl::prepare_payment` builds its payments, which the full wallet signs offline.
     ///
     /// # Example
     ///
     /// ```
-    /// # use xpring::Xrpl;
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// let mut xpring =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
+    /// # use xpring::XrplPool;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, true)?;
+    /// let watch_only = pool.watch_only_wallet_from_public_key(
+    ///     "031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE",
+    /// )?;
+    /// assert_eq!(watch_only.classic_address, "rHsMGQEkVNJmpGWs8XUBoTBiAAbwxZN5v3");
     /// # Ok(())
     /// # }
     /// ```
     #[throws(_)]
-    pub fn new<S: Into<String>>(xrplclient_url: S, test: bool) -> Xrpl {
-        let xrpljs_path = copy_js_to_exec_path()?;
-        set_vars()?;
-        Xrpl {
-            jscontext: JavaScript::new(xrpljs_path)?,
-            xrplclient: XrplClient::connect(xrplclient_url.into())?,
-            test,
-        }
+    pub fn watch_only_wallet_from_public_key(&self, public_key: &str) -> XWatchOnlyWallet {
+        wallet::watch_only_from_public_key(&self.jscontext, public_key.to_owned(), self.test)?
     }
 
-    // Wallet
-
-    /// Generates a random wallet. An Entropy can be passed for generation but it is optional.
+    /// Creates a watch-only wallet from an address.
     ///
     /// # Arguments
     ///
-    /// * `entropy` -  `Option<String>` (Optional) Entropy.
+    /// * `address` - `&str` X-Address without a tag, or classic address.
     ///
     /// # Remarks
     ///
-    /// Returns a XWalletGenerationResult with the generated wallet wrapped in a Result (Result<XWalletGenerationResult, anyhow::Error>).
+    /// Returns a XWatchOnlyWallet wrapped in a Result (Result<XWatchOnlyWallet, XpringError>).
+    /// The wallet does not know its public key, so any key of the account can sign the payments
+    /// it prepares. An X-Address must be for the network of this pool.
     ///
     /// # Example
     ///
     /// ```
     /// # use xpring::Xrpl;
-    /// # use xpring::wallet::{XWalletGenerationResult};
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xpring =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
-    /// let random_wallet = xpring.generate_random_wallet(None)?;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", true)?;
+    /// let watch_only = xrpl
+    ///     .pool()
+    ///     .watch_only_wallet_from_address("rHsMGQEkVNJmpGWs8XUBoTBiAAbwxZN5v3")?;
+    /// let balance = xrpl.get_balance(&watch_only.address);
     /// # Ok(())
     /// # }
-    ///
-    /// // {
-    /// //   wallet:
-    /// //     XWallet {
-    /// //        public_key: "029D92AA16B71AB5EBADFD7A911C7CF8253C86BABFD7C6CB6A5587FCE20D26C5F0",
-    /// //        private_key: "0006039508EB1F0BDDD511276BB8E08CDC00426992840F20083DF8E81E0AD84270",
-    /// //        test: false,
-    /// //        address: Some("XVesH3RwNwJ3bpAcVh54A2TxaVyyyomhErvVhfjHvrA3z2h")
-    /// //     },
-    /// //     mnemonic: "notable dilemma fringe install chicken icon please aim era security utility atom",
-    /// //     derivation_path: "m/44\'/144\'/0\'/0/0"
-    /// // }
     /// ```
     #[throws(_)]
-    pub fn generate_random_wallet<S: Into<Option<String>>>(
-        &mut self,
-        entropy: S,
-    ) -> XWalletGenerationResult {
-        wallet::generate_random(&mut self.jscontext, entropy.into(), self.test)?
+    pub fn watch_only_wallet_from_address(&self, address: &str) -> XWatchOnlyWallet {
+        wallet::watch_only_from_address(&self.jscontext, address, self.test)?
     }
 
-    /// Generates a wallet from a mnemonic (and derivation path).
+    /// Signs a prepared payment, without a network connection.
     ///
     /// # Arguments
     ///
-    /// * `mnemonic` -  `Option<String>` Mnemonic.
+    /// * `unsigned` - `&XUnsignedTransaction` Payment prepared with `Xrpl::prepare_payment`.
+    /// * `wallet` - `&XWallet` Wallet that signs the payment.
     ///
     /// # Remarks
     ///
-    /// Returns a XWaller with the generated wallet wrapped in a Result (Result<XWallet, anyhow::Error>).
+    /// Returns a XSignedTransaction wrapped in a Result (Result<XSignedTransaction, XpringError>).
+    /// If the payment was prepared for a public key, `wallet` must have that key, and the wallet
+    /// must be for the network of the payment. Fees above `transaction::MAX_OFFLINE_FEE` drops
+    /// are refused. `XSignedTransaction::to_json` writes the result for
+    /// `Xrpl::submit_transaction`.
     ///
     /// # Example
     ///
     /// ```
-    /// # use xpring::Xrpl;
-    /// # use xpring::wallet::{XWallet};
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", true)?;
-    /// let wallet_from_mnemonic = xrpl.wallet_from_mnemonic(
-    ///     "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
-    ///     Some("m/44'/144'/0'/0/1")
-    /// )?;
+    /// # use xpring::XrplPool;
+    /// # use xpring::transaction::{XPayment, XUnsignedTransaction};
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, false)?;
+    /// let wallet = pool.wallet_from_seed("sn3UJSLzAEeAGcrK3nsQTDZW6KT92", None)?;
+    /// let unsigned = XUnsignedTransaction {
+    ///     payment: XPayment::new(
+    ///         12.12,
+    ///         "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+    ///         "rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7",
+    ///     ),
+    ///     fee: 12,
+    ///     sequence: 1,
+    ///     last_ledger_sequence: 1000,
+    ///     signing_public_key: None,
+    ///     test: false,
+    /// };
+    /// let signed = pool.sign_transaction(&unsigned, &wallet)?;
     /// # Ok(())
     /// # }
-    /// // XWallet {
-    /// //  public_key: "038BF420B5271ADA2D7479358FF98A29954CF18DC25155184AEAD05796DA737E89",
-    /// //  private_key: "000974B4CFE004A2E6C4364CBF3510A36A352796728D0861F6B555ED7E54A70389",
-    /// //  test: true,
-    /// //  address: Some("T7FxQEtaiNkq6ELhqGk3Pz2ov5aEoaGo6V642R74aaywJNT")
-    /// // }
     /// ```
     #[throws(_)]
-    pub fn wallet_from_mnemonic<S: Into<String>>(
-        &mut self,
-        mnemonic: S,
-        derivation_path: Option<&str>,
-    ) -> XWallet {
-        let derivation_path = if derivation_path.is_some() {
-            Some(derivation_path.unwrap().to_owned())
-        } else {
-            None
-        };
-        wallet::from_mnemonic(
-            &mut self.jscontext,
-            mnemonic.into(),
-            derivation_path,
-            self.test,
-        )?
+    pub fn sign_transaction(
+        &self,
+        unsigned: &XUnsignedTransaction,
+        wallet: &XWallet,
+    ) -> XSignedTransaction {
+        transaction::sign_unsigned_transaction(&self.jscontext, unsigned, wallet)?
     }
 
-    /// Generates a wallet from a seed.
+    /// Same as `Xrpl::generate_vanity_wallet`, searching on every worker of the pool.
+    #[throws(_)]
+    pub fn generate_vanity_wallet(
+        &self,
+        pattern: &XVanityPattern,
+        algorithm: KeyAlgorithm,
+        max_attempts: Option<u64>,
+    ) -> XVanityWallet {
+        vanity::search(&self.jscontext, pattern, algorithm, max_attempts, self.test)?
+    }
+
+    /// Issues a challenge to prove the ownership of an address, for a client to answer with
+    /// `XrplPool::prove_ownership`. Keep it to verify the answer and accept it only once.
     ///
     /// # Arguments
     ///
-    /// * `seed` -  `String` Seed
-    /// * `derivation_path` - `Option<String>` (Optional) Derivation path.
+    /// * `address` - `&str` Address claimed by the client, classic or X-Address.
+    /// * `domain` - `&str` Signing domain of the server, such as its host name.
+    /// * `lifetime` - `Duration` Time the client has to answer.
     ///
     /// # Remarks
     ///
-    /// Returns a XWallet with the generated wallet wrapped in a Result (Result<XWallet, anyhow::Error>).
+    /// Returns a XOwnershipChallenge wrapped in a Result (Result<XOwnershipChallenge, XpringError>).
     ///
     /// # Example
     ///
     /// ```
     /// # use xpring::Xrpl;
-    /// # use xpring::wallet::{XWallet};
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xprl =  Xrpl::new("http://test.xrp.xpring.io:50051", true)?;
-    /// let wallet_from_seed =
-    ///     xprl.wallet_from_seed("snYP7oArxKepd3GPDcrjMsJYiJeJB", None)?;
+    /// # use std::time::Duration;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", true)?;
+    /// let challenge = xrpl.pool().issue_ownership_challenge(
+    ///     "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+    ///     "example.com",
+    ///     Duration::from_secs(300),
+    /// )?;
+    /// let wallet = xrpl.pool().wallet_from_seed("sn3UJSLzAEeAGcrK3nsQTDZW6KT92", None)?;
+    /// let proof = xrpl.pool().prove_ownership(&challenge, &wallet)?;
+    /// assert!(xrpl.verify_ownership(&proof, &challenge)?.is_verified());
     /// # Ok(())
     /// # }
-    ///
-    /// // XWallet {
-    /// //  public_key: "038BF420B5271ADA2D7479358FF98A29954CF18DC25155184AEAD05796DA737E89",
-    /// //  private_key: "000974B4CFE004A2E6C4364CBF3510A36A352796728D0861F6B555ED7E54A70389",
-    /// //  test: true,
-    /// //  address: Some("T7FxQEtaiNkq6ELhqGk3Pz2ov5aEoaGo6V642R74aaywJNT")
-    /// // }
     /// ```
     #[throws(_)]
-    pub fn wallet_from_seed<S: Into<String>>(
-        &mut self,
-        seed: S,
-        derivation_path: Option<&str>,
-    ) -> XWallet {
-        let derivation_path = if derivation_path.is_some() {
-            Some(derivation_path.unwrap().to_owned())
-        } else {
-            None
-        };
-        wallet::from_seed(&mut self.jscontext, seed.into(), derivation_path, self.test)?
+    pub fn issue_ownership_challenge(
+        &self,
+        address: &str,
+        domain: &str,
+        lifetime: Duration,
+    ) -> XOwnershipChallenge {
+        challenge::issue(&self.jscontext, address, domain, lifetime, self.test)?
+    }
+
+    /// Answers a challenge of `XrplPool::issue_ownership_challenge` with a wallet: the one of the
+    /// address, or the regular key of its account.
+    ///
+    /// # Arguments
+    ///
+    /// * `challenge` - `&XOwnershipChallenge` Challenge received from the server.
+    /// * `wallet` - `&XWallet` Wallet that signs the challenge.
+    ///
+    /// # Remarks
+    ///
+    /// Returns a XOwnershipProof wrapped in a Result (Result<XOwnershipProof, XpringError>).
+    #[throws(_)]
+    pub fn prove_ownership(
+        &self,
+        challenge: &XOwnershipChallenge,
+        wallet: &XWallet,
+    ) -> XOwnershipProof {
+        challenge::prove(&self.jscontext, challenge, wallet)?
     }
 
     /// Signs a message with a private key.
@@ -208,15 +1475,15 @@ impl Xrpl {
     ///
     /// # Remarks
     ///
-    /// Returns a String with the signed message wrapped in a Result (Result<String, anyhow::Error>).
+    /// Returns a String with the signed message wrapped in a Result (Result<String, XpringError>).
     ///
     /// # Example
     ///
     /// ```
-    /// # use xpring::Xrpl;
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
-    /// let signed_message = xrpl.wallet_sign(
+    /// # use xpring::XrplPool;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, false)?;
+    /// let signed_message = pool.wallet_sign(
     ///     "mymessage",
     ///     "000974B4CFE004A2E6C4364CBF3510A36A352796728D0861F6B555ED7E54A70389",
     /// )?;
@@ -226,8 +1493,8 @@ impl Xrpl {
     /// // "3045022100DD88E31FF9AFD2A6DA48D40C4B4E8F11725E11C9D9E52388710E35ED19212EF6022068CFA9C09071322751C11DD21E89088879DC28B3B683D3F863090FB7C331EC32"
     /// ```
     #[throws(_)]
-    pub fn wallet_sign<S: Into<String>>(&mut self, message: S, private_key: S) -> String {
-        wallet::sign(&mut self.jscontext, message.into(), private_key.into())?
+    pub fn wallet_sign<S: Into<String>>(&self, message: S, private_key: S) -> String {
+        wallet::sign(&self.jscontext, message.into(), private_key.into())?
     }
 
     /// Verifies with a public key a signed message.
@@ -240,15 +1507,15 @@ impl Xrpl {
     ///
     /// # Remarks
     ///
-    /// Returns a bool, true if verification is successful, false if not, wrapped in a Result (Result<bool, anyhow::Error>).
+    /// Returns a bool, true if verification is successful, false if not, wrapped in a Result (Result<bool, XpringError>).
     ///
     /// # Example
     ///
     /// ```
-    /// # use xpring::Xrpl;
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
-    /// let message_verification_result = xrpl.wallet_verify(
+    /// # use xpring::XrplPool;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, false)?;
+    /// let message_verification_result = pool.wallet_verify(
     ///     "mymessage",
     ///     "3045022100DD88E31FF9AFD2A6DA48D40C4B4E8F11725E11C9D9E52388710E35ED19212EF6022068CFA9C09071322751C11DD21E89088879DC28B3B683D3F863090FB7C331EC32",
     ///     "038BF420B5271ADA2D7479358FF98A29954CF18DC25155184AEAD05796DA737E89"
@@ -259,289 +1526,279 @@ impl Xrpl {
     /// // true
     /// ```
     #[throws(_)]
-    pub fn wallet_verify<S: Into<String>>(
-        &mut self,
-        message: S,
-        signature: S,
-        public_key: S,
-    ) -> bool {
+    pub fn wallet_verify<S: Into<String>>(&self, message: S, signature: S, public_key: S) -> bool {
         wallet::verify(
-            &mut self.jscontext,
+            &self.jscontext,
             message.into(),
             signature.into(),
             public_key.into(),
         )?
     }
 
-    // Util
-
-    /// Validates an address (X or Classic).
+    /// Signs a message of raw bytes within a signing domain. The signature is over the
+    /// SHA-512Half of a message hash prefix, the domain and the message (see
+    /// `message::message_digest`), so it cannot be replayed as a transaction signature or in
+    /// another domain.
     ///
     /// # Arguments
     ///
-    /// * `address` -  `&str` Address.
+    /// * `message` - `&[u8]` Message to be signed.
+    /// * `domain` - `&str` Application and purpose of the signature, at most 255 bytes.
+    /// * `private_key` - `&PrivateKey` Private key, with or without its `00` or `ED` prefix.
+    /// * `algorithm` - `KeyAlgorithm` Algorithm of the private key.
     ///
     /// # Remarks
     ///
-    /// Returns a bool, true if verification is successful, false if not, wrapped in a Result (Result<bool, anyhow::Error>).
+    /// Returns a XMessageSignature wrapped in a Result (Result<XMessageSignature, XpringError>).
+    /// secp256k1 signatures are DER encoded and low-S.
     ///
     /// # Example
     ///
     /// ```
-    /// # use xpring::Xrpl;
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
-    /// let is_address_valid =
-    ///     xrpl.validate_address("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
+    /// # use xpring::XrplPool;
+    /// # use xpring::secret::PrivateKey;
+    /// # use xpring::wallet::KeyAlgorithm;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, false)?;
+    /// let key = PrivateKey::from("000974B4CFE004A2E6C4364CBF3510A36A352796728D0861F6B555ED7E54A70389");
+    /// let signed = pool.sign_message(b"hello", "example.com login", &key, KeyAlgorithm::Secp256k1)?;
+    /// let verification = pool.verify_message(
+    ///     b"hello",
+    ///     "example.com login",
+    ///     &signed.signature,
+    ///     &signed.public_key,
+    /// )?;
+    /// assert!(verification.is_valid());
     /// # Ok(())
     /// # }
-    ///
-    /// // true
     /// ```
     #[throws(_)]
-    pub fn validate_address(&mut self, address: &str) -> bool {
-        util::is_valid_address(&mut self.jscontext, address.into())?
+    pub fn sign_message(
+        &self,
+        message: &[u8],
+        domain: &str,
+        private_key: &PrivateKey,
+        algorithm: KeyAlgorithm,
+    ) -> XMessageSignature {
+        message::sign_message(&self.jscontext, message, domain, private_key, algorithm)?
     }
 
-    /// Validates an X-Address
+    /// Signs a 32 byte digest computed by the caller, without hashing it again. ed25519 signs
+    /// the digest as its message.
     ///
     /// # Arguments
     ///
-    /// * `x_address` -  `&str` X-Address
+    /// * `digest` - `&[u8; 32]` Digest to be signed.
+    /// * `private_key` - `&PrivateKey` Private key, with or without its `00` or `ED` prefix.
+    /// * `algorithm` - `KeyAlgorithm` Algorithm of the private key.
     ///
     /// # Remarks
     ///
-    /// Returns a bool, true if verification is successful, false if not, wrapped in a Result (Result<bool, anyhow::Error>).
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// # use xpring::Xrpl;
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
-    /// let is_address_valid =
-    ///     xrpl.validate_x_address("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
-    /// # Ok(())
-    /// # }
-    ///
-    /// // true
-    /// ```
+    /// Returns a XMessageSignature wrapped in a Result (Result<XMessageSignature, XpringError>).
     #[throws(_)]
-    pub fn validate_x_address(&mut self, x_address: &str) -> bool {
-        util::is_valid_x_address(&mut self.jscontext, x_address)?
+    pub fn sign_digest(
+        &self,
+        digest: &[u8; 32],
+        private_key: &PrivateKey,
+        algorithm: KeyAlgorithm,
+    ) -> XMessageSignature {
+        message::sign_digest(&self.jscontext, digest, private_key, algorithm)?
     }
 
-    /// Validates a Classic Address.
+    /// Verifies a signature of `XrplPool::sign_message`. The algorithm is given by the public key.
     ///
     /// # Arguments
     ///
-    /// * `address` -  `&str` Classic Address.
+    /// * `message` - `&[u8]` Signed message.
+    /// * `domain` - `&str` Signing domain of the message.
+    /// * `signature` - `&str` Hex signature, DER for secp256k1.
+    /// * `public_key` - `&str` Signer's public key.
     ///
     /// # Remarks
     ///
-    /// Returns a bool, true if verification is successful, false if not, wrapped in a Result (Result<bool, anyhow::Error>).
+    /// Returns a XSignatureVerification wrapped in a Result (Result<XSignatureVerification, XpringError>).
+    /// Malformed signatures and public keys are errors rather than mismatches.
+    #[throws(_)]
+    pub fn verify_message(
+        &self,
+        message: &[u8],
+        domain: &str,
+        signature: &str,
+        public_key: &str,
+    ) -> XSignatureVerification {
+        message::verify_message(&self.jscontext, message, domain, signature, public_key)?
+    }
+
+    /// Verifies a signature of `XrplPool::sign_digest`.
     ///
-    /// # Example
+    /// # Arguments
     ///
-    /// ```
-    /// # use xpring::Xrpl;
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
-    /// let is_address_valid =
-    ///     xrpl.validate_classic_address("rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1")?;
-    /// # Ok(())
-    /// # }
+    /// * `digest` - `&[u8; 32]` Signed digest.
+    /// * `signature` - `&str` Hex signature, DER for secp256k1.
+    /// * `public_key` - `&str` Signer's public key.
     ///
-    /// // true
-    /// ```
+    /// # Remarks
+    ///
+    /// Returns a XSignatureVerification wrapped in a Result (Result<XSignatureVerification, XpringError>).
     #[throws(_)]
-    pub fn validate_classic_address(&mut self, classic_address: &str) -> bool {
-        util::is_valid_classic_address(&mut self.jscontext, classic_address)?
+    pub fn verify_digest(
+        &self,
+        digest: &[u8; 32],
+        signature: &str,
+        public_key: &str,
+    ) -> XSignatureVerification {
+        message::verify_digest(&self.jscontext, digest, signature, public_key)?
     }
 
-    /// Encodes a Classic Address into a X-Address
+    /// Validates an address (X or Classic).
     ///
     /// # Arguments
     ///
-    /// * `classic_address` -  `&str` Classic Address
+    /// * `address` -  `&str` Address.
     ///
     /// # Remarks
     ///
-    /// Returns a String with the X-Address wrapped in a Result (Result<String, anyhow::Error>).
+    /// Returns a bool, true if verification is successful, false if not, wrapped in a Result (Result<bool, XpringError>).
     ///
     /// # Example
     ///
     /// ```
-    /// # use xpring::Xrpl;
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
-    /// let x_address =
-    ///     xrpl.encode_classic_address("rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1", Some(12345), None)?;
+    /// # use xpring::XrplPool;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, false)?;
+    /// let is_address_valid =
+    ///     pool.validate_address("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
     /// # Ok(())
-    ///
     /// # }
     ///
-    /// // "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUvtU3HnooQDgBnUpQT"
+    /// // true
     /// ```
     #[throws(_)]
-    pub fn encode_classic_address(
-        &mut self,
-        classic_address: &str,
-        tag: Option<u16>,
-        test: Option<bool>,
-    ) -> String {
-        util::encode_classic_address(&mut self.jscontext, classic_address, tag, test)?
+    pub fn validate_address(&self, address: &str) -> bool {
+        util::is_valid_address(&self.jscontext, address)?
     }
 
-    /// Decodes a X-Address into a Classic Address.x
+    /// Validates an X-Address
     ///
     /// # Arguments
     ///
-    /// * `x_address` -  `&str` X-Address.
+    /// * `x_address` -  `&str` X-Address
     ///
     /// # Remarks
     ///
-    /// Returns a XClassicAddress struct wrapped in a Result (Result<XClassicAddress, anyhow::Error>).
+    /// Returns a bool, true if verification is successful, false if not, wrapped in a Result (Result<bool, XpringError>).
     ///
     /// # Example
     ///
     /// ```
-    /// # use xpring::Xrpl;
-    /// # use xpring::address::XClassicAddress;
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
-    /// let classic_address =
-    ///     xrpl.decode_x_address("XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUvtU3HnooQDgBnUpQT")?;
+    /// # use xpring::XrplPool;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, false)?;
+    /// let is_address_valid =
+    ///     pool.validate_x_address("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
     /// # Ok(())
     /// # }
     ///
-    /// // {
-    /// //  address: "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1",
-    /// //  tag: Some(12345),
-    /// //  test: false
-    /// // }
+    /// // true
     /// ```
     #[throws(_)]
-    pub fn decode_x_address(&mut self, x_address: &str) -> XClassicAddress {
-        util::decode_x_address(&mut self.jscontext, x_address)?
+    pub fn validate_x_address(&self, x_address: &str) -> bool {
+        util::is_valid_x_address(&self.jscontext, x_address)?
     }
 
-    // XrplClient
-
-    /// Returns an account balance.
+    /// Validates a Classic Address.
     ///
     /// # Arguments
     ///
-    /// * `x_address` -  `&str` Account in x format.
+    /// * `address` -  `&str` Classic Address.
     ///
     /// # Remarks
     ///
-    /// Returns a f32 with the balance in decimal format wrapped in a Result (Result<f32, anyhow::Error> ).
+    /// Returns a bool, true if verification is successful, false if not, wrapped in a Result (Result<bool, XpringError>).
     ///
     /// # Example
     ///
     /// ```
-    /// # use xpring::Xrpl;
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
-    /// let balance = xrpl.get_balance("TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
+    /// # use xpring::XrplPool;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, false)?;
+    /// let is_address_valid =
+    ///     pool.validate_classic_address("rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1")?;
     /// # Ok(())
     /// # }
     ///
-    /// // 1000
+    /// // true
     /// ```
     #[throws(_)]
-    pub fn get_balance(&mut self, x_address: &str) -> f32 {
-        self.xrplclient
-            .get_balance(&mut self.jscontext, x_address)?
+    pub fn validate_classic_address(&self, classic_address: &str) -> bool {
+        util::is_valid_classic_address(&self.jscontext, classic_address)?
     }
 
-    /// Sends a payment from one account to another.
+    /// Encodes a Classic Address into a X-Address
     ///
     /// # Arguments
     ///
-    /// * `amount` -  `f32` Payment amount in decimal format (Ex. 10.32).
-    /// * `from_address` -  `&str` Origin account in x format.
-    /// * `to_address` -  `&str` Destination account in x format.
-    /// * `source_wallet` -  `XWallet` Wallet that will fund the payment and sign the transaction.
+    /// * `classic_address` -  `&str` Classic Address
     ///
     /// # Remarks
     ///
-    /// Returns a XrplReliableSendResponse wrapped in a Result (Result<XrplReliableSendResponse, anyhow::Error>).
+    /// Returns a String with the X-Address wrapped in a Result (Result<String, XpringError>).
     ///
     /// # Example
     ///
     /// ```
-    /// # use xpring::Xrpl;
-    /// # use xpring::xrplclient::{XrplReliableSendResponse};
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
-    /// let sending_wallet =
-    ///     xrpl.wallet_from_seed(
-    ///         "sn3UJSLzAEeAGcrK3nsQTDZW6KT92",
-    ///         None
-    ///     )?;
-    /// let payment = xrpl.send(
-    ///     12.12,
-    ///     "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
-    ///     "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
-    ///     sending_wallet,
-    /// )?;
-    ///
+    /// # use xpring::XrplPool;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, false)?;
+    /// let x_address =
+    ///     pool.encode_classic_address("rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1", Some(12345), None)?;
     /// # Ok(())
+    ///
     /// # }
     ///
-    /// // {
-    /// //  transaction_status: FAILED,
-    /// //  transaction_hash: "2E01FED358DDB9B843116D858695D8EF3285BA6C7A478D054E05AD20BD50857C",
-    /// //  transaction_info: "Insufficient XRP balance to send."
-    /// // }
+    /// // "XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUvtU3HnooQDgBnUpQT"
     /// ```
     #[throws(_)]
-    pub fn send(
-        &mut self,
-        amount: f32,
-        from_x_address: &str,
-        to_x_address: &str,
-        source_wallet: XWallet,
-    ) -> XrplReliableSendResponse {
-        self.xrplclient.send(
-            &mut self.jscontext,
-            amount,
-            from_x_address,
-            to_x_address,
-            source_wallet,
-        )?
+    pub fn encode_classic_address(
+        &self,
+        classic_address: &str,
+        tag: Option<u32>,
+        test: Option<bool>,
+    ) -> String {
+        util::encode_classic_address(&self.jscontext, classic_address, tag, test)?
     }
 
-    /// Returns a certain transaction status.
+    /// Decodes a X-Address into a Classic Address.x
     ///
     /// # Arguments
     ///
-    /// * `transaction_hash` -  `&str` Transaction hash.
+    /// * `x_address` -  `&str` X-Address.
     ///
     /// # Remarks
     ///
-    /// Returns a XTransactionStatus with the transaction status wrapped in a Result (Result<XTransactionStatus, anyhow::Error>).
+    /// Returns a XClassicAddress struct wrapped in a Result (Result<XClassicAddress, XpringError>).
     ///
     /// # Example
     ///
     /// ```
-    /// # use xpring::Xrpl;
-    /// # use xpring::transaction::XTransactionStatus;
-    /// # fn main() -> Result<(), anyhow::Error> {
-    /// # let mut xrpl =  Xrpl::new("http://test.xrp.xpring.io:50051", false)?;
-    /// let transaction_status = xrpl.get_transaction_status(
-    ///      "B171331C9B3D24ACF7ED35F9B6A63090D10048B56C8B187DE6EA71D0AB483086",
-    /// )?;
+    /// # use xpring::XrplPool;
+    /// # use xpring::address::XClassicAddress;
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
+    /// # let pool = XrplPool::new(1, false)?;
+    /// let classic_address =
+    ///     pool.decode_x_address("XVfC9CTCJh6GN2x8bnrw3LtdbqiVCUvtU3HnooQDgBnUpQT")?;
     /// # Ok(())
     /// # }
     ///
-    /// // FAILED
+    /// // {
+    /// //  address: "rU6K7V3Po4snVhBBaU29sesqs2qTQJWDw1",
+    /// //  tag: Some(12345),
+    /// //  test: false
+    /// // }
     /// ```
     #[throws(_)]
-    pub fn get_transaction_status(&mut self, transaction_hash: &str) -> XTransactionStatus {
-        self.xrplclient.get_transaction_status(transaction_hash)?
+    pub fn decode_x_address(&self, x_address: &str) -> XClassicAddress {
+        util::decode_x_address(&self.jscontext, x_address)?
     }
 }
 
@@ -552,7 +1809,7 @@ pub struct Ilp {
 
 impl Ilp {
     #[throws(_)]
-    pub fn new(ilpclient_url: &'static str, account_id: &'static str, token: &str) -> Ilp {
+    pub fn new(ilpclient_url: &str, account_id: &'static str, token: &str) -> Ilp {
         Ilp {
             ilpclient: IlpClient::connect(ilpclient_url, account_id, token)?,
         }
@@ -562,13 +1819,13 @@ impl Ilp {
     ///
     /// # Remarks
     ///
-    /// Returns a IlpBalanceResponse with the transaction status wrapped in a Result (Result<IlpBalanceResponse, anyhow::Error>).
+    /// Returns a IlpBalanceResponse with the transaction status wrapped in a Result (Result<IlpBalanceResponse, XpringError>).
     ///
     /// # Example
     ///
     /// ```
     /// # use xpring::Ilp;
-    /// # fn main() -> Result<(), anyhow::Error> {
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
     /// # let mut ilp = Ilp::new("http://hermes-grpc.ilpv4.dev", "sdk_account1", "password")?;
     /// let balance = ilp.get_balance()?;
     /// # Ok(())
@@ -592,13 +1849,13 @@ impl Ilp {
     ///
     /// # Remarks
     ///
-    /// Returns a IlpSendResponse with the transaction status wrapped in a Result (Result<IlpSendResponse, anyhow::Error>).
+    /// Returns a IlpSendResponse with the transaction status wrapped in a Result (Result<IlpSendResponse, XpringError>).
     ///
     /// # Example
     ///
     /// ```
     /// # use xpring::Ilp;
-    /// # fn main() -> Result<(), anyhow::Error> {
+    /// # fn main() -> Result<(), xpring::error::XpringError> {
     /// # let mut ilp = Ilp::new("http://hermes-grpc.ilpv4.dev", "sdk_account1", "password")?;
     /// let payment = ilp.send_to(
     ///         "$money.ilpv4.dev/sdk_account2",
@@ -1,37 +1,73 @@
 use crate::address;
+use crate::challenge::{
+    self, XOwnershipChallenge, XOwnershipKey, XOwnershipProof, XOwnershipVerification,
+    XSignedProof, LSF_DISABLE_MASTER,
+};
 use crate::config;
+use crate::error::{EngineResultCategory, XpringError as Error};
 use crate::javascript::JavaScript;
+use crate::ledger_object::{XAccountObjects, XAccountRoot, XLedgerObject, XSignerList};
+use crate::recording::Cassette;
+use crate::secret::Mnemonic;
 use crate::transaction;
-use crate::transaction::{XAmount, XPayment, XRawTransactionStatus, XTransactionStatus};
-use crate::wallet::XWallet;
+use crate::transaction::{
+    XCurrencyAmount, XIncomingPayment, XPayment, XRawTransactionStatus, XSignedTransaction,
+    XTransactionStatus, XUnsignedTransaction,
+};
+use crate::wallet::{self, XDerivedWallet, XWallet, XWatchOnlyWallet, MAX_DERIVATION_INDEX};
 use crate::x::prelude::*;
-use anyhow::{bail, Error};
-use fehler::throws;
+use crate::x::{affected_node, AffectedNode, GetAccountInfoResponse, LedgerObject};
+use fehler::{throw, throws};
 use hex;
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::str;
 use std::thread;
 use std::time::Duration;
 use tokio::runtime::{Builder, Runtime};
 
 type StdError = Box<dyn std::error::Error + Send + Sync + 'static>;
+type Client = XrpLedgerApiServiceClient<tonic::transport::Channel>;
 
 #[derive(PartialEq, Debug)]
 pub struct XrplReliableSendResponse {
     pub transaction_status: XTransactionStatus,
     pub transaction_hash: String,
     pub transaction_info: String,
+    pub delivered_amount: Option<XCurrencyAmount>,
 }
 
 pub(self) fn drops_to_decimal(drops: u64) -> f32 {
     drops as f32 / 1_000_000.
 }
 
+/// Number of addresses derived at once by account discovery, which bounds the size of the calls
+/// to xpring.js whatever the gap limit.
+#[cfg(not(test))]
+const DISCOVERY_BATCH: u32 = 20;
+// Makes the tests cross batches.
+#[cfg(test)]
+const DISCOVERY_BATCH: u32 = 2;
+
+/// Time between two polls of a submitted transaction, about the time it takes to close a ledger.
+#[cfg(not(test))]
+const LEDGER_CLOSE_TIME: Duration = Duration::from_secs(4);
+// Keeps the tests that poll several times fast.
+#[cfg(test)]
+const LEDGER_CLOSE_TIME: Duration = Duration::from_millis(10);
+
+/// Error for a field the node left out of its response.
+fn missing(field: &str) -> Error {
+    Error::Decode(format!("Malformed response: missing field `{}`", field))
+}
+
 // The order of the fields in this struct is important. The runtime must be the first field and the
 // client must be the last field so that when `BlockingClient` is dropped the client is dropped
 // before the runtime. Not doing this will result in a deadlock when dropped.
 pub struct XrplClient {
     rt: Runtime,
-    client: XrpLedgerApiServiceClient<tonic::transport::Channel>,
+    cassette: Option<Cassette>,
+    client: Option<Client>,
 }
 
 impl XrplClient {
@@ -45,31 +81,115 @@ impl XrplClient {
             .basic_scheduler()
             .enable_all()
             .build()
-            .unwrap();
+            .map_err(|e| Error::Transport(e.to_string()))?;
         let client = rt.block_on(XrpLedgerApiServiceClient::connect(url))?;
-        Self { rt, client }
+        Self {
+            rt,
+            cassette: None,
+            client: Some(client),
+        }
+    }
+
+    /// Connects to `url` and records every exchange with the node to the file at `path`.
+    #[throws(_)]
+    pub(crate) fn record<D, P>(url: D, path: P) -> Self
+    where
+        D: std::convert::TryInto<tonic::transport::Endpoint>,
+        D::Error: Into<StdError>,
+        P: AsRef<Path>,
+    {
+        let mut client = Self::connect(url)?;
+        client.cassette = Some(Cassette::record(path)?);
+        client
+    }
+
+    /// Answers every request from the recording at `path` without connecting to a node.
+    #[throws(_)]
+    pub(crate) fn replay<P: AsRef<Path>>(path: P) -> Self {
+        let rt = Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .map_err(|e| Error::Transport(e.to_string()))?;
+        Self {
+            rt,
+            cassette: Some(Cassette::replay(path)?),
+            client: None,
+        }
+    }
+
+    fn replaying(&self) -> bool {
+        matches!(&self.cassette, Some(cassette) if cassette.is_replay())
+    }
+
+    /// Sends `request` to the node with `send`, or answers it from the recording when replaying.
+    /// Exchanges are recorded when recording.
+    #[throws(_)]
+    fn call<Req, Resp, F>(&mut self, method: &str, request: Req, send: F) -> Resp
+    where
+        Req: prost::Message,
+        Resp: prost::Message + Default,
+        F: FnOnce(
+            &mut Runtime,
+            &mut Client,
+            tonic::Request<Req>,
+        ) -> Result<tonic::Response<Resp>, tonic::Status>,
+    {
+        let mut encoded_request = Vec::with_capacity(request.encoded_len());
+        request
+            .encode(&mut encoded_request)
+            .map_err(|e| Error::Transport(e.to_string()))?;
+        if self.replaying() {
+            let cassette = self.cassette.as_mut().ok_or_else(|| missing("cassette"))?;
+            let response = cassette.next(method, &encoded_request)?;
+            return Resp::decode(&response?[..])
+                .map_err(|e| Error::Decode(format!("Invalid recorded response: {}", e)))?;
+        }
+        let client = self
+            .client
+            .as_mut()
+            .ok_or_else(|| Error::Transport("Not connected to a node".to_owned()))?;
+        let response = send(&mut self.rt, client, tonic::Request::new(request))
+            .map(tonic::Response::into_inner);
+        if let Some(cassette) = self.cassette.as_mut() {
+            let mut encoded_response = vec![];
+            if let Ok(response) = &response {
+                response
+                    .encode(&mut encoded_response)
+                    .map_err(|e| Error::Transport(e.to_string()))?;
+            }
+            cassette.write(
+                method,
+                &encoded_request,
+                response.as_ref().map(|_| &encoded_response[..]),
+            )?;
+        }
+        response?
     }
 
     //TODO Tests
     #[throws(_)]
     pub(self) fn get_fees(&mut self) -> GetFeeResponse {
-        let request = tonic::Request::new(GetFeeRequest {});
-        let response = self.rt.block_on(self.client.get_fee(request))?;
-        response.into_inner()
+        self.call("GetFee", GetFeeRequest {}, |rt, client, request| {
+            rt.block_on(client.get_fee(request))
+        })?
     }
 
     #[throws(_)]
     #[allow(dead_code)]
     pub(self) fn get_base_fee(&mut self) -> u64 {
         let fees = self.get_fees()?;
-        fees.fee.unwrap().base_fee.unwrap().drops
+        let fee = fees.fee.ok_or_else(|| missing("fee"))?;
+        fee.base_fee.ok_or_else(|| missing("fee.base_fee"))?.drops
     }
 
     #[throws(_)]
-    #[allow(dead_code)]
     pub(self) fn get_open_ledger_fee(&mut self) -> u64 {
         let fees = self.get_fees()?;
-        fees.fee.unwrap().open_ledger_fee.unwrap().drops
+        let fee = fees.fee.ok_or_else(|| missing("fee"))?;
+        fee.open_ledger_fee
+            .ok_or_else(|| missing("fee.open_ledger_fee"))?
+            .drops
     }
 
     #[throws(_)]
@@ -79,41 +199,297 @@ impl XrplClient {
     }
 
     #[throws(_)]
-    pub(self) fn get_account_info(&mut self, address: &str) -> AccountRoot {
-        let request = tonic::Request::new(GetAccountInfoRequest {
+    fn account_info_response(
+        &mut self,
+        address: &str,
+        signer_lists: bool,
+    ) -> GetAccountInfoResponse {
+        let request = GetAccountInfoRequest {
             account: Some(AccountAddress {
                 address: address.to_owned(),
             }),
-            signer_lists: false, //TODO
-            strict: false,       //TODO
-            ledger: None,        //TODO
-            queue: false,        //TODO
-        });
-        let response = self.rt.block_on(self.client.get_account_info(request))?;
-        response.into_inner().account_data.unwrap()
+            signer_lists,
+            strict: false, //TODO
+            ledger: None,  //TODO
+            queue: false,  //TODO
+        };
+        self.call("GetAccountInfo", request, |rt, client, request| {
+            rt.block_on(client.get_account_info(request))
+        })?
+    }
+
+    #[throws(_)]
+    pub(self) fn get_account_info(&mut self, address: &str) -> AccountRoot {
+        self.account_info_response(address, false)?
+            .account_data
+            .ok_or_else(|| missing("account_data"))?
+    }
+
+    #[throws(_)]
+    pub(crate) fn get_account_root(
+        &mut self,
+        jscontext: &JavaScript,
+        x_address: &str,
+    ) -> XAccountRoot {
+        let decoded_address = address::decode_x_address(jscontext, x_address)?;
+        XAccountRoot::from_proto(&self.get_account_info(&decoded_address.address)?)
     }
 
     #[throws(_)]
-    pub(self) fn get_account_sequence(
+    pub(crate) fn get_signer_list(
         &mut self,
-        jscontext: &mut JavaScript,
+        jscontext: &JavaScript,
         x_address: &str,
-    ) -> u32 {
+    ) -> Option<XSignerList> {
+        let decoded_address = address::decode_x_address(jscontext, x_address)?;
+        self.account_info_response(&decoded_address.address, true)?
+            .signer_list
+            .as_ref()
+            .map(XSignerList::from_proto)
+    }
+
+    /// The objects an account owns or is the destination of, in the order of their indexes.
+    ///
+    /// The node has no query for the owner directory of an account, so the objects are
+    /// reconstructed from the metadata of the account's validated transactions, newest first,
+    /// back to the one that created the account: the latest state of every object they touched,
+    /// without the deleted ones. When the history of the node does not reach that transaction,
+    /// the objects cannot be known and `IncompleteHistory` is returned.
+    #[throws(_)]
+    pub(crate) fn get_account_objects(
+        &mut self,
+        jscontext: &JavaScript,
+        x_address: &str,
+    ) -> XAccountObjects {
+        // Fails with `NotFound` for accounts that do not exist, which have no history.
+        let signer_list = self.get_signer_list(jscontext, x_address)?;
+        let account = address::decode_x_address(jscontext, x_address)?.address;
+        let is_creation = |node: &AffectedNode| match &node.node {
+            Some(affected_node::Node::CreatedNode(created)) => matches!(
+                created.new_fields.as_ref().and_then(XLedgerObject::from_proto),
+                Some(XLedgerObject::AccountRoot(root)) if root.account.as_deref() == Some(&account)
+            ),
+            _ => false,
+        };
+        // Latest state by index; `None` for deleted objects.
+        let mut latest: BTreeMap<Vec<u8>, Option<LedgerObject>> = BTreeMap::new();
+        let mut marker = None;
+        'pages: loop {
+            let request = GetAccountTransactionHistoryRequest {
+                account: Some(AccountAddress {
+                    address: account.clone(),
+                }),
+                ledger: None,
+                binary: false,
+                forward: false,
+                limit: 0,
+                marker,
+            };
+            let response = self.call(
+                "GetAccountTransactionHistory",
+                request,
+                |rt, client, request| rt.block_on(client.get_account_transaction_history(request)),
+            )?;
+            for transaction in response.transactions.into_iter().filter(|t| t.validated) {
+                let meta = match transaction.serialized_meta {
+                    Some(get_transaction_response::SerializedMeta::Meta(meta)) => meta,
+                    _ => continue,
+                };
+                let created = meta.affected_nodes.iter().any(is_creation);
+                // Within a transaction every object is affected once; across transactions the
+                // newest one, seen first, wins.
+                for node in meta.affected_nodes {
+                    let state = match node.node {
+                        Some(affected_node::Node::CreatedNode(n)) => n.new_fields,
+                        Some(affected_node::Node::ModifiedNode(n)) => n.final_fields,
+                        Some(affected_node::Node::DeletedNode(_)) | None => None,
+                    };
+                    latest.entry(node.ledger_index).or_insert(state);
+                }
+                // Older transactions are about a deleted account of the same address, whose
+                // objects were deleted with it.
+                if created {
+                    break 'pages;
+                }
+            }
+            if response.marker.is_none() {
+                throw!(Error::IncompleteHistory(format!(
+                    "The history of the node starts at ledger {}, after the creation of {}",
+                    response.ledger_index_min, account
+                )));
+            }
+            marker = response.marker;
+        }
+        let mut objects = XAccountObjects::default();
+        for (index, state) in latest {
+            if let Some(object) = state.as_ref().and_then(XLedgerObject::from_proto) {
+                objects.add(&account, hex::encode_upper(index), object);
+            }
+        }
+        objects.signer_list = signer_list;
+        objects
+    }
+
+    /// Status of a transaction, or `None` while the node does not know it.
+    #[throws(_)]
+    fn find_raw_transaction_status(
+        &mut self,
+        transaction_hash: &str,
+    ) -> Option<XRawTransactionStatus> {
+        match self.get_raw_transaction_status(transaction_hash) {
+            Ok(status) => Some(status),
+            Err(Error::Rpc {
+                code: tonic::Code::NotFound,
+                ..
+            }) => None,
+            Err(e) => throw!(e),
+        }
+    }
+
+    /// Whether the account exists in the ledger.
+    #[throws(_)]
+    pub(self) fn is_funded(&mut self, address: &str) -> bool {
+        match self.get_account_info(address) {
+            Ok(_) => true,
+            Err(Error::Rpc {
+                code: tonic::Code::NotFound,
+                ..
+            }) => false,
+            Err(e) => throw!(e),
+        }
+    }
+
+    /// Returns the funded wallets of `account`, scanning its addresses until `gap_limit`
+    /// consecutive ones are not funded.
+    #[throws(_)]
+    fn discover_account(
+        &mut self,
+        jscontext: &JavaScript,
+        mnemonic: &Mnemonic,
+        account: u32,
+        gap_limit: u32,
+        test: bool,
+    ) -> Vec<XDerivedWallet> {
+        let mut funded = Vec::new();
+        let mut unfunded = 0;
+        let mut next = 0;
+        while unfunded < gap_limit && next <= MAX_DERIVATION_INDEX {
+            // Derives a batch at most, and no more addresses than it takes to reach the gap
+            // limit if none of them is funded.
+            let end = next
+                .saturating_add((gap_limit - unfunded).min(DISCOVERY_BATCH))
+                .min(MAX_DERIVATION_INDEX + 1);
+            for derived in wallet::derive_range(jscontext, mnemonic, account, next..end, test)? {
+                if self.is_funded(&derived.classic_address)? {
+                    unfunded = 0;
+                    funded.push(derived);
+                } else {
+                    unfunded += 1;
+                }
+            }
+            next = end;
+        }
+        funded
+    }
+
+    /// BIP44 discovery: scans accounts in order and stops at the first one without funded
+    /// addresses.
+    #[throws(_)]
+    pub(crate) fn discover_wallets(
+        &mut self,
+        jscontext: &JavaScript,
+        mnemonic: &Mnemonic,
+        gap_limit: u32,
+        test: bool,
+    ) -> Vec<XDerivedWallet> {
+        if gap_limit == 0 {
+            throw!(Error::Validation(
+                "The gap limit must be at least 1".to_owned()
+            ));
+        }
+        let mut funded = Vec::new();
+        for account in 0..=MAX_DERIVATION_INDEX {
+            let found = self.discover_account(jscontext, mnemonic, account, gap_limit, test)?;
+            if found.is_empty() {
+                break;
+            }
+            funded.extend(found);
+        }
+        funded
+    }
+
+    /// Verifies a proof of ownership at `now`, looking up the account of the claimed address for
+    /// its regular key and whether its master key is disabled.
+    #[throws(_)]
+    pub(crate) fn verify_ownership(
+        &mut self,
+        jscontext: &JavaScript,
+        proof: &XOwnershipProof,
+        issued: &XOwnershipChallenge,
+        now: u64,
+    ) -> XOwnershipVerification {
+        let (claimed, signer) = match challenge::check_signature(jscontext, proof, issued, now)? {
+            XSignedProof::Failed(failure) => return failure,
+            XSignedProof::Signed { claimed, signer } => (claimed, signer),
+        };
+        let account = match self.get_account_info(&claimed) {
+            Ok(account) => Some(account),
+            // An unfunded address can only be proven with its master key.
+            Err(Error::Rpc {
+                code: tonic::Code::NotFound,
+                ..
+            }) => None,
+            Err(e) => throw!(e),
+        };
+        let regular_key = account
+            .as_ref()
+            .and_then(|a| a.regular_key.as_ref())
+            .and_then(|k| k.value.as_ref())
+            .map(|k| k.address.as_str());
+        let master_disabled = account
+            .as_ref()
+            .and_then(|a| a.flags.as_ref())
+            .is_some_and(|f| f.value & LSF_DISABLE_MASTER != 0);
+        let key = if regular_key == Some(signer.as_str()) {
+            XOwnershipKey::Regular
+        } else if signer == claimed && master_disabled {
+            return XOwnershipVerification::MasterKeyDisabled;
+        } else if signer == claimed {
+            XOwnershipKey::Master
+        } else {
+            return XOwnershipVerification::UnauthorizedKey;
+        };
+        XOwnershipVerification::Verified {
+            classic_address: claimed,
+            key,
+        }
+    }
+
+    #[throws(_)]
+    pub(self) fn get_account_sequence(&mut self, jscontext: &JavaScript, x_address: &str) -> u32 {
         let decoded_address = address::decode_x_address(jscontext, x_address)?;
         let account_info = self.get_account_info(&decoded_address.address)?;
-        account_info.sequence.unwrap().value
+        account_info
+            .sequence
+            .ok_or_else(|| missing("account_data.sequence"))?
+            .value
     }
 
     #[throws(_)]
-    pub(crate) fn get_balance(&mut self, jscontext: &mut JavaScript, x_address: &str) -> f32 {
+    pub(crate) fn get_balance(&mut self, jscontext: &JavaScript, x_address: &str) -> f32 {
         let decoded_address = address::decode_x_address(jscontext, x_address)?;
         let response = self.get_account_info(&decoded_address.address)?;
-        if let currency_amount::Amount::XrpAmount(d) =
-            response.balance.unwrap().value.unwrap().amount.unwrap()
-        {
+        let amount = response
+            .balance
+            .and_then(|b| b.value)
+            .and_then(|v| v.amount)
+            .ok_or_else(|| missing("account_data.balance"))?;
+        if let currency_amount::Amount::XrpAmount(d) = amount {
             drops_to_decimal(d.drops)
         } else {
-            0.00
+            throw!(Error::Decode(
+                "Malformed response: account balance is not an XRP amount".to_owned()
+            ));
         }
     }
 
@@ -122,16 +498,17 @@ impl XrplClient {
         &mut self,
         transaction_hash: Vec<u8>,
     ) -> GetTransactionResponse {
-        let request = tonic::Request::new(GetTransactionRequest {
+        let request = GetTransactionRequest {
             hash: transaction_hash,
             binary: false,
             ledger_range: Some(LedgerRange {
                 ledger_index_min: 1,
                 ledger_index_max: 0, // all
             }),
-        });
-        let response = self.rt.block_on(self.client.get_transaction(request))?;
-        response.into_inner()
+        };
+        self.call("GetTransaction", request, |rt, client, request| {
+            rt.block_on(client.get_transaction(request))
+        })?
     }
 
     #[throws(_)]
@@ -139,26 +516,84 @@ impl XrplClient {
         &mut self,
         transaction_hash: &str,
     ) -> XRawTransactionStatus {
-        let trx_hash_vec = hex::decode(transaction_hash)?;
+        let trx_hash_vec = hex::decode(transaction_hash)
+            .map_err(|e| Error::Validation(format!("Invalid transaction hash: {}", e)))?;
         let response = self.get_raw_transaction(trx_hash_vec)?;
-        let last_ledger_sequence =
-            if let get_transaction_response::SerializedTransaction::Transaction(t) =
-                response.serialized_transaction.unwrap()
-            {
-                t.last_ledger_sequence.unwrap().value
-            } else {
-                0
-            };
-        if let get_transaction_response::SerializedMeta::Meta(c) = response.serialized_meta.unwrap()
+        // A missing last_ledger_sequence is reported as 0, which `send` refuses to wait on.
+        let last_ledger_sequence = match response.serialized_transaction {
+            Some(get_transaction_response::SerializedTransaction::Transaction(t)) => {
+                t.last_ledger_sequence.map_or(0, |l| l.value)
+            }
+            Some(_) => 0,
+            None => throw!(missing("transaction")),
+        };
+        if let get_transaction_response::SerializedMeta::Meta(c) =
+            response.serialized_meta.ok_or_else(|| missing("meta"))?
         {
             XRawTransactionStatus {
-                transaction_result: c.transaction_result.unwrap(),
+                transaction_result: c
+                    .transaction_result
+                    .ok_or_else(|| missing("meta.transaction_result"))?,
                 last_ledger_sequence,
                 validated: response.validated,
+                delivered_amount: c
+                    .delivered_amount
+                    .as_ref()
+                    .and_then(|d| d.value.as_ref())
+                    .and_then(XCurrencyAmount::from_proto),
             }
         } else {
-            bail!("Unknown raw transaction status");
+            throw!(Error::Decode("Unknown raw transaction status".to_owned()));
+        }
+    }
+
+    #[throws(_)]
+    pub(crate) fn get_incoming_payments(
+        &mut self,
+        jscontext: &JavaScript,
+        x_address: &str,
+        ledger_index_min: u32,
+        ledger_index_max: u32,
+    ) -> Vec<XIncomingPayment> {
+        let decoded_address = address::decode_x_address(jscontext, x_address)?;
+        let mut payments = Vec::new();
+        let mut marker = None;
+        loop {
+            let request = GetAccountTransactionHistoryRequest {
+                account: Some(AccountAddress {
+                    address: decoded_address.address.to_owned(),
+                }),
+                ledger: Some(
+                    get_account_transaction_history_request::Ledger::LedgerRange(LedgerRange {
+                        ledger_index_min,
+                        ledger_index_max,
+                    }),
+                ),
+                binary: false,
+                forward: true,
+                limit: 0,
+                marker,
+            };
+            let response = self.call(
+                "GetAccountTransactionHistory",
+                request,
+                |rt, client, request| rt.block_on(client.get_account_transaction_history(request)),
+            )?;
+            payments.extend(
+                response
+                    .transactions
+                    .iter()
+                    .filter_map(|t| transaction::incoming_payment(&decoded_address.address, t))
+                    .filter(|p| {
+                        decoded_address.tag.is_none() || p.destination_tag == decoded_address.tag
+                    }),
+            );
+            if response.marker.is_none() {
+                break;
+            }
+            marker = response.marker;
         }
+        payments
     }
 
     #[throws(_)]
@@ -170,75 +605,161 @@ impl XrplClient {
     #[throws(_)]
     pub(crate) fn send(
         &mut self,
-        jscontext: &mut JavaScript,
+        jscontext: &JavaScript,
         amount: f32,
         from_address: &str,
         to_address: &str,
         source_wallet: XWallet,
     ) -> XrplReliableSendResponse {
-        let ledger_close_time_seconds = 4;
-        let payment = XPayment {
-            amount: XAmount::new(amount),
-            from_address: from_address.to_owned(),
-            to_address: to_address.to_owned(),
-        };
-        if !address::is_valid_x_address(jscontext, &payment.to_address).unwrap()
-            || !address::is_valid_x_address(jscontext, &payment.from_address).unwrap()
+        let payment = XPayment::new(amount, from_address, to_address);
+        self.send_payment(jscontext, payment, source_wallet)?
+    }
+
+    /// Fetches the sequence and last ledger sequence of `payment`, the online part of signing it.
+    #[throws(_)]
+    fn prepare(
+        &mut self,
+        jscontext: &JavaScript,
+        mut payment: XPayment,
+        signing_public_key: Option<String>,
+        test: bool,
+    ) -> XUnsignedTransaction {
+        if !address::is_valid_x_address(jscontext, &payment.to_address)?
+            || !address::is_valid_x_address(jscontext, &payment.from_address)?
         {
-            bail!("Please use the X-Address format. See: https://xrpaddress.info.");
+            throw!(Error::Validation(
+                "Please use the X-Address format. See: https://xrpaddress.info.".to_owned()
+            ));
         }
-        let account_sequence = self.get_account_sequence(jscontext, &from_address)?;
+        let from_address = payment.from_address.clone();
+        transaction::resolve_destination(jscontext, &mut payment)?;
+        let sequence = self.get_account_sequence(jscontext, &from_address)?;
+        // Pay what gets the transaction into the open ledger, but never more than signing accepts.
+        let fee = self
+            .get_open_ledger_fee()?
+            .min(transaction::MAX_OFFLINE_FEE);
         let latest_ledger = self.get_latest_validated_ledger_sequence()?;
-        let last_validated_ledger_sequence = latest_ledger + config::MAX_LEDGER_VERSION_OFFSET;
-        let transaction = transaction::build_payment_transaction(
+        XUnsignedTransaction {
             payment,
-            12,
-            account_sequence,
-            last_validated_ledger_sequence,
-            &source_wallet,
-        )?;
+            fee,
+            sequence,
+            last_ledger_sequence: latest_ledger + config::MAX_LEDGER_VERSION_OFFSET,
+            signing_public_key,
+            test,
+        }
+    }
 
-        let signed_transaction =
-            transaction::sign_transaction(jscontext, &transaction, &source_wallet)?;
+    #[throws(_)]
+    pub(crate) fn prepare_payment(
+        &mut self,
+        jscontext: &JavaScript,
+        payment: XPayment,
+        wallet: &XWatchOnlyWallet,
+    ) -> XUnsignedTransaction {
+        if address::is_valid_x_address(jscontext, &payment.from_address)? {
+            let from_address = address::decode_x_address(jscontext, &payment.from_address)?;
+            if from_address.address != wallet.classic_address {
+                throw!(Error::Validation(format!(
+                    "The payment is not from the watched account {}",
+                    wallet.address
+                )));
+            }
+        }
+        self.prepare(jscontext, payment, wallet.public_key.clone(), wallet.test)?
+    }
 
-        let request = tonic::Request::new(SubmitTransactionRequest {
-            signed_transaction: hex::decode(signed_transaction.result).unwrap(),
+    #[throws(_)]
+    pub(crate) fn send_payment(
+        &mut self,
+        jscontext: &JavaScript,
+        payment: XPayment,
+        source_wallet: XWallet,
+    ) -> XrplReliableSendResponse {
+        let unsigned = self.prepare(
+            jscontext,
+            payment,
+            Some(source_wallet.public_key.clone()),
+            source_wallet.test,
+        )?;
+        let signed = transaction::sign_unsigned_transaction(jscontext, &unsigned, &source_wallet)?;
+        self.submit_transaction(jscontext, &signed, source_wallet.test)?
+    }
+
+    /// Submits a signed transaction and waits until it is validated or its last ledger sequence
+    /// has passed.
+    #[throws(_)]
+    pub(crate) fn submit_transaction(
+        &mut self,
+        jscontext: &JavaScript,
+        signed: &XSignedTransaction,
+        test: bool,
+    ) -> XrplReliableSendResponse {
+        if signed.test != test {
+            throw!(Error::Validation(format!(
+                "The transaction is not for the {} network",
+                if test { "test" } else { "main" }
+            )));
+        }
+        // The polling below relies on the last ledger sequence, so it is read from the blob.
+        let last_validated_ledger_sequence =
+            transaction::verify_signed_transaction(jscontext, signed)?;
+        let request = SubmitTransactionRequest {
+            signed_transaction: hex::decode(&signed.transaction_blob)
+                .map_err(|e| Error::Decode(format!("Invalid signed transaction: {}", e)))?,
             fail_hard: false,
-        });
-        let result = self.rt.block_on(self.client.submit_transaction(request))?;
-        let response = result.into_inner();
-        let result_transaction_status;
+        };
+        let response = self.call("SubmitTransaction", request, |rt, client, request| {
+            rt.block_on(client.submit_transaction(request))
+        })?;
         let result_transaction_hash = hex::encode(&response.hash).to_uppercase();
 
-        // The code tesSUCCESS is the only code that indicates a transaction succeeded
-        // any other prefix will mean our transaction run into a problem
-        let result_transaction_info = if !response.engine_result.unwrap().result.starts_with("tes")
+        // Only tem, tef and tel results keep the transaction out of every ledger. Any other
+        // result is provisional: a tec or ter transaction, including a queued one, can still be
+        // validated, so it is tracked like a tesSUCCESS one.
+        let engine_result = response
+            .engine_result
+            .ok_or_else(|| missing("engine_result"))?
+            .result;
+        let provisional = matches!(
+            EngineResultCategory::from_code(&engine_result),
+            Some(EngineResultCategory::Tec) | Some(EngineResultCategory::Ter)
+        );
+        if !engine_result.starts_with("tes") && !provisional {
+            throw!(Error::EngineResult {
+                code: engine_result,
+                message: response.engine_result_message,
+            });
+        }
+        // A queued or not yet applied transaction is unknown to the node until it reaches a
+        // ledger, so it is only missing once the last ledger sequence was validated.
+        let mut latest_validated_ledger_sequence = self.get_latest_validated_ledger_sequence()?;
+        let mut transaction_status = self.find_raw_transaction_status(&result_transaction_hash)?;
+        while latest_validated_ledger_sequence <= last_validated_ledger_sequence
+            && !matches!(transaction_status, Some(ref s) if s.validated)
         {
-            result_transaction_status = XTransactionStatus::FAILED;
-            response.engine_result_message
-        } else {
-            let mut latest_validated_ledger_sequence =
-                self.get_latest_validated_ledger_sequence()?;
-            let mut transaction_status =
-                self.get_raw_transaction_status(&result_transaction_hash)?;
-            while latest_validated_ledger_sequence <= last_validated_ledger_sequence
-                && !transaction_status.validated
-            {
-                thread::sleep(Duration::from_secs(ledger_close_time_seconds));
-                latest_validated_ledger_sequence = self.get_latest_validated_ledger_sequence()?;
-                transaction_status = self.get_raw_transaction_status(&result_transaction_hash)?;
-                if transaction_status.last_ledger_sequence == 0 {
-                    bail!("The transaction did not have a last_ledger_sequence field so transaction status cannot be reliably determined.");
-                }
+            // A replayed session already knows the outcome of every poll.
+            if !self.replaying() {
+                thread::sleep(LEDGER_CLOSE_TIME);
+            }
+            latest_validated_ledger_sequence = self.get_latest_validated_ledger_sequence()?;
+            transaction_status = self.find_raw_transaction_status(&result_transaction_hash)?;
+            if matches!(transaction_status, Some(ref s) if s.last_ledger_sequence == 0) {
+                throw!(Error::Decode("The transaction did not have a last_ledger_sequence field so transaction status cannot be reliably determined.".to_owned()));
+            }
+        }
+        let (result_transaction_status, result_delivered_amount) = match transaction_status {
+            Some(status) => {
+                let delivered_amount = status.delivered_amount.clone();
+                (transaction::from_raw_status(status), delivered_amount)
             }
-            result_transaction_status = transaction::from_raw_status(transaction_status);
-            "".to_owned()
+            None => (XTransactionStatus::FAILED, None),
         };
 
         XrplReliableSendResponse {
             transaction_status: result_transaction_status,
             transaction_hash: result_transaction_hash,
-            transaction_info: result_transaction_info,
+            transaction_info: response.engine_result_message,
+            delivered_amount: result_delivered_amount,
         }
     }
 }
@@ -247,14 +768,340 @@ impl XrplClient {
 mod tests {
 
     use super::*;
+    use crate::testing::{MockLedger, MockServer, MockTransaction};
     use crate::wallet::from_seed;
+    use crate::x::xrp_ledger_api_service_server::{XrpLedgerApiService, XrpLedgerApiServiceServer};
+    use crate::x::{
+        ledger_object, AffectedNode, Balance, Check, CreatedNode, DeletedNode, Escrow,
+        GetAccountInfoResponse, GetAccountTransactionHistoryResponse, HighLimit, LowLimit, Meta,
+        ModifiedNode, Offer, PayChannel, RippleState, SignerList, SignerQuorum,
+        SubmitTransactionResponse,
+    };
+    use tonic::{transport::Server, Request, Response, Status};
+
+    const TRANSACTION_HASH: &str =
+        "B171331C9B3D24ACF7ED35F9B6A63090D10048B56C8B187DE6EA71D0AB483086";
+
+    /// Ledger with the accounts used by these tests that validates every submission.
+    #[throws(_)]
+    fn mock_ledger() -> MockServer {
+        let ledger = MockLedger::new();
+        ledger
+            .account("rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7", 1_000_000_000, 1)
+            .account("r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4", 500_000_000, 7)
+            .submit_result("tesSUCCESS", "", TRANSACTION_HASH)
+            .transaction(TRANSACTION_HASH, MockTransaction::validated("tesSUCCESS"));
+        ledger.serve()?
+    }
+
+    /// Node answering every request with the same, possibly malformed, response.
+    #[derive(Clone, Default)]
+    struct MalformedNode {
+        account_info: GetAccountInfoResponse,
+        fee: GetFeeResponse,
+        transaction: GetTransactionResponse,
+        submit: SubmitTransactionResponse,
+        account_history: GetAccountTransactionHistoryResponse,
+    }
+
+    #[tonic::async_trait]
+    impl XrpLedgerApiService for MalformedNode {
+        async fn get_account_info(
+            &self,
+            _request: Request<GetAccountInfoRequest>,
+        ) -> Result<Response<GetAccountInfoResponse>, Status> {
+            Ok(Response::new(self.account_info.clone()))
+        }
+
+        async fn get_fee(
+            &self,
+            _request: Request<GetFeeRequest>,
+        ) -> Result<Response<GetFeeResponse>, Status> {
+            Ok(Response::new(self.fee.clone()))
+        }
+
+        async fn submit_transaction(
+            &self,
+            _request: Request<SubmitTransactionRequest>,
+        ) -> Result<Response<SubmitTransactionResponse>, Status> {
+            Ok(Response::new(self.submit.clone()))
+        }
+
+        async fn get_transaction(
+            &self,
+            _request: Request<GetTransactionRequest>,
+        ) -> Result<Response<GetTransactionResponse>, Status> {
+            Ok(Response::new(self.transaction.clone()))
+        }
+
+        async fn get_account_transaction_history(
+            &self,
+            _request: Request<GetAccountTransactionHistoryRequest>,
+        ) -> Result<Response<GetAccountTransactionHistoryResponse>, Status> {
+            Ok(Response::new(self.account_history.clone()))
+        }
+    }
+
+    /// Serves `node` on a free localhost port and returns its url.
+    #[throws(_)]
+    fn serve(node: MalformedNode) -> MockServer {
+        crate::testing::spawn(move |mut listener, signal| async move {
+            let _ = Server::builder()
+                .add_service(XrpLedgerApiServiceServer::new(node))
+                .serve_with_incoming_shutdown(listener.incoming(), async {
+                    let _ = signal.await;
+                })
+                .await;
+        })?
+    }
+
+    fn assert_missing(error: Error, field: &str) {
+        match error {
+            Error::Decode(message) => assert_eq!(
+                format!("Malformed response: missing field `{}`", field),
+                message
+            ),
+            _ => panic!("expected a decode error, got {:?}", error),
+        }
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_malformed_fee_response() {
+        let server = serve(MalformedNode::default())?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        assert_missing(client.get_base_fee().unwrap_err(), "fee");
+        assert_missing(client.get_open_ledger_fee().unwrap_err(), "fee");
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_malformed_account_info_response() {
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let address = "TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ";
+        let server = serve(MalformedNode::default())?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        assert_missing(
+            client.get_balance(&jscontext, address).unwrap_err(),
+            "account_data",
+        );
+
+        let mut node = MalformedNode::default();
+        node.account_info.account_data = Some(AccountRoot {
+            balance: Some(Balance { value: None }),
+            ..Default::default()
+        });
+        let server = serve(node)?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        assert_missing(
+            client.get_balance(&jscontext, address).unwrap_err(),
+            "account_data.balance",
+        );
+        assert_missing(
+            client
+                .get_account_sequence(&jscontext, address)
+                .unwrap_err(),
+            "account_data.sequence",
+        );
+    }
+
+    fn affected_node(
+        index: u8,
+        object: ledger_object::Object,
+        node: fn(Option<LedgerObject>) -> affected_node::Node,
+    ) -> AffectedNode {
+        AffectedNode {
+            ledger_entry_type: 0,
+            ledger_index: vec![index; 32],
+            node: Some(node(Some(LedgerObject {
+                object: Some(object),
+            }))),
+        }
+    }
+
+    fn created(new_fields: Option<LedgerObject>) -> affected_node::Node {
+        affected_node::Node::CreatedNode(CreatedNode { new_fields })
+    }
+
+    fn modified(final_fields: Option<LedgerObject>) -> affected_node::Node {
+        affected_node::Node::ModifiedNode(ModifiedNode {
+            final_fields,
+            ..Default::default()
+        })
+    }
+
+    fn deleted(final_fields: Option<LedgerObject>) -> affected_node::Node {
+        affected_node::Node::DeletedNode(DeletedNode { final_fields })
+    }
 
-    pub const DEFAULT_SERVER_URL: &str = "http://test.xrp.xpring.io:50051";
+    fn with_meta(validated: bool, affected_nodes: Vec<AffectedNode>) -> GetTransactionResponse {
+        GetTransactionResponse {
+            validated,
+            serialized_meta: Some(get_transaction_response::SerializedMeta::Meta(Meta {
+                affected_nodes,
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_get_account_objects() {
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let x_address = "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs";
+        let owner = "r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4";
+        let other = "rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7";
+        let account = |address: &str| {
+            Some(Account {
+                value: Some(AccountAddress {
+                    address: address.to_owned(),
+                }),
+            })
+        };
+        let destination = |address: &str| {
+            Some(Destination {
+                value: Some(AccountAddress {
+                    address: address.to_owned(),
+                }),
+            })
+        };
+        let check = |drops: f32| {
+            ledger_object::Object::Check(Check {
+                account: account(owner),
+                destination: destination(other),
+                send_max: Some(SendMax {
+                    value: Some(XCurrencyAmount::xrp(drops).to_proto()),
+                }),
+                ..Default::default()
+            })
+        };
+        let offer = ledger_object::Object::Offer(Offer {
+            account: account(owner),
+            sequence: Some(Sequence { value: 3 }),
+            ..Default::default()
+        });
+        let limit = |issuer: &str| XCurrencyAmount::issued("USD", issuer, "100").to_proto();
+        let trust_line = ledger_object::Object::RippleState(RippleState {
+            low_limit: Some(LowLimit {
+                value: Some(limit(other)),
+            }),
+            high_limit: Some(HighLimit {
+                value: Some(limit(owner)),
+            }),
+            ..Default::default()
+        });
+        let escrow = ledger_object::Object::Escrow(Escrow {
+            account: account(other),
+            destination: destination(owner),
+            ..Default::default()
+        });
+        let channel = ledger_object::Object::PayChannel(PayChannel {
+            account: account(other),
+            destination: destination(other),
+            ..Default::default()
+        });
+        let mut node = MalformedNode::default();
+        let root = ledger_object::Object::AccountRoot(AccountRoot {
+            account: account(owner),
+            ..Default::default()
+        });
+        // Newest transaction first.
+        node.account_history.transactions = vec![
+            // Not validated yet.
+            with_meta(false, vec![affected_node(3, escrow.clone(), deleted)]),
+            with_meta(
+                true,
+                vec![
+                    affected_node(1, offer.clone(), deleted),
+                    affected_node(2, check(5.0), modified),
+                ],
+            ),
+            with_meta(
+                true,
+                vec![
+                    affected_node(1, offer, created),
+                    affected_node(2, check(10.0), created),
+                    affected_node(3, escrow, created),
+                    affected_node(4, trust_line, created),
+                    affected_node(5, channel, created),
+                ],
+            ),
+            with_meta(true, vec![affected_node(9, root, created)]),
+            // Before the account was created: deleted with a previous account of the address.
+            with_meta(true, vec![affected_node(6, check(1.0), created)]),
+        ];
+        node.account_info.signer_list = Some(SignerList {
+            signer_quorum: Some(SignerQuorum { value: 2 }),
+            ..Default::default()
+        });
+
+        // Without the transaction that created the account, the objects cannot be known.
+        let mut partial = node.clone();
+        partial.account_history.transactions.truncate(3);
+        partial.account_history.ledger_index_min = 1000;
+        let server = serve(partial)?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        match client.get_account_objects(&jscontext, x_address) {
+            Err(Error::IncompleteHistory(message)) => assert!(message.contains("1000")),
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        let server = serve(node)?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        let objects = client.get_account_objects(&jscontext, x_address)?;
+        assert!(objects.offers.is_empty());
+        assert_eq!(objects.checks.len(), 1);
+        assert_eq!(objects.checks[0].index, "02".repeat(32));
+        assert_eq!(
+            objects.checks[0].object.send_max,
+            Some(XCurrencyAmount::xrp(5.0))
+        );
+        assert_eq!(objects.escrows.len(), 1);
+        assert_eq!(objects.escrows[0].index, "03".repeat(32));
+        assert_eq!(objects.trust_lines.len(), 1);
+        assert!(objects.pay_channels.is_empty());
+        assert!(objects.deposit_preauths.is_empty());
+        assert_eq!(objects.signer_list.and_then(|l| l.signer_quorum), Some(2));
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_malformed_transaction_response() {
+        let hash = "B171331C9B3D24ACF7ED35F9B6A63090D10048B56C8B187DE6EA71D0AB483086";
+        let server = serve(MalformedNode::default())?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        assert_missing(
+            client.get_transaction_status(hash).unwrap_err(),
+            "transaction",
+        );
+
+        let mut node = MalformedNode::default();
+        node.transaction.serialized_transaction = Some(
+            get_transaction_response::SerializedTransaction::Transaction(Transaction::default()),
+        );
+        let server = serve(node.clone())?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        assert_missing(client.get_transaction_status(hash).unwrap_err(), "meta");
+
+        node.transaction.serialized_meta = Some(get_transaction_response::SerializedMeta::Meta(
+            Default::default(),
+        ));
+        let server = serve(node)?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        assert_missing(
+            client.get_transaction_status(hash).unwrap_err(),
+            "meta.transaction_result",
+        );
+    }
 
     #[throws(_)]
     #[test]
     fn test_xrp_client_ok() {
-        match XrplClient::connect(DEFAULT_SERVER_URL) {
+        let server = mock_ledger()?;
+        match XrplClient::connect(server.url().to_owned()) {
             Ok(_result) => {
                 assert!(true);
             }
@@ -283,7 +1130,8 @@ mod tests {
     #[throws(_)]
     #[test]
     fn test_xpring_get_base_fee() {
-        let mut client = XrplClient::connect(DEFAULT_SERVER_URL)?;
+        let server = mock_ledger()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
         let response = client.get_base_fee().unwrap();
         assert_eq!(response, 10);
     }
@@ -291,38 +1139,69 @@ mod tests {
     #[throws(_)]
     #[test]
     fn test_xpring_get_balance() {
-        let mut client = XrplClient::connect(DEFAULT_SERVER_URL)?;
+        let server = mock_ledger()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let response = client
             .get_balance(
-                &mut jscontext,
+                &jscontext,
                 "TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ",
             )
             .unwrap();
         assert_eq!(response, 1000.00);
     }
 
+    #[throws(_)]
+    #[test]
+    fn test_discover_wallets() {
+        // Account 0 has funded addresses 0 and 3, account 1 address 2, and account 2 none.
+        let ledger = MockLedger::new();
+        ledger
+            .account("rHsMGQEkVNJmpGWs8XUBoTBiAAbwxZN5v3", 20_000_000, 1)
+            .account("rwT7dzQuZim2SdY1jGFGwpre4bh6xpr31a", 20_000_000, 1)
+            .account("rKA8dgjxEC2fv4aVR6tr5z6eVwBTspSLH5", 20_000_000, 1);
+        let server = ledger.serve()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let mnemonic = Mnemonic::new("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about");
+
+        let found = client.discover_wallets(&jscontext, &mnemonic, 3, true)?;
+        let found: Vec<_> = found.iter().map(|d| (d.account, d.index)).collect();
+        assert_eq!(found, vec![(0, 0), (0, 3), (1, 2)]);
+
+        // Address 3 is past the gap of two unfunded addresses.
+        let found = client.discover_wallets(&jscontext, &mnemonic, 2, true)?;
+        let found: Vec<_> = found.iter().map(|d| (d.account, d.index)).collect();
+        assert_eq!(found, vec![(0, 0)]);
+
+        assert!(matches!(
+            client.discover_wallets(&jscontext, &mnemonic, 0, true),
+            Err(Error::Validation(_))
+        ));
+    }
+
     #[throws(_)]
     #[test]
     fn test_xpring_raw_transaction_status() {
-        let mut client = XrplClient::connect(DEFAULT_SERVER_URL)?;
+        let server = mock_ledger()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let w = from_seed(
-            &mut jscontext,
+            &jscontext,
             "sn3UJSLzAEeAGcrK3nsQTDZW6KT92".to_string(),
             None,
             true,
         )?;
         let payment = client.send(
-            &mut jscontext,
+            &jscontext,
             12.12,
             "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
             "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
             w,
         )?;
-        thread::sleep(Duration::from_secs(4));
         let response = client.get_raw_transaction_status(&payment.transaction_hash);
         assert_eq!(
             response.unwrap().transaction_result.result.starts_with("t"),
@@ -333,17 +1212,18 @@ mod tests {
     #[throws(_)]
     #[test]
     fn test_send() {
-        let mut client = XrplClient::connect(DEFAULT_SERVER_URL)?;
+        let server = mock_ledger()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
         let out_dir = std::env::var("OUT_DIR").unwrap();
-        let mut jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
         let w = from_seed(
-            &mut jscontext,
+            &jscontext,
             "sn3UJSLzAEeAGcrK3nsQTDZW6KT92".to_string(),
             None,
             true,
         )?;
         match client.send(
-            &mut jscontext,
+            &jscontext,
             12.12,
             "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
             "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
@@ -358,4 +1238,398 @@ mod tests {
             }
         }
     }
+
+    /// Sends a payment to a ledger that answers the submission with `result` and validates the
+    /// transaction with `validated_result`.
+    fn send_with_result(
+        result: &str,
+        validated_result: &str,
+    ) -> Result<XrplReliableSendResponse, Error> {
+        let ledger = MockLedger::new();
+        ledger.transaction(
+            TRANSACTION_HASH,
+            MockTransaction::validated(validated_result),
+        );
+        send_to(&ledger, result)
+    }
+
+    /// Sends a payment to `ledger`, which answers the submission with `result`.
+    fn send_to(ledger: &MockLedger, result: &str) -> Result<XrplReliableSendResponse, Error> {
+        ledger
+            .account("rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7", 1_000_000_000, 1)
+            .account("r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4", 500_000_000, 7)
+            .submit_result(result, "engine message", TRANSACTION_HASH);
+        let server = ledger.serve()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let w = from_seed(
+            &jscontext,
+            "sn3UJSLzAEeAGcrK3nsQTDZW6KT92".to_string(),
+            None,
+            true,
+        )?;
+        client.send(
+            &jscontext,
+            12.12,
+            "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+            "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
+            w,
+        )
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_send_waits_for_queued_transaction() {
+        // The node only knows the transaction once it reaches a ledger.
+        let ledger = MockLedger::new();
+        let validated = MockTransaction {
+            last_ledger_sequence: 1004,
+            ..MockTransaction::validated("tesSUCCESS")
+        };
+        ledger
+            .transaction(TRANSACTION_HASH, validated)
+            .unknown_for(TRANSACTION_HASH, 3);
+        let response = send_to(&ledger, "terQUEUED")?;
+        assert_eq!(response.transaction_status, XTransactionStatus::SUCCEEDED);
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_send_expired_transaction() {
+        let ledger = MockLedger::new();
+        ledger.submit_result("terQUEUED", "", TRANSACTION_HASH);
+        let server = ledger.serve()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let w = from_seed(
+            &jscontext,
+            "sn3UJSLzAEeAGcrK3nsQTDZW6KT92".to_string(),
+            None,
+            true,
+        )?;
+        // The ledger is at 1000, so a transaction it does not know is never going to be
+        // validated.
+        let unsigned = XUnsignedTransaction {
+            payment: XPayment::new(
+                12.12,
+                "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+                "rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7",
+            ),
+            fee: 12,
+            sequence: 1,
+            last_ledger_sequence: 999,
+            signing_public_key: None,
+            test: true,
+        };
+        let signed = transaction::sign_unsigned_transaction(&jscontext, &unsigned, &w)?;
+        let response = client.submit_transaction(&jscontext, &signed, true)?;
+        assert_eq!(response.transaction_status, XTransactionStatus::FAILED);
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_send_tracks_provisional_results() {
+        let queued = send_with_result("terQUEUED", "tesSUCCESS")?;
+        assert_eq!(queued.transaction_status, XTransactionStatus::SUCCEEDED);
+        assert_eq!(queued.transaction_info, "engine message");
+        let claimed = send_with_result("tecUNFUNDED_PAYMENT", "tecUNFUNDED_PAYMENT")?;
+        assert_eq!(claimed.transaction_status, XTransactionStatus::FAILED);
+        assert_eq!(claimed.transaction_info, "engine message");
+    }
+
+    #[test]
+    fn test_send_rejected() {
+        match send_with_result("temBAD_FEE", "tesSUCCESS") {
+            Err(Error::EngineResult { code, message }) => {
+                assert_eq!(code, "temBAD_FEE");
+                assert_eq!(message, "engine message");
+            }
+            result => panic!("expected an engine result error, got {:?}", result),
+        }
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_prepare_and_sign_payment() {
+        let server = mock_ledger()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let w = from_seed(
+            &jscontext,
+            "sn3UJSLzAEeAGcrK3nsQTDZW6KT92".to_string(),
+            None,
+            true,
+        )?;
+        let watch_only =
+            wallet::watch_only_from_public_key(&jscontext, w.public_key.clone(), true)?;
+        let payment = XPayment::new(
+            12.12,
+            "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+            "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
+        );
+        let unsigned = client.prepare_payment(&jscontext, payment, &watch_only)?;
+        assert_eq!(unsigned.sequence, 7);
+        assert_eq!(
+            unsigned.last_ledger_sequence,
+            client.get_latest_validated_ledger_sequence()? + config::MAX_LEDGER_VERSION_OFFSET
+        );
+        assert_eq!(
+            unsigned.payment.to_address,
+            "rY4KGXL6ZL1P68rGoHN4y6iWnjM7Xj2kc"
+        );
+        assert_eq!(unsigned.payment.destination_tag, Some(13));
+        assert_eq!(unsigned.signing_public_key.as_ref(), Some(&w.public_key));
+
+        let signed = transaction::sign_unsigned_transaction(&jscontext, &unsigned, &w)?;
+        assert_eq!(signed.transaction_hash.len(), 64);
+        assert_eq!(signed.last_ledger_sequence, unsigned.last_ledger_sequence);
+
+        let other = from_seed(
+            &jscontext,
+            "snYP7oArxKepd3GPDcrjMsJYiJeJB".to_string(),
+            None,
+            true,
+        )?;
+        assert!(matches!(
+            transaction::sign_unsigned_transaction(&jscontext, &unsigned, &other),
+            Err(Error::Validation(_))
+        ));
+
+        let receiver = wallet::watch_only_from_address(
+            &jscontext,
+            "rUBs6nryaaFYJ3Y2CPL8miuhSdNgThrqg7",
+            true,
+        )?;
+        let payment = XPayment::new(
+            12.12,
+            "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+            "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
+        );
+        assert!(matches!(
+            client.prepare_payment(&jscontext, payment, &receiver),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_prepare_uses_open_ledger_fee() {
+        let ledger = MockLedger::new();
+        ledger
+            .account("r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4", 500_000_000, 7)
+            .fees(10, 5000);
+        let server = ledger.serve()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let watch_only = wallet::watch_only_from_address(
+            &jscontext,
+            "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+            true,
+        )?;
+        let payment = XPayment::new(
+            12.12,
+            "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+            "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
+        );
+        let unsigned = client.prepare_payment(&jscontext, payment.clone(), &watch_only)?;
+        assert_eq!(unsigned.fee, 5000);
+
+        // A congested open ledger does not produce a transaction that cannot be signed.
+        ledger.fees(10, transaction::MAX_OFFLINE_FEE * 3);
+        let unsigned = client.prepare_payment(&jscontext, payment, &watch_only)?;
+        assert_eq!(unsigned.fee, transaction::MAX_OFFLINE_FEE);
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_offline_workflow() {
+        let server = mock_ledger()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let watch_only = wallet::watch_only_from_address(
+            &jscontext,
+            "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+            true,
+        )?;
+        let payment = XPayment::new(
+            12.12,
+            "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+            "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU",
+        );
+        let unsigned_file = client
+            .prepare_payment(&jscontext, payment, &watch_only)?
+            .to_json()?;
+
+        // The signing machine only has the file and the wallet.
+        let w = from_seed(
+            &jscontext,
+            "sn3UJSLzAEeAGcrK3nsQTDZW6KT92".to_string(),
+            None,
+            true,
+        )?;
+        let unsigned = XUnsignedTransaction::from_json(&unsigned_file)?;
+        let signed_file =
+            transaction::sign_unsigned_transaction(&jscontext, &unsigned, &w)?.to_json()?;
+
+        let signed = XSignedTransaction::from_json(&signed_file)?;
+        assert!(matches!(
+            client.submit_transaction(&jscontext, &signed, false),
+            Err(Error::Validation(_))
+        ));
+        // The fields next to the blob must match it.
+        let mut later = signed.clone();
+        later.last_ledger_sequence += 100;
+        assert!(matches!(
+            client.submit_transaction(&jscontext, &later, true),
+            Err(Error::Validation(_))
+        ));
+        let mut other = signed.clone();
+        other.transaction_hash = TRANSACTION_HASH.to_owned();
+        assert!(matches!(
+            client.submit_transaction(&jscontext, &other, true),
+            Err(Error::Validation(_))
+        ));
+        let result = client.submit_transaction(&jscontext, &signed, true)?;
+        assert_eq!(result.transaction_status, XTransactionStatus::SUCCEEDED);
+        assert_eq!(result.transaction_hash, TRANSACTION_HASH);
+
+        let main_wallet = from_seed(
+            &jscontext,
+            "sn3UJSLzAEeAGcrK3nsQTDZW6KT92".to_string(),
+            None,
+            false,
+        )?;
+        assert!(matches!(
+            transaction::sign_unsigned_transaction(&jscontext, &unsigned, &main_wallet),
+            Err(Error::Validation(_))
+        ));
+        let mut expensive = unsigned.clone();
+        expensive.fee = transaction::MAX_OFFLINE_FEE + 1;
+        assert!(matches!(
+            transaction::sign_unsigned_transaction(&jscontext, &expensive, &w),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_record_and_replay() {
+        let path = std::env::temp_dir().join(format!("xpring-session-{}", std::process::id()));
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let session = |client: &mut XrplClient, jscontext: &JavaScript| {
+            let balance =
+                client.get_balance(jscontext, "TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ")?;
+            let unknown =
+                client.get_balance(jscontext, "T7QqSicoC1nB4YRyzWzctWW7KjwiYUtDzVaLwFd4N7W1AUU");
+            assert!(matches!(
+                unknown,
+                Err(Error::Rpc {
+                    code: tonic::Code::NotFound,
+                    ..
+                })
+            ));
+            let w = from_seed(
+                jscontext,
+                "sn3UJSLzAEeAGcrK3nsQTDZW6KT92".to_string(),
+                None,
+                true,
+            )?;
+            let sent = client.send(
+                jscontext,
+                12.12,
+                "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+                "TVr7v7JGN5suv7Zgdu9aL4PtCkwayZNYWvjSG23uMMWMvzZ",
+                w,
+            )?;
+            Ok::<_, Error>((balance, sent))
+        };
+
+        let server = mock_ledger()?;
+        let mut client = XrplClient::record(server.url().to_owned(), &path)?;
+        let recorded = session(&mut client, &jscontext)?;
+        drop(client);
+        drop(server);
+
+        let mut client = XrplClient::replay(&path)?;
+        assert_eq!(session(&mut client, &jscontext)?, recorded);
+        assert!(matches!(client.get_fees(), Err(Error::Transport(_))));
+
+        // Requests that differ from the recording are rejected.
+        let mut client = XrplClient::replay(&path)?;
+        let other = client.get_balance(
+            &jscontext,
+            "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+        );
+        assert!(matches!(other, Err(Error::Transport(_))));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[throws(_)]
+    #[test]
+    fn test_verify_ownership() {
+        let ledger = MockLedger::new();
+        let address = "r32zXoA8TotxGLHXL9vvwZUAUnfUu6u7Y4";
+        ledger.account(address, 500_000_000, 7);
+        let server = ledger.serve()?;
+        let mut client = XrplClient::connect(server.url().to_owned())?;
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let jscontext = JavaScript::new(format!("{}/xpring.js", out_dir))?;
+        let wallet = |seed: &str| from_seed(&jscontext, seed.to_owned(), None, true);
+        let master = wallet("sn3UJSLzAEeAGcrK3nsQTDZW6KT92")?;
+        let regular = wallet("snYP7oArxKepd3GPDcrjMsJYiJeJB")?;
+        let stranger = wallet("sp6JS7f14BuwFY8Mw6bTtLKWauoUs")?;
+        let regular_address =
+            wallet::watch_only_from_public_key(&jscontext, regular.public_key.clone(), true)?
+                .classic_address;
+        ledger.regular_key(address, &regular_address);
+
+        let lifetime = Duration::from_secs(300);
+        let verify = |client: &mut XrplClient, claimed: &str, signer: &XWallet| {
+            let issued = challenge::issue(&jscontext, claimed, "example.com", lifetime, true)?;
+            let proof = challenge::prove(&jscontext, &issued, signer)?;
+            client.verify_ownership(&jscontext, &proof, &issued, challenge::now())
+        };
+        let verified = |classic_address: &str, key| XOwnershipVerification::Verified {
+            classic_address: classic_address.to_owned(),
+            key,
+        };
+        assert_eq!(
+            verify(
+                &mut client,
+                "T7o8yn1iXP9wC1vgCuCpYYQoVt4qqua87AJRy5nZwbaCLDs",
+                &master
+            )?,
+            verified(address, XOwnershipKey::Master)
+        );
+        assert_eq!(
+            verify(&mut client, address, &regular)?,
+            verified(address, XOwnershipKey::Regular)
+        );
+        assert_eq!(
+            verify(&mut client, address, &stranger)?,
+            XOwnershipVerification::UnauthorizedKey
+        );
+        // The master key of an unfunded address proves its ownership, no other key does.
+        assert_eq!(
+            verify(&mut client, &regular_address, &regular)?,
+            verified(&regular_address, XOwnershipKey::Master)
+        );
+        assert_eq!(
+            verify(&mut client, &regular_address, &master)?,
+            XOwnershipVerification::UnauthorizedKey
+        );
+
+        ledger.account_flags(address, LSF_DISABLE_MASTER);
+        assert_eq!(
+            verify(&mut client, address, &master)?,
+            XOwnershipVerification::MasterKeyDisabled
+        );
+        assert!(verify(&mut client, address, &regular)?.is_verified());
+    }
 }